@@ -0,0 +1,33 @@
+//! clipboard access for window backends, as egui can't access the clipboard by itself.
+//!
+//! Window backends read the clipboard for the paste shortcut (see [`keyboard::KeyboardShortcuts`]) and write the
+//! `copied_text` of [`egui::PlatformOutput`] into it. They use the system clipboard by default. Set the `clipboard`
+//! field of the window backend's configuration to use your own provider instead (eg: [`MemoryClipboard`] in tests),
+//! and use it yourself with [`WindowBackend::get_clipboard`].
+//!
+//! [`keyboard::KeyboardShortcuts`]: crate::keyboard::KeyboardShortcuts
+//! [`WindowBackend::get_clipboard`]: crate::WindowBackend::get_clipboard
+
+/// text clipboard. Providers log their errors, as callers can't do anything about them anyway.
+pub trait ClipboardProvider {
+    /// `None` if the clipboard is empty, doesn't have text or on error.
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: &str);
+}
+
+/// in-process clipboard which never touches the system clipboard.
+/// used by the headless backend, and as a fallback when the system clipboard is unavailable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryClipboard {
+    pub text: Option<String>,
+}
+
+impl ClipboardProvider for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.text = Some(text.to_string());
+    }
+}
@@ -0,0 +1,188 @@
+//! gamepad (game controller) input which is common to all window backends.
+//!
+//! Window backends which support gamepads keep a [`GamepadState`] for every connected gamepad.
+//! Get them with [`WindowBackend::gamepads`] inside [`UserApp::run`] for things like camera controls.
+//!
+//! If [`BackendConfig::gamepad_navigation`] is enabled, window backends also send egui key events for
+//! some of the buttons (see [`navigation_event`]), so that you can use egui's focus navigation with a gamepad.
+//!
+//! [`WindowBackend::gamepads`]: crate::WindowBackend::gamepads
+//! [`UserApp::run`]: crate::UserApp::run
+//! [`BackendConfig::gamepad_navigation`]: crate::BackendConfig::gamepad_navigation
+use egui::{Event, Key, Modifiers};
+
+/// buttons of a gamepad. The face buttons are named by their position, as their labels depend on the brand.
+/// eg: [`Self::South`] is `A` on xbox and `Cross` on playstation controllers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    Back,
+    Start,
+    Guide,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl GamepadButton {
+    pub const COUNT: usize = 15;
+    /// every button, in index order
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::South,
+        Self::East,
+        Self::West,
+        Self::North,
+        Self::LeftBumper,
+        Self::RightBumper,
+        Self::Back,
+        Self::Start,
+        Self::Guide,
+        Self::LeftThumb,
+        Self::RightThumb,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+    ];
+}
+
+/// axes of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl GamepadAxis {
+    pub const COUNT: usize = 6;
+    /// every axis, in index order
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::LeftX,
+        Self::LeftY,
+        Self::RightX,
+        Self::RightY,
+        Self::LeftTrigger,
+        Self::RightTrigger,
+    ];
+}
+
+/// The state of a connected gamepad.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GamepadState {
+    /// identifies the gamepad as long as it is connected. eg: the instance id in sdl2 or the joystick id in glfw.
+    pub id: u32,
+    pub name: String,
+    /// indexed by [`GamepadButton`]. use [`Self::is_pressed`]
+    pub buttons: [bool; GamepadButton::COUNT],
+    /// indexed by [`GamepadAxis`]. use [`Self::axis`]
+    pub axes: [f32; GamepadAxis::COUNT],
+}
+
+impl GamepadState {
+    pub fn new(id: u32, name: String) -> Self {
+        Self {
+            id,
+            name,
+            ..Default::default()
+        }
+    }
+    pub fn is_pressed(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize]
+    }
+    /// sticks are in the range `-1.0..=1.0`, where positive y is down. triggers are in the range `0.0..=1.0`.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+}
+
+/// maps the button to the key which is used by egui's focus navigation.
+/// * dpad down/right: `Tab` (focus next widget)
+/// * dpad up/left: `Shift + Tab` (focus previous widget)
+/// * south: `Enter` (click the focused widget)
+/// * east: `Escape` (unfocus)
+///
+/// returns `None` for the rest of the buttons.
+pub fn navigation_event(button: GamepadButton, pressed: bool) -> Option<Event> {
+    let (key, shift) = match button {
+        GamepadButton::DPadDown | GamepadButton::DPadRight => (Key::Tab, false),
+        GamepadButton::DPadUp | GamepadButton::DPadLeft => (Key::Tab, true),
+        GamepadButton::South => (Key::Enter, false),
+        GamepadButton::East => (Key::Escape, false),
+        _ => return None,
+    };
+    Some(Event::Key {
+        key,
+        pressed,
+        repeat: false,
+        modifiers: Modifiers {
+            shift,
+            ..Default::default()
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(key: Key, shift: bool) -> Option<Event> {
+        Some(Event::Key {
+            key,
+            pressed: true,
+            repeat: false,
+            modifiers: Modifiers {
+                shift,
+                ..Default::default()
+            },
+        })
+    }
+
+    #[test]
+    fn navigation_events() {
+        use GamepadButton::*;
+        for button in GamepadButton::ALL {
+            let expected = match button {
+                DPadDown | DPadRight => key(Key::Tab, false),
+                DPadUp | DPadLeft => key(Key::Tab, true),
+                South => key(Key::Enter, false),
+                East => key(Key::Escape, false),
+                _ => None,
+            };
+            assert_eq!(navigation_event(button, true), expected, "{button:?}");
+        }
+        assert_eq!(
+            navigation_event(South, false),
+            Some(Event::Key {
+                key: Key::Enter,
+                pressed: false,
+                repeat: false,
+                modifiers: Modifiers::NONE,
+            })
+        );
+    }
+
+    #[test]
+    fn counts_match_enums() {
+        // the last variant must be the last index, so that no variant is out of bounds
+        assert_eq!(GamepadButton::DPadRight as usize + 1, GamepadButton::COUNT);
+        assert_eq!(GamepadAxis::RightTrigger as usize + 1, GamepadAxis::COUNT);
+        for (i, button) in GamepadButton::ALL.into_iter().enumerate() {
+            assert_eq!(button as usize, i);
+        }
+        for (i, axis) in GamepadAxis::ALL.into_iter().enumerate() {
+            assert_eq!(axis as usize, i);
+        }
+    }
+}
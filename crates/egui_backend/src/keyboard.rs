@@ -0,0 +1,287 @@
+//! keyboard input which is common to all window backends.
+//!
+//! Window backends only map their native key codes to egui's [`Key`] and their modifier state with [`modifiers`].
+//! Then, [`KeyboardShortcuts::push_key_events`] turns that [`KeyInput`] into egui events, including the
+//! clipboard shortcuts configured in [`BackendConfig::shortcuts`]. Text input goes through [`text_event`].
+//!
+//! [`BackendConfig::shortcuts`]: crate::BackendConfig::shortcuts
+use egui::{Event, Key, KeyboardShortcut, Modifiers};
+
+/// converts the modifier state of a window backend into egui's modifiers.
+/// egui's `command` is the `cmd` key on mac and `ctrl` everywhere else. `logo` is the cmd / windows / super key.
+pub fn modifiers(alt: bool, ctrl: bool, shift: bool, logo: bool) -> Modifiers {
+    let mac = cfg!(target_os = "macos");
+    Modifiers {
+        alt,
+        ctrl,
+        shift,
+        mac_cmd: mac && logo,
+        command: if mac { logo } else { ctrl },
+    }
+}
+
+/// a key press or release, after the window backend mapped its native key code to [`Key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyInput {
+    pub key: Key,
+    pub pressed: bool,
+    pub repeat: bool,
+    pub modifiers: Modifiers,
+}
+
+/// shortcuts which need the clipboard. egui can't access the clipboard, so window backends handle these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShortcutAction {
+    /// sends [`Event::Copy`]
+    Copy,
+    /// sends [`Event::Cut`]
+    Cut,
+    /// sends [`Event::Paste`] with the clipboard text
+    Paste,
+}
+
+/// the key bindings of [`ShortcutAction`]s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardShortcuts {
+    /// checked in order and the first match wins. Modifiers are matched with [`Self::matches`].
+    pub bindings: Vec<(KeyboardShortcut, ShortcutAction)>,
+}
+
+impl Default for KeyboardShortcuts {
+    /// `command + C/X/V`. And `command + Insert`, `shift + Delete` and `shift + Insert` on non-mac platforms.
+    fn default() -> Self {
+        let mut bindings = vec![
+            (
+                KeyboardShortcut::new(Modifiers::COMMAND, Key::C),
+                ShortcutAction::Copy,
+            ),
+            (
+                KeyboardShortcut::new(Modifiers::COMMAND, Key::X),
+                ShortcutAction::Cut,
+            ),
+            (
+                KeyboardShortcut::new(Modifiers::COMMAND, Key::V),
+                ShortcutAction::Paste,
+            ),
+        ];
+        if !cfg!(target_os = "macos") {
+            bindings.extend([
+                (
+                    KeyboardShortcut::new(Modifiers::COMMAND, Key::Insert),
+                    ShortcutAction::Copy,
+                ),
+                (
+                    KeyboardShortcut::new(Modifiers::SHIFT, Key::Delete),
+                    ShortcutAction::Cut,
+                ),
+                (
+                    KeyboardShortcut::new(Modifiers::SHIFT, Key::Insert),
+                    ShortcutAction::Paste,
+                ),
+            ]);
+        }
+        Self { bindings }
+    }
+}
+
+impl KeyboardShortcuts {
+    /// no bindings at all. egui still gets the key events, but copy/paste won't work.
+    pub fn empty() -> Self {
+        Self {
+            bindings: Vec::new(),
+        }
+    }
+    /// `alt`, `shift` and `command` must be the same. `ctrl` is only checked on mac, as it is `command` elsewhere.
+    pub fn matches(shortcut: &KeyboardShortcut, key: Key, modifiers: Modifiers) -> bool {
+        let expected = shortcut.modifiers;
+        shortcut.key == key
+            && expected.alt == modifiers.alt
+            && expected.shift == modifiers.shift
+            && expected.command == modifiers.command
+            && (!cfg!(target_os = "macos") || expected.ctrl == modifiers.ctrl)
+    }
+    /// the action bound to this key combination, if any.
+    pub fn action(&self, key: Key, modifiers: Modifiers) -> Option<ShortcutAction> {
+        self.bindings
+            .iter()
+            .find(|(shortcut, _)| Self::matches(shortcut, key, modifiers))
+            .map(|(_, action)| *action)
+    }
+    /// pushes the egui events for this key input. The [`Event::Key`] is always sent, so that egui can still
+    /// use it (eg: `InputState::consume_shortcut`). If a key press matches a binding, the action's event is sent before it.
+    ///
+    /// `clipboard_text` is only called for [`ShortcutAction::Paste`].
+    pub fn push_key_events(
+        &self,
+        input: KeyInput,
+        events: &mut Vec<Event>,
+        clipboard_text: impl FnOnce() -> Option<String>,
+    ) {
+        if input.pressed {
+            match self.action(input.key, input.modifiers) {
+                Some(ShortcutAction::Copy) => events.push(Event::Copy),
+                Some(ShortcutAction::Cut) => events.push(Event::Cut),
+                Some(ShortcutAction::Paste) => {
+                    if let Some(text) = clipboard_text().filter(|text| !text.is_empty()) {
+                        // text edits would insert the `\r` of windows line endings
+                        events.push(Event::Paste(text.replace("\r\n", "\n")));
+                    }
+                }
+                None => {}
+            }
+        }
+        events.push(Event::Key {
+            key: input.key,
+            pressed: input.pressed,
+            repeat: input.repeat,
+            modifiers: input.modifiers,
+        });
+    }
+}
+
+/// converts the text input of a window backend into [`Event::Text`].
+///
+/// Removes control characters (eg: backspace or enter, which are sent as key events anyway) and the private use
+/// characters which mac sends for function keys. Returns `None` if nothing is left, or if `command` is held
+/// because that is a shortcut and not text (eg: winit sends `\u{3}` for `ctrl + C`). `ctrl + alt` is allowed, as that is `AltGr` on windows.
+pub fn text_event(text: &str, modifiers: Modifiers) -> Option<Event> {
+    if modifiers.command && !modifiers.alt {
+        return None;
+    }
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_control() && !('\u{e000}'..='\u{f8ff}').contains(c))
+        .collect();
+    (!text.is_empty()).then_some(Event::Text(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAC: bool = cfg!(target_os = "macos");
+
+    /// the platform's command modifier (+ shift)
+    fn command(shift: bool) -> Modifiers {
+        modifiers(false, !MAC, shift, MAC)
+    }
+
+    fn press(key: Key, modifiers: Modifiers) -> Vec<Event> {
+        let mut events = vec![];
+        KeyboardShortcuts::default().push_key_events(
+            KeyInput {
+                key,
+                pressed: true,
+                repeat: false,
+                modifiers,
+            },
+            &mut events,
+            || Some("line\r\nbreak".to_string()),
+        );
+        events
+    }
+
+    #[test]
+    fn clipboard_shortcuts() {
+        let shortcuts = KeyboardShortcuts::default();
+        let cmd = command(false);
+        assert_eq!(shortcuts.action(Key::C, cmd), Some(ShortcutAction::Copy));
+        assert_eq!(shortcuts.action(Key::X, cmd), Some(ShortcutAction::Cut));
+        assert_eq!(shortcuts.action(Key::V, cmd), Some(ShortcutAction::Paste));
+        assert_eq!(shortcuts.action(Key::C, Modifiers::NONE), None);
+        // without the modifiers of the binding, it is not a shortcut
+        assert_eq!(shortcuts.action(Key::Insert, Modifiers::NONE), None);
+
+        let insert_delete = [
+            (Key::Insert, cmd, ShortcutAction::Copy),
+            (Key::Delete, Modifiers::SHIFT, ShortcutAction::Cut),
+            (Key::Insert, Modifiers::SHIFT, ShortcutAction::Paste),
+        ];
+        for (key, modifiers, action) in insert_delete {
+            let expected = (!MAC).then_some(action);
+            assert_eq!(shortcuts.action(key, modifiers), expected, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn extra_modifiers_dont_match() {
+        let shortcuts = KeyboardShortcuts::default();
+        assert_eq!(shortcuts.action(Key::C, command(true)), None);
+        let cmd_alt = Modifiers {
+            alt: true,
+            ..command(false)
+        };
+        assert_eq!(shortcuts.action(Key::V, cmd_alt), None);
+        assert_eq!(
+            KeyboardShortcuts::empty().action(Key::C, command(false)),
+            None
+        );
+    }
+
+    #[test]
+    fn key_events() {
+        let cmd = command(false);
+        let key_event = |key| Event::Key {
+            key,
+            pressed: true,
+            repeat: false,
+            modifiers: cmd,
+        };
+        assert_eq!(press(Key::C, cmd), [Event::Copy, key_event(Key::C)]);
+        assert_eq!(press(Key::X, cmd), [Event::Cut, key_event(Key::X)]);
+        // windows line endings are normalized
+        assert_eq!(
+            press(Key::V, cmd),
+            [Event::Paste("line\nbreak".to_string()), key_event(Key::V)]
+        );
+        // not a shortcut, only the key event
+        assert_eq!(
+            press(Key::A, Modifiers::NONE),
+            [Event::Key {
+                key: Key::A,
+                pressed: true,
+                repeat: false,
+                modifiers: Modifiers::NONE,
+            }]
+        );
+    }
+
+    #[test]
+    fn paste_from_memory_clipboard() {
+        use crate::clipboard::{ClipboardProvider, MemoryClipboard};
+        let mut clipboard = MemoryClipboard::default();
+        let input = KeyInput {
+            key: Key::V,
+            pressed: true,
+            repeat: false,
+            modifiers: command(false),
+        };
+        let shortcuts = KeyboardShortcuts::default();
+
+        // empty clipboard doesn't paste anything
+        let mut events = vec![];
+        shortcuts.push_key_events(input, &mut events, || clipboard.get_text());
+        assert!(!events.iter().any(|e| matches!(e, Event::Paste(_))));
+
+        clipboard.set_text("from\r\nmemory");
+        let mut events = vec![];
+        shortcuts.push_key_events(input, &mut events, || clipboard.get_text());
+        assert_eq!(events[0], Event::Paste("from\nmemory".to_string()));
+    }
+
+    #[test]
+    fn text_events() {
+        let text = |s: &str| Some(Event::Text(s.to_string()));
+        assert_eq!(text_event("a", Modifiers::NONE), text("a"));
+        assert_eq!(text_event("A", Modifiers::SHIFT), text("A"));
+        // control characters and mac function key characters are dropped
+        assert_eq!(text_event("a\u{8}\r\n\tb", Modifiers::NONE), text("ab"));
+        assert_eq!(text_event("\u{f700}", Modifiers::NONE), None);
+        assert_eq!(text_event("", Modifiers::NONE), None);
+        // command shortcuts are not text
+        assert_eq!(text_event("c", command(false)), None);
+        // but ctrl + alt (AltGr) is
+        let alt_gr = modifiers(true, true, false, false);
+        assert_eq!(text_event("@", alt_gr), text("@"));
+    }
+}
@@ -32,14 +32,48 @@ use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
 use std::time::Duration;
 
 /// Intended to provide a common struct which all window backends accept as their configuration.
-/// To set size/position/title etc.. just use the windowbackend trait functions after you created the window.
 /// This struct is primarily intended for settings which are to be specified *before* creating a window like opengl or transparency etc..
+/// The initial window settings (title, size etc..) are applied the same way by all window backends, so that apps look the same with any backend.
+/// To change them later, just use the windowbackend trait functions.
 #[derive(Debug, Clone)]
 pub struct BackendConfig {
     /// true by default
     pub is_opengl: bool,
     pub opengl_config: Option<OpenGlConfig>,
     pub transparent: Option<bool>,
+    /// title of the main window
+    pub title: String,
+    /// initial size of the main window in logical points. ignored on web and android, as the canvas or screen decides the size.
+    pub size: [f32; 2],
+    /// in logical points
+    pub min_size: Option<[f32; 2]>,
+    /// in logical points
+    pub max_size: Option<[f32; 2]>,
+    /// initial position of the main window. `None` lets the OS decide.
+    pub position: Option<[f32; 2]>,
+    /// show the title bar and borders. true by default
+    pub decorations: bool,
+    /// true by default
+    pub resizable: bool,
+    /// wait for the display's vertical blank before presenting. true by default.
+    /// opengl window backends set the swap interval. non-opengl renderers (wgpu) use it to choose the present mode.
+    pub vsync: bool,
+    /// identifies the app to the desktop environment. eg: WM_CLASS on x11 or app_id on wayland.
+    /// desktops use this to group windows or to find the icon of the app in its `.desktop` file.
+    pub app_id: Option<String>,
+    /// when should the event loop run the next frame. see [`RepaintPolicy`]
+    pub repaint_policy: RepaintPolicy,
+    /// limits the frame rate. The event loop sleeps if a frame starts too early.
+    /// `None` (default) means no limit. vsync might still limit the frame rate depending on the renderer.
+    pub max_fps: Option<f32>,
+    /// if set, window backends save egui memory and window geometry on exit and restore them at startup.
+    /// It is used as the directory name inside the data dir of the user. see [`storage::FileStorage::from_app_id`]
+    pub persistence_id: Option<String>,
+    /// send egui key events for the dpad and face buttons of gamepads, to navigate egui with a gamepad.
+    /// true by default. see [`gamepad::navigation_event`]
+    pub gamepad_navigation: bool,
+    /// key bindings of copy / cut / paste. see [`keyboard::KeyboardShortcuts`]
+    pub shortcuts: keyboard::KeyboardShortcuts,
 }
 
 impl Default for BackendConfig {
@@ -50,9 +84,292 @@ impl Default for BackendConfig {
             is_opengl,
             transparent: None,
             opengl_config: Default::default(),
+            title: "egui window".to_string(),
+            size: [800.0, 600.0],
+            min_size: None,
+            max_size: None,
+            position: None,
+            decorations: true,
+            resizable: true,
+            vsync: true,
+            app_id: None,
+            repaint_policy: Default::default(),
+            max_fps: None,
+            persistence_id: None,
+            gamepad_navigation: true,
+            shortcuts: Default::default(),
+        }
+    }
+}
+
+/// decides how long the event loop waits for events before running the next frame.
+/// All window backends use [`util::FrameScheduler`] to follow this.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepaintPolicy {
+    /// run frames back to back without waiting for events. eg: games or visualizations.
+    /// use [`BackendConfig::max_fps`] to get a steady frame rate.
+    Continuous,
+    /// run a frame when we get events or when egui asks for a repaint (animations, `Context::request_repaint_after` etc..).
+    /// `max_wait` wakes up the event loop at least this often, even if egui doesn't need a repaint.
+    /// use `None` to wait forever (idle at 0% cpu).
+    Reactive { max_wait: Option<Duration> },
+    /// only run a frame when we get events (input, resize etc..). repaint requests of egui are ignored.
+    /// So, animations will only progress when you move the mouse.
+    ReactiveOnly,
+}
+
+impl Default for RepaintPolicy {
+    /// [`Self::Reactive`] with a `max_wait` of 1 second
+    fn default() -> Self {
+        Self::Reactive {
+            max_wait: Some(Duration::from_secs(1)),
+        }
+    }
+}
+/// The error returned by [`WindowBackend::try_new`] and [`GfxBackend::try_new`] (and other fallible fns of backends).
+/// Most of the underlying libraries have their own error types, so we just keep their error message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackendError {
+    /// failed to initialize the windowing library. eg: glfw/sdl2 init or winit event loop.
+    WindowBackendInit(String),
+    /// failed to create the window
+    WindowCreation(String),
+    /// window backend doesn't have a window right now. eg: on android, before resume event.
+    WindowNotAvailable,
+    /// the window is not compatible with the gfx backend. eg: glow renderer with a non-opengl window.
+    IncompatibleWindow(String),
+    /// failed to create an opengl context
+    GlContextCreation(String),
+    /// failed to create or configure a surface (swapchain) for the window
+    SurfaceCreation(String),
+    /// couldn't find a gpu adapter which is compatible with the surface
+    AdapterNotFound,
+    /// failed to create a logical device
+    DeviceCreation(String),
+    /// the operation is not supported by this backend (or platform).
+    /// contains the name of the operation. check [`WindowBackend::capabilities`] before calling.
+    Unsupported(&'static str),
+    /// anything else
+    Other(String),
+}
+
+impl std::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendError::WindowBackendInit(e) => {
+                write!(f, "failed to initialize window backend: {e}")
+            }
+            BackendError::WindowCreation(e) => write!(f, "failed to create window: {e}"),
+            BackendError::WindowNotAvailable => write!(f, "window is not available"),
+            BackendError::IncompatibleWindow(e) => write!(f, "incompatible window: {e}"),
+            BackendError::GlContextCreation(e) => {
+                write!(f, "failed to create opengl context: {e}")
+            }
+            BackendError::SurfaceCreation(e) => write!(f, "failed to create surface: {e}"),
+            BackendError::AdapterNotFound => write!(f, "failed to find a compatible gpu adapter"),
+            BackendError::DeviceCreation(e) => write!(f, "failed to create device: {e}"),
+            BackendError::Unsupported(op) => write!(f, "{op} is not supported by this backend"),
+            BackendError::Other(e) => write!(f, "{e}"),
         }
     }
 }
+
+impl std::error::Error for BackendError {}
+
+/// Tells you which of the optional [`WindowBackend`] functions actually work with a window backend.
+/// If a getter is not supported, it will return `None`. If a setter is not supported, it will either do nothing
+/// or return [`BackendError::Unsupported`].
+///
+/// This can change at runtime (eg: `get_proc_address` is only supported if the window has an opengl context)
+/// and may depend on the platform (eg: wayland doesn't let you change window position).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowCapabilities {
+    pub swap_buffers: bool,
+    pub get_proc_address: bool,
+    pub get_window_position: bool,
+    pub set_window_position: bool,
+    pub get_window_size: bool,
+    pub set_window_size: bool,
+    pub get_window_minimized: bool,
+    pub set_minimize_window: bool,
+    pub get_window_maximized: bool,
+    pub set_maximize_window: bool,
+    pub get_window_visibility: bool,
+    pub set_window_visibility: bool,
+    pub get_always_on_top: bool,
+    pub set_always_on_top: bool,
+    pub get_passthrough: bool,
+    pub set_passthrough: bool,
+    pub get_decorations: bool,
+    pub set_decorations: bool,
+    pub get_resizable: bool,
+    pub set_resizable: bool,
+    pub begin_drag_move: bool,
+    pub begin_drag_resize: bool,
+    /// [`WindowBackend::monitors`] and [`WindowBackend::current_monitor`]
+    pub monitors: bool,
+    pub get_fullscreen: bool,
+    pub set_fullscreen: bool,
+    pub set_cursor_grab: bool,
+    pub set_cursor_visible: bool,
+    /// [`WindowBackend::get_relative_mouse_mode`] and [`WindowBackend::set_relative_mouse_mode`]
+    pub relative_mouse_mode: bool,
+    /// [`WindowBackend::gamepads`]
+    pub gamepads: bool,
+    /// [`WindowBackend::create_window`] and friends. see [`WindowId`]
+    pub multi_window: bool,
+    /// [`WindowBackend::get_window`] is a real window, which gfx backends can create a surface (swapchain) for.
+    /// false for headless backends. gfx backends render offscreen instead, if they support it.
+    pub window_surface: bool,
+}
+
+/// Identifies a window of a [`WindowBackend`].
+/// The window created by [`WindowBackend::try_new`] is always [`WindowId::MAIN`].
+/// Any windows created with [`WindowBackend::create_window`] get a new id, which is never reused.
+///
+/// The non-id fns of [`WindowBackend`], [`GfxBackend`] and [`UserApp`] (eg: `get_window`, `prepare_frame` or `run`) always refer to the main window.
+/// The `*_by_id` variants work with any window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WindowId(pub u64);
+
+impl WindowId {
+    /// the window created at startup.
+    pub const MAIN: Self = Self(0);
+}
+
+/// The edge (or corner) of the window which is dragged by [`WindowBackend::begin_drag_resize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResizeEdge {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl ResizeEdge {
+    /// `[x, y]` direction in which this edge moves when the window grows. eg: `[-1, 0]` for west and `[1, 1]` for south east.
+    pub fn direction(self) -> [i8; 2] {
+        match self {
+            ResizeEdge::North => [0, -1],
+            ResizeEdge::South => [0, 1],
+            ResizeEdge::East => [1, 0],
+            ResizeEdge::West => [-1, 0],
+            ResizeEdge::NorthEast => [1, -1],
+            ResizeEdge::NorthWest => [-1, -1],
+            ResizeEdge::SouthEast => [1, 1],
+            ResizeEdge::SouthWest => [-1, 1],
+        }
+    }
+    /// the resize cursor which should be shown when hovering this edge.
+    pub fn cursor_icon(self) -> egui::CursorIcon {
+        match self {
+            ResizeEdge::North => egui::CursorIcon::ResizeNorth,
+            ResizeEdge::South => egui::CursorIcon::ResizeSouth,
+            ResizeEdge::East => egui::CursorIcon::ResizeEast,
+            ResizeEdge::West => egui::CursorIcon::ResizeWest,
+            ResizeEdge::NorthEast => egui::CursorIcon::ResizeNorthEast,
+            ResizeEdge::NorthWest => egui::CursorIcon::ResizeNorthWest,
+            ResizeEdge::SouthEast => egui::CursorIcon::ResizeSouthEast,
+            ResizeEdge::SouthWest => egui::CursorIcon::ResizeSouthWest,
+        }
+    }
+}
+
+/// A video mode of a monitor. see [`MonitorInfo::video_modes`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VideoMode {
+    /// in physical pixels
+    pub size: [u32; 2],
+    /// bits per pixel of all color channels combined. eg: 24 for rgb8
+    pub bit_depth: u16,
+    /// in hertz. `None` if unknown.
+    pub refresh_rate: Option<f32>,
+}
+
+/// A monitor connected to the system. returned by [`WindowBackend::monitors`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    /// human readable name. might be empty if the platform doesn't tell us.
+    pub name: String,
+    /// position of the top left corner of the monitor in the same units as [`WindowBackend::get_window_position`].
+    pub position: [f32; 2],
+    /// size of the current video mode in physical pixels
+    pub size: [u32; 2],
+    /// content scale (physical pixels per logical point)
+    pub scale: f32,
+    /// refresh rate of the current video mode in hertz. `None` if unknown.
+    pub refresh_rate: Option<f32>,
+    /// all the video modes supported by this monitor. use them for [`FullscreenMode::Exclusive`].
+    pub video_modes: Vec<VideoMode>,
+}
+
+/// used by [`WindowBackend::set_fullscreen`].
+/// monitors are indices into the list returned by [`WindowBackend::monitors`]. `None` means the current monitor of the window.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FullscreenMode {
+    /// a normal window
+    #[default]
+    Windowed,
+    /// a window without decorations which covers the whole monitor, without changing its video mode.
+    /// switching to/from this mode is fast and the user can still alt-tab to other windows.
+    Borderless { monitor: Option<usize> },
+    /// takes over the monitor and changes its video mode. should be one of [`MonitorInfo::video_modes`].
+    Exclusive {
+        monitor: Option<usize>,
+        video_mode: VideoMode,
+    },
+}
+
+/// restricts the cursor to the window. used by [`WindowBackend::set_cursor_grab`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CursorGrab {
+    /// cursor can move freely
+    #[default]
+    None,
+    /// cursor can't leave the window
+    Confined,
+    /// cursor can't move at all. Usually, you want [`WindowBackend::set_relative_mouse_mode`] instead,
+    /// which also hides the cursor and gives you the mouse movement.
+    Locked,
+}
+
+/// window events which are forwarded to the hooks of [`UserApp`] (eg: [`UserApp::on_close_requested`]).
+/// window backends collect these while gathering events of the main window,
+/// and pass them to [`UserApp::handle_lifecycle_events`] at the start of the next frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LifecycleEvent {
+    /// user clicked the close button, pressed alt+f4 etc..
+    CloseRequested,
+    /// true if the window gained focus. false if it lost focus.
+    FocusChanged(bool),
+    /// these files are also added to `RawInput::dropped_files` for egui.
+    FilesDropped(Vec<egui::DroppedFile>),
+}
+
+/// settings for a window created with [`WindowBackend::create_window`].
+#[derive(Debug, Clone)]
+pub struct WindowAttributes {
+    pub title: String,
+    /// size in logical points
+    pub size: [f32; 2],
+    /// position relative to the top left of the monitor/screen/workspace. `None` lets the platform decide.
+    pub position: Option<[f32; 2]>,
+}
+
+impl Default for WindowAttributes {
+    fn default() -> Self {
+        Self {
+            title: "egui window".to_string(),
+            size: [800.0, 600.0],
+            position: None,
+        }
+    }
+}
+
 /// Implement this trait for your windowing backend. the main responsibility of a
 /// Windowing Backend is to
 /// 1. run event loop and call the necessary functions of Gfx and UserApp every frame.
@@ -66,12 +383,22 @@ pub trait WindowBackend: Sized {
     type Configuration: Default + Sized;
     /// This type is used by GfxBackend to create/manage swapchain/surfaces. We use an associated type,
     /// because impl Trait is not yet supported in return positions of trait functions.
-    /// For now, we only support a single window.
+    /// All the windows of a backend have the same type. see [`WindowId`] for multiple windows.
     type WindowType: HasRawDisplayHandle + HasRawWindowHandle + Sized;
-    /// Create a new window backend.
+    /// Create a new window backend. This also creates the main window (see [`WindowId::MAIN`]).
     /// config is the custom configuration of a specific window backend
     /// while backend_config is a general config struct for common enough settings like window title.
-    fn new(config: Self::Configuration, backend_config: BackendConfig) -> Self;
+    ///
+    /// returns an error if the windowing library fails to initialize or create a window.
+    fn try_new(
+        config: Self::Configuration,
+        backend_config: BackendConfig,
+    ) -> Result<Self, BackendError>;
+    /// same as [`Self::try_new`], but panics on error.
+    fn new(config: Self::Configuration, backend_config: BackendConfig) -> Self {
+        Self::try_new(config, backend_config)
+            .unwrap_or_else(|e| panic!("failed to create window backend: {e}"))
+    }
     /// extracts all the events of this frame.
     fn take_raw_input(&mut self) -> RawInput;
     /// This gives us the "Window" struct of this particular backend. should implement raw window handle apis.
@@ -86,32 +413,66 @@ pub trait WindowBackend: Sized {
     /// Run the event loop
     /// Window Backend must call the relevant UserApp functions at the right time.
     fn run_event_loop<U: UserApp<UserWindowBackend = Self> + 'static>(user_app: U);
+    /// gathers pending events of all windows without blocking. They will be part of the input for the next frame.
+    ///
+    /// use this with [`Self::run_frame`] and [`Self::should_close`] instead of [`Self::run_event_loop`],
+    /// if you want to own the main loop (eg: inside a game engine or a test).
+    /// returns [`BackendError::Unsupported`] if the backend can only run its own event loop (eg: winit on web).
+    fn poll_events(&mut self) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("poll_events"))
+    }
+    /// runs a single frame of `user_app` using the events gathered by [`Self::poll_events`].
+    /// This resizes the framebuffer if needed, calls [`UserApp::run`] and handles the platform output.
+    /// windows created with [`Self::create_window`] are run too.
+    ///
+    /// returns the platform output of the main window and the shortest `repaint_after` of all windows.
+    /// `None` if the main window had nothing to draw (eg: no window).
+    fn run_frame<U: UserApp<UserWindowBackend = Self>>(
+        _user_app: &mut U,
+    ) -> Option<(PlatformOutput, Duration)> {
+        None
+    }
+    /// true if the user (or app) wants to close the main window. Check this after [`Self::poll_events`].
+    fn should_close(&self) -> bool {
+        false
+    }
+    /// sets (or resets) the close flag of the main window. event loops exit after the current frame if this is true.
+    fn set_should_close(&mut self, _should_close: bool) {}
+    /// storage used by [`storage::load_state`] and [`storage::save_state`]. `None` if persistence is disabled.
+    fn get_storage(&mut self) -> Option<&mut dyn storage::Storage> {
+        None
+    }
+    /// clipboard used for copy / paste. `None` if the backend has no clipboard.
+    fn get_clipboard(&mut self) -> Option<&mut dyn clipboard::ClipboardProvider> {
+        None
+    }
     /// config if GfxBackend needs them. usually tells the GfxBackend whether we have an opengl or non-opengl window.
     /// for example, if a vulkan backend gets a window with opengl, it can gracefully panic instead of segfaulting.
     /// this also serves as an indicator for opengl gfx backends, on whether this backend supports `swap_buffers` or `get_proc_address` functions.
     fn get_config(&self) -> &BackendConfig;
+    /// which of the optional functions of this trait are supported by this backend.
+    /// generic code can check this instead of calling the functions and handling errors.
+    fn capabilities(&self) -> WindowCapabilities;
     /// optional. only implemented by gl windowing libraries like glfw/sdl2 which hold the gl context with Window
     /// gfx backends like glow (or raw opengl) will call this if needed.
-    /// panic! if your WindowBackend doesn't implemented this functionality (eg: winit)
-    fn swap_buffers(&mut self) {
-        unimplemented!("swap buffers is not implemented for this window backend");
+    /// returns [`BackendError::Unsupported`] if your WindowBackend doesn't implement this functionality (eg: winit)
+    fn swap_buffers(&mut self) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("swap_buffers"))
     }
     /// A direct helper function to tell us if the window is backed by opengl or non-opengl (vk/dx/mtl).
     fn is_opengl(&self) -> bool;
 
     /// get openGL function addresses. optional, just like `Self::swap_buffers`.
-    /// panic! if it doesn't apply to your WindowBackend. eg: winit.
-    fn get_proc_address(&mut self, symbol: &str) -> *const core::ffi::c_void {
-        unimplemented!(
-            "get_proc_address is not implemented for this window backend. called with {symbol}"
-        );
+    /// returns None if it doesn't apply to your WindowBackend. eg: winit or non-opengl windows.
+    fn get_proc_address(&mut self, _symbol: &str) -> Option<*const core::ffi::c_void> {
+        None
     }
     /// To change the title of the window
     fn set_window_title(&mut self, title: &str);
     /// The position of the window relative to top left of the monitor/screen/workspace.
     fn get_window_position(&mut self) -> Option<[f32; 2]>;
     /// set the position of the window relative to top left of the monitor/screen/workspace.
-    fn set_window_position(&mut self, pos: [f32; 2]);
+    fn set_window_position(&mut self, pos: [f32; 2]) -> Result<(), BackendError>;
     /// get the size of the window in logical pixels. Use `Self::get_live_physical_framebuffer_size` for physical surface size.
     fn get_window_size(&mut self) -> Option<[f32; 2]>;
     /// set the window size in logical pixels.
@@ -133,13 +494,154 @@ pub trait WindowBackend: Sized {
     /// If the window will always stay on top of other windows
     fn get_always_on_top(&mut self) -> Option<bool>;
     /// To make the window always stay on top of other windows. Usually used for Overlays.
-    fn set_always_on_top(&mut self, always_on_top: bool);
+    fn set_always_on_top(&mut self, always_on_top: bool) -> Result<(), BackendError>;
     /// If the window is "passthrough".
     /// Passthrough simply means that the window is only visually visible, but input will go to whatever is behind/below the window.
     fn get_passthrough(&mut self) -> Option<bool>;
     /// To make the window passthrough or non-passthrough. used by overlays.
     /// By checking if you application gui (egui) requires the input or not, you can set this to act as an overlay.
-    fn set_passthrough(&mut self, passthrough: bool);
+    fn set_passthrough(&mut self, passthrough: bool) -> Result<(), BackendError>;
+    /// If the window has a title bar and borders.
+    fn get_decorations(&mut self) -> Option<bool> {
+        None
+    }
+    /// To show/hide the title bar and borders. Borderless windows can draw their own title bar with egui,
+    /// and use [`Self::begin_drag_move`] and [`Self::begin_drag_resize`] to let the user move/resize the window.
+    fn set_decorations(&mut self, _decorations: bool) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("set_decorations"))
+    }
+    /// If the user can resize the window.
+    fn get_resizable(&mut self) -> Option<bool> {
+        None
+    }
+    /// To allow/disallow the user to resize the window. [`Self::set_window_size`] works either way.
+    fn set_resizable(&mut self, _resizable: bool) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("set_resizable"))
+    }
+    /// starts moving the window with the mouse, until the (left) mouse button is released.
+    /// call this when the user presses the mouse button on your custom title bar. eg: `response.drag_started()`.
+    fn begin_drag_move(&mut self) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("begin_drag_move"))
+    }
+    /// starts resizing the window by dragging the `edge` with the mouse, until the (left) mouse button is released.
+    /// just like [`Self::begin_drag_move`], call this when the user presses the mouse button near the `edge` of the window.
+    fn begin_drag_resize(&mut self, _edge: ResizeEdge) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("begin_drag_resize"))
+    }
+    /// all the monitors connected to the system. empty if the backend doesn't support monitor enumeration.
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        vec![]
+    }
+    /// index (into [`Self::monitors`]) of the monitor which contains (most of) the window.
+    fn current_monitor(&mut self) -> Option<usize> {
+        None
+    }
+    /// whether the window is fullscreen, and if so, which kind of fullscreen.
+    fn get_fullscreen(&mut self) -> Option<FullscreenMode> {
+        None
+    }
+    /// switch between windowed, borderless fullscreen and exclusive fullscreen.
+    /// When switching back to [`FullscreenMode::Windowed`], the window gets its previous position and size.
+    fn set_fullscreen(&mut self, _mode: FullscreenMode) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("set_fullscreen"))
+    }
+    /// confine the cursor to the window or lock it in place. not every platform supports every [`CursorGrab`] mode.
+    fn set_cursor_grab(&mut self, _grab: CursorGrab) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("set_cursor_grab"))
+    }
+    /// To show/hide the cursor while it is over the window. A hidden cursor stays hidden even if egui sets a cursor icon.
+    fn set_cursor_visible(&mut self, _visible: bool) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("set_cursor_visible"))
+    }
+    /// If the window is in relative mouse mode.
+    fn get_relative_mouse_mode(&mut self) -> Option<bool> {
+        None
+    }
+    /// In relative mouse mode, the cursor is hidden and locked to the window. Instead of [`egui::Event::PointerMoved`],
+    /// the raw (unaccelerated) mouse movement is collected in [`Self::mouse_motion`]. eg: for FPS-style camera controls.
+    fn set_relative_mouse_mode(&mut self, _relative: bool) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("set_relative_mouse_mode"))
+    }
+    /// the mouse movement of this frame in relative mouse mode. egui has no event for raw mouse motion, so backends
+    /// add up the movement while gathering events and reset it after the frame. read it inside `UserApp::run`.
+    ///
+    /// The movement is in logical points, just like the positions of [`egui::Event::PointerMoved`].
+    /// Backends which only get raw device counts (eg: winit) treat them as physical pixels and divide them by the scale.
+    fn mouse_motion(&mut self) -> Option<[f32; 2]> {
+        None
+    }
+    /// the state of all the connected gamepads. updated while gathering events, so it is up to date inside `UserApp::run`.
+    fn gamepads(&mut self) -> Vec<gamepad::GamepadState> {
+        vec![]
+    }
+    /// act on the [`PlatformOutput`] of a frame. eg: copy text to clipboard, change cursor icon, open url,
+    /// enable/disable text input (IME) when a text field gains/loses focus and move the IME popup to the text cursor.
+    ///
+    /// event loops call this after every `UserApp::run`. If you drive the event loop yourself, call it too.
+    /// default impl does nothing, as not every backend has a clipboard or cursor (eg: headless).
+    fn handle_platform_output(&mut self, _platform_output: &PlatformOutput) {}
+
+    /// creates a new window. returns [`BackendError::Unsupported`] if the backend only supports a single window.
+    /// The window is driven by the event loop just like the main window. Each frame, the event loop calls
+    /// [`UserApp::run_window`] for every window other than the main window.
+    ///
+    /// You will also need to call [`GfxBackend::add_window`] before the window can be drawn to.
+    /// [`UserApp::open_window`] does both.
+    fn create_window(&mut self, _attributes: WindowAttributes) -> Result<WindowId, BackendError> {
+        Err(BackendError::Unsupported("create_window"))
+    }
+    /// closes the window. the main window can't be destroyed. Call [`GfxBackend::remove_window`] *before* this fn.
+    fn destroy_window(&mut self, _id: WindowId) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("destroy_window"))
+    }
+    /// ids of all the live windows, including the main window.
+    fn window_ids(&self) -> Vec<WindowId> {
+        vec![WindowId::MAIN]
+    }
+    /// same as [`Self::get_window`], but for any window.
+    fn get_window_by_id(&mut self, id: WindowId) -> Option<&mut Self::WindowType> {
+        if id == WindowId::MAIN {
+            self.get_window()
+        } else {
+            None
+        }
+    }
+    /// same as [`Self::get_live_physical_size_framebuffer`], but for any window.
+    fn get_live_physical_size_framebuffer_by_id(&mut self, id: WindowId) -> Option<[u32; 2]> {
+        if id == WindowId::MAIN {
+            self.get_live_physical_size_framebuffer()
+        } else {
+            None
+        }
+    }
+    /// same as [`Self::take_raw_input`], but for any window. Each window has its own input.
+    fn take_raw_input_by_id(&mut self, id: WindowId) -> RawInput {
+        if id == WindowId::MAIN {
+            self.take_raw_input()
+        } else {
+            RawInput::default()
+        }
+    }
+    /// same as [`Self::swap_buffers`], but for any window.
+    fn swap_buffers_by_id(&mut self, id: WindowId) -> Result<(), BackendError> {
+        if id == WindowId::MAIN {
+            self.swap_buffers()
+        } else {
+            Err(BackendError::Unsupported("swap_buffers_by_id"))
+        }
+    }
+    /// opengl only. makes the gl context of this window current, so that gl commands draw to this window.
+    /// all the gl contexts of a backend share their objects (textures, buffers, programs etc..) with the main window's context.
+    /// NOTE: vertex array objects are *not* shared between contexts.
+    ///
+    /// default impl does nothing for the main window, as its context is always current in single window backends.
+    fn make_gl_context_current(&mut self, id: WindowId) -> Result<(), BackendError> {
+        if id == WindowId::MAIN {
+            Ok(())
+        } else {
+            Err(BackendError::Unsupported("make_gl_context_current"))
+        }
+    }
 }
 
 /// Trait for Gfx backends. these could be Gfx APIs like opengl or vulkan or wgpu etc..
@@ -153,8 +655,21 @@ pub trait GfxBackend {
     /// for compatibility.
     ///
     /// for example, a glow renderer might want an opengl context. but if the window was created without one,
-    /// the glow renderer should panic.
-    fn new(window_backend: &mut impl WindowBackend, config: Self::Configuration) -> Self;
+    /// the glow renderer should return [`BackendError::IncompatibleWindow`].
+    fn try_new(
+        window_backend: &mut impl WindowBackend,
+        config: Self::Configuration,
+    ) -> Result<Self, BackendError>
+    where
+        Self: Sized;
+    /// same as [`Self::try_new`], but panics on error.
+    fn new(window_backend: &mut impl WindowBackend, config: Self::Configuration) -> Self
+    where
+        Self: Sized,
+    {
+        Self::try_new(window_backend, config)
+            .unwrap_or_else(|e| panic!("failed to create gfx backend: {e}"))
+    }
 
     /// Android only. callend on app suspension, which destroys the window.
     /// so, will need to destroy the `Surface` and recreate during resume event.
@@ -186,6 +701,59 @@ pub trait GfxBackend {
     /// on opengl, renderer might call `WindowBackend::swap_buffers`.
     /// on wgpu / vulkan, renderer might submit commands to queues, present swapchain image etc..
     fn present(&mut self, window_backend: &mut impl WindowBackend);
+
+    /// create the surface (and anything else needed to draw) for a window created with [`WindowBackend::create_window`].
+    /// Every window gets its own egui painter, as each window has its own egui context (and therefore, its own managed textures).
+    /// returns [`BackendError::Unsupported`] if this renderer only supports the main window.
+    fn add_window(
+        &mut self,
+        _window_backend: &mut impl WindowBackend,
+        _id: WindowId,
+    ) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("add_window"))
+    }
+    /// destroys the surface of the window. call this *before* [`WindowBackend::destroy_window`].
+    fn remove_window(&mut self, _window_backend: &mut impl WindowBackend, _id: WindowId) {}
+    /// same as [`Self::resize_framebuffer`], but for any window.
+    fn resize_framebuffer_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            self.resize_framebuffer(window_backend);
+        }
+    }
+    /// same as [`Self::prepare_frame`], but for any window.
+    fn prepare_frame_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            self.prepare_frame(window_backend);
+        }
+    }
+    /// same as [`Self::render_egui`], but for any window.
+    fn render_egui_by_id(
+        &mut self,
+        id: WindowId,
+        meshes: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        if id == WindowId::MAIN {
+            self.render_egui(meshes, textures_delta, logical_screen_size);
+        }
+    }
+    /// same as [`Self::present`], but for any window.
+    fn present_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            self.present(window_backend);
+        }
+    }
+    /// asks the renderer to capture the next frame of the main window when it is presented.
+    /// The image can be taken with [`Self::take_screenshot`] after the frame is presented.
+    /// So, if you request it in `gui_run`, it will be ready in the `gui_run` of the next frame.
+    fn request_screenshot(&mut self) {}
+    /// returns the frame captured after [`Self::request_screenshot`] (if it is ready).
+    /// pixels are srgb with premultiplied alpha, like [`egui::Color32`], regardless of the surface format.
+    /// always returns `None` if the renderer doesn't support screenshots.
+    fn take_screenshot(&mut self) -> Option<egui::ColorImage> {
+        None
+    }
 }
 
 /// This is the trait most users care about.
@@ -252,10 +820,305 @@ pub trait UserApp {
     /// This is the only function user needs to implement. this function will be called every frame by the default implementation of `run` fn.
     /// Just use the egui context to build the user interface, and after this function is called, `run` fn default impl will take care of drawing egui.
     fn gui_run(&mut self);
+
+    /// called when the user tries to close the main window. return `false` to keep the window open
+    /// (eg: to show an "unsaved changes" dialog). You can close it later with [`WindowBackend::set_should_close`]
+    fn on_close_requested(&mut self) -> bool {
+        true
+    }
+    /// called when the main window gains (`true`) or loses (`false`) focus.
+    fn on_focus_changed(&mut self, _focused: bool) {}
+    /// called when files are dropped on the main window.
+    fn on_files_dropped(&mut self, _files: &[egui::DroppedFile]) {}
+    /// called once, just before the event loop exits.
+    /// If you drive the frames yourself with [`WindowBackend::run_frame`], you have to call this yourself.
+    fn on_exit(&mut self) {}
+    /// calls the hooks above for each event. window backends call this before running a frame.
+    /// returns true if the main window should be closed (close was requested and not vetoed).
+    fn handle_lifecycle_events(&mut self, events: Vec<LifecycleEvent>) -> bool {
+        let mut close = false;
+        for event in events {
+            match event {
+                LifecycleEvent::CloseRequested => {
+                    if self.on_close_requested() {
+                        close = true;
+                    }
+                }
+                LifecycleEvent::FocusChanged(focused) => self.on_focus_changed(focused),
+                LifecycleEvent::FilesDropped(files) => self.on_files_dropped(&files),
+            }
+        }
+        close
+    }
+
+    /// egui context of a window other than the main window. Every window needs its own context.
+    /// return `None` if you don't know the window. Then, `run_window` will skip the window.
+    fn get_window_context(&mut self, _id: WindowId) -> Option<egui::Context> {
+        None
+    }
+    /// same as `gui_run`, but for windows created with [`UserApp::open_window`]. use the context from `get_window_context`.
+    fn gui_run_window(&mut self, _id: WindowId) {}
+    /// creates a window with the window backend and adds it to the gfx backend.
+    /// don't forget to create an egui context for it and return it from `get_window_context`.
+    fn open_window(&mut self, attributes: WindowAttributes) -> Result<WindowId, BackendError> {
+        let (wb, gb, _) = self.get_all();
+        let id = wb.create_window(attributes)?;
+        if let Err(e) = gb.add_window(wb, id) {
+            let _ = wb.destroy_window(id);
+            return Err(e);
+        }
+        Ok(id)
+    }
+    /// removes the window from gfx backend and destroys it. The event loop calls this when the user tries to close a
+    /// window other than the main window. override this if you want to keep the window open or clean up your window state.
+    fn close_window(&mut self, id: WindowId) {
+        let (wb, gb, _) = self.get_all();
+        gb.remove_window(wb, id);
+        if let Err(e) = wb.destroy_window(id) {
+            tracing::error!("failed to destroy window {id:?}: {e}");
+        }
+    }
+    /// same as `resize_framebuffer`, but for any window.
+    fn resize_framebuffer_by_id(&mut self, id: WindowId) {
+        let (wb, gb, _) = self.get_all();
+        gb.resize_framebuffer_by_id(wb, id);
+    }
+    /// same as `run`, but for a window other than the main window. uses `get_window_context` and `gui_run_window` instead.
+    fn run_window(
+        &mut self,
+        id: WindowId,
+        logical_size: [f32; 2],
+    ) -> Option<(PlatformOutput, Duration)> {
+        if id == WindowId::MAIN {
+            return self.run(logical_size);
+        }
+        let egui_context = self.get_window_context(id)?;
+        let (wb, gb, _) = self.get_all();
+        wb.get_window_by_id(id)?;
+        let input = wb.take_raw_input_by_id(id);
+        gb.prepare_frame_by_id(wb, id);
+        egui_context.begin_frame(input);
+        self.gui_run_window(id);
+        let FullOutput {
+            platform_output,
+            repaint_after,
+            textures_delta,
+            shapes,
+        } = egui_context.end_frame();
+        let (wb, gb, _) = self.get_all();
+        gb.render_egui_by_id(
+            id,
+            egui_context.tessellate(shapes),
+            textures_delta,
+            logical_size,
+        );
+        gb.present_by_id(wb, id);
+        Some((platform_output, repaint_after))
+    }
 }
 
+/// lets you pass `&mut app` to fns like [`WindowBackend::run_frame`] which take `impl UserApp`.
+impl<T: UserApp> UserApp for &mut T {
+    type UserGfxBackend = T::UserGfxBackend;
+    type UserWindowBackend = T::UserWindowBackend;
+
+    fn get_all(
+        &mut self,
+    ) -> (
+        &mut Self::UserWindowBackend,
+        &mut Self::UserGfxBackend,
+        &egui::Context,
+    ) {
+        (**self).get_all()
+    }
+    fn resize_framebuffer(&mut self) {
+        (**self).resize_framebuffer()
+    }
+    fn resume(&mut self) {
+        (**self).resume()
+    }
+    fn suspend(&mut self) {
+        (**self).suspend()
+    }
+    fn run(&mut self, logical_size: [f32; 2]) -> Option<(PlatformOutput, Duration)> {
+        (**self).run(logical_size)
+    }
+    fn gui_run(&mut self) {
+        (**self).gui_run()
+    }
+    fn on_close_requested(&mut self) -> bool {
+        (**self).on_close_requested()
+    }
+    fn on_focus_changed(&mut self, focused: bool) {
+        (**self).on_focus_changed(focused)
+    }
+    fn on_files_dropped(&mut self, files: &[egui::DroppedFile]) {
+        (**self).on_files_dropped(files)
+    }
+    fn on_exit(&mut self) {
+        (**self).on_exit()
+    }
+    fn handle_lifecycle_events(&mut self, events: Vec<LifecycleEvent>) -> bool {
+        (**self).handle_lifecycle_events(events)
+    }
+    fn get_window_context(&mut self, id: WindowId) -> Option<egui::Context> {
+        (**self).get_window_context(id)
+    }
+    fn gui_run_window(&mut self, id: WindowId) {
+        (**self).gui_run_window(id)
+    }
+    fn open_window(&mut self, attributes: WindowAttributes) -> Result<WindowId, BackendError> {
+        (**self).open_window(attributes)
+    }
+    fn close_window(&mut self, id: WindowId) {
+        (**self).close_window(id)
+    }
+    fn resize_framebuffer_by_id(&mut self, id: WindowId) {
+        (**self).resize_framebuffer_by_id(id)
+    }
+    fn run_window(
+        &mut self,
+        id: WindowId,
+        logical_size: [f32; 2],
+    ) -> Option<(PlatformOutput, Duration)> {
+        (**self).run_window(id, logical_size)
+    }
+}
+
+pub mod clipboard;
+pub mod gamepad;
+pub mod keyboard;
+pub mod storage;
+
 /// Some nice util functions commonly used by egui backends.
 pub mod util {
+    use crate::{BackendConfig, RepaintPolicy, ResizeEdge};
+    use std::time::Duration;
+
+    /// moves/resizes a window with the mouse. used by window backends which don't have native support for
+    /// [`crate::WindowBackend::begin_drag_move`] or [`crate::WindowBackend::begin_drag_resize`] (eg: glfw).
+    ///
+    /// create it when the drag starts, call [`Self::update`] when the cursor moves and drop it when the mouse button is released.
+    /// All positions and sizes are in the screen coordinates of the windowing library.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct WindowDrag {
+        /// the edge which is being dragged. `None` if the whole window is being moved.
+        pub edge: Option<ResizeEdge>,
+        /// cursor position relative to the top left of the screen (not window) when the drag started.
+        pub start_cursor: [f32; 2],
+        pub start_position: [f32; 2],
+        pub start_size: [f32; 2],
+    }
+
+    impl WindowDrag {
+        /// returns the new position and size of the window for the current `cursor` position (relative to the screen).
+        /// The size never shrinks below `min_size`. When dragging the north/west edges, the opposite edges stay where they are.
+        pub fn update(&self, cursor: [f32; 2], min_size: [f32; 2]) -> ([f32; 2], [f32; 2]) {
+            let delta = [
+                cursor[0] - self.start_cursor[0],
+                cursor[1] - self.start_cursor[1],
+            ];
+            let mut position = self.start_position;
+            let mut size = self.start_size;
+            match self.edge {
+                None => {
+                    position[0] += delta[0];
+                    position[1] += delta[1];
+                }
+                Some(edge) => {
+                    let direction = edge.direction();
+                    for i in 0..2 {
+                        match direction[i] {
+                            1 => size[i] = (self.start_size[i] + delta[i]).max(min_size[i]),
+                            -1 => {
+                                size[i] = (self.start_size[i] - delta[i]).max(min_size[i]);
+                                position[i] = self.start_position[i] + self.start_size[i] - size[i];
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            (position, size)
+        }
+    }
+
+    /// helps event loops follow [`BackendConfig::repaint_policy`] and [`BackendConfig::max_fps`].
+    ///
+    /// 1. wait for events using [`Self::wait`]. `None` means wait until we get an event.
+    /// 2. call [`Self::start_frame`], which sleeps if we are above the max fps.
+    /// 3. run the frame and call [`Self::end_frame`] with the `repaint_after` returned by `UserApp::run`.
+    #[derive(Debug, Clone)]
+    pub struct FrameScheduler {
+        pub repaint_policy: RepaintPolicy,
+        /// `1 / max_fps`
+        pub min_frame_time: Option<Duration>,
+        /// how long the event loop should wait for events before the next frame. `None` means forever.
+        pub wait: Option<Duration>,
+        /// instant is not available on web. but on web, browser decides when we draw anyway.
+        #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+        last_frame_start: Option<std::time::Instant>,
+    }
+
+    impl FrameScheduler {
+        pub fn new(config: &BackendConfig) -> Self {
+            Self {
+                repaint_policy: config.repaint_policy,
+                min_frame_time: config
+                    .max_fps
+                    .filter(|fps| *fps > 0.0)
+                    .map(|fps| Duration::from_secs_f32(1.0 / fps)),
+                // run the first frame right away
+                wait: Some(Duration::ZERO),
+                #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+                last_frame_start: None,
+            }
+        }
+        /// sleeps until `min_frame_time` has passed since the start of previous frame.
+        pub fn start_frame(&mut self) {
+            #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+            {
+                let now = std::time::Instant::now();
+                if let (Some(min_frame_time), Some(last_frame_start)) =
+                    (self.min_frame_time, self.last_frame_start)
+                {
+                    let elapsed = now.duration_since(last_frame_start);
+                    if elapsed < min_frame_time {
+                        std::thread::sleep(min_frame_time - elapsed);
+                    }
+                }
+                self.last_frame_start = Some(std::time::Instant::now());
+            }
+        }
+        /// calculates [`Self::wait`] for the next frame. `repaint_after` is `None` if we didn't render a frame (eg: no window).
+        pub fn end_frame(&mut self, repaint_after: Option<Duration>) {
+            self.wait = match self.repaint_policy {
+                RepaintPolicy::Continuous => Some(Duration::ZERO),
+                // keep polling until we have a window to draw on.
+                _ if repaint_after.is_none() => Some(Duration::ZERO),
+                RepaintPolicy::Reactive { max_wait } => {
+                    let repaint_after = repaint_after.unwrap_or_default();
+                    // egui uses Duration::MAX when it doesn't need a repaint
+                    match max_wait {
+                        Some(max_wait) => Some(repaint_after.min(max_wait)),
+                        None => (repaint_after != Duration::MAX).then_some(repaint_after),
+                    }
+                }
+                RepaintPolicy::ReactiveOnly => None,
+            };
+        }
+        /// for other windows. the shortest wait wins.
+        pub fn request_repaint_after(&mut self, repaint_after: Duration) {
+            if self.repaint_policy == RepaintPolicy::ReactiveOnly || repaint_after == Duration::MAX
+            {
+                return;
+            }
+            self.wait = Some(match self.wait {
+                Some(wait) => wait.min(repaint_after),
+                None => repaint_after,
+            });
+        }
+    }
 
     /// input: clip rectangle in logical pixels, scale and framebuffer size in physical pixels
     /// we will get [x, y, width, height] of the scissor rectangle.
@@ -312,6 +1175,7 @@ pub mod util {
     /// 2. but this `y` is represents top border + y units. in opengl, we need units from bottom border  
     /// 3. we know that for any point y, distance between top and y + distance between bottom and y gives us total height
     /// 4. so, height - y units from top gives us y units from bottom.
+    ///
     /// math is suprisingly hard to write down.. just draw it on a paper, it makes sense.
     pub fn scissor_from_clip_rect_opengl(
         clip_rect: &egui::Rect,
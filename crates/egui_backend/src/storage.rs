@@ -0,0 +1,384 @@
+//! saving and restoring state (egui memory, window geometry) between runs.
+//!
+//! set [`BackendConfig::persistence_id`] and window backends will create a [`FileStorage`] for your app.
+//! They call [`load_state`] before entering the event loop and [`save_state`] when the event loop exits.
+//! If you drive the frames yourself with [`WindowBackend::run_frame`], call them yourself.
+//!
+//! [`egui::Memory`] is only saved if `egui_persistence` feature is enabled.
+use crate::{BackendConfig, UserApp, WindowBackend};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// key used for [`WindowGeometry`] of the main window.
+pub const WINDOW_GEOMETRY_KEY: &str = "etk_window_geometry";
+/// key used for [`egui::Memory`]
+pub const EGUI_MEMORY_KEY: &str = "etk_egui_memory";
+
+/// a simple key-value store. values are strings, so that storages don't need to care about the format.
+pub trait Storage {
+    fn get_string(&self, key: &str) -> Option<String>;
+    fn set_string(&mut self, key: &str, value: String);
+    fn remove(&mut self, key: &str);
+    /// write the changes to disk (or wherever the storage keeps its data).
+    fn flush(&mut self) {}
+}
+
+/// in-memory storage. useful for tests or as a base for custom storages.
+impl Storage for BTreeMap<String, String> {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        self.insert(key.to_string(), value);
+    }
+
+    fn remove(&mut self, key: &str) {
+        BTreeMap::remove(self, key);
+    }
+}
+
+/// stores everything in a single text file with a `key=value` per line.
+/// the file is read once when created, and written on [`Storage::flush`] (if something changed).
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    pub path: PathBuf,
+    pub entries: BTreeMap<String, String>,
+    /// true if entries changed since the last flush
+    pub dirty: bool,
+}
+
+impl FileStorage {
+    /// loads the file at `path` if it exists. the file (and its parent directories) are created on flush.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut entries = BTreeMap::new();
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                for line in contents.lines() {
+                    if let Some((key, value)) = line.split_once('=') {
+                        entries.insert(unescape(key), unescape(value));
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => tracing::error!("failed to read storage file {path:?}: {e}"),
+        }
+        Self {
+            path,
+            entries,
+            dirty: false,
+        }
+    }
+    /// `<data dir>/<app_id>/etk_storage.txt`. returns `None` if we can't find the data dir (eg: on web).
+    ///
+    /// data dir is `$XDG_DATA_HOME` or `~/.local/share` on linux, `~/Library/Application Support` on mac and `%APPDATA%` on windows.
+    pub fn from_app_id(app_id: &str) -> Option<Self> {
+        Some(Self::new(data_dir()?.join(app_id).join("etk_storage.txt")))
+    }
+}
+
+impl Storage for FileStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.entries.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        if self.entries.get(key) != Some(&value) {
+            self.entries.insert(key.to_string(), value);
+            self.dirty = true;
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if self.entries.remove(key).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let mut contents = String::new();
+        for (key, value) in &self.entries {
+            contents.push_str(&escape(key));
+            contents.push('=');
+            contents.push_str(&escape(value));
+            contents.push('\n');
+        }
+        if let Some(parent) = self.path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                tracing::error!("failed to create storage directory {parent:?}: {e}");
+                return;
+            }
+        }
+        match std::fs::write(&self.path, contents) {
+            Ok(_) => self.dirty = false,
+            Err(e) => tracing::error!("failed to write storage file {:?}: {e}", self.path),
+        }
+    }
+}
+
+/// creates a [`FileStorage`] if [`BackendConfig::persistence_id`] is set. window backends use this during creation.
+pub fn storage_from_config(config: &BackendConfig) -> Option<Box<dyn Storage>> {
+    let app_id = config.persistence_id.as_deref()?;
+    match FileStorage::from_app_id(app_id) {
+        Some(storage) => Some(Box::new(storage)),
+        None => {
+            tracing::warn!("couldn't find a data directory. state won't be saved");
+            None
+        }
+    }
+}
+
+fn data_dir() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty());
+    if cfg!(target_os = "windows") {
+        var("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else if cfg!(any(
+        target_arch = "wasm32",
+        target_os = "android",
+        target_os = "ios"
+    )) {
+        None
+    } else {
+        var("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    }
+}
+
+/// escapes newlines and `=`, so that every entry stays on a single line.
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('=', "\\e")
+}
+
+fn unescape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('e') => result.push('='),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+/// position, size and maximized state of a window.
+/// position is in the units of [`WindowBackend::get_window_position`] and size is in logical points, like [`WindowBackend::get_window_size`].
+/// So, the saved size doesn't change when the window is restored on a monitor with a different scale.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WindowGeometry {
+    /// `None` if the platform doesn't let us get the position (eg: wayland)
+    pub position: Option<[f32; 2]>,
+    pub size: Option<[f32; 2]>,
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    /// if the window is maximized, position and size are `None`, as they are just the size of the monitor.
+    /// [`save_state`] keeps the previously saved position/size in that case, so that the restored window can be un-maximized to its older size.
+    pub fn from_window_backend(window_backend: &mut impl WindowBackend) -> Self {
+        let maximized = window_backend.get_window_maximized().unwrap_or_default();
+        if maximized {
+            return Self {
+                maximized,
+                ..Default::default()
+            };
+        }
+        Self {
+            position: window_backend.get_window_position(),
+            size: window_backend.get_window_size(),
+            maximized,
+        }
+    }
+    /// sets the position (if supported), size and then maximizes the window.
+    pub fn apply(&self, window_backend: &mut impl WindowBackend) {
+        if let Some(position) = self.position {
+            if let Err(e) = window_backend.set_window_position(position) {
+                tracing::warn!("failed to restore window position: {e}");
+            }
+        }
+        if let Some(size) = self.size {
+            window_backend.set_window_size(size);
+        }
+        if self.maximized {
+            window_backend.set_maximize_window(true);
+        }
+    }
+}
+
+/// `position=x,y size=w,h maximized=false`. position and size are skipped if they are `None`.
+impl std::fmt::Display for WindowGeometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some([x, y]) = self.position {
+            write!(f, "position={x},{y} ")?;
+        }
+        if let Some([w, h]) = self.size {
+            write!(f, "size={w},{h} ")?;
+        }
+        write!(f, "maximized={}", self.maximized)
+    }
+}
+
+impl std::str::FromStr for WindowGeometry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pair = |value: &str| -> Result<[f32; 2], String> {
+            let (a, b) = value
+                .split_once(',')
+                .ok_or_else(|| format!("expected two numbers: {value}"))?;
+            let parse = |n: &str| n.parse::<f32>().map_err(|e| format!("{n}: {e}"));
+            Ok([parse(a)?, parse(b)?])
+        };
+        let mut geometry = Self::default();
+        for field in s.split_whitespace() {
+            match field.split_once('=') {
+                Some(("position", value)) => geometry.position = Some(pair(value)?),
+                Some(("size", value)) => geometry.size = Some(pair(value)?),
+                Some(("maximized", value)) => {
+                    geometry.maximized = value.parse().map_err(|e| format!("{value}: {e}"))?
+                }
+                _ => return Err(format!("unknown field in window geometry: {field}")),
+            }
+        }
+        Ok(geometry)
+    }
+}
+
+/// restores the window geometry and egui memory from the storage of the window backend.
+/// does nothing if the window backend doesn't have a storage.
+pub fn load_state<U: UserApp>(user_app: &mut U) {
+    let (window_backend, _, egui_context) = user_app.get_all();
+    let Some(storage) = window_backend.get_storage() else {
+        return;
+    };
+    let geometry = storage.get_string(WINDOW_GEOMETRY_KEY);
+    #[cfg(feature = "egui_persistence")]
+    if let Some(memory) = storage.get_string(EGUI_MEMORY_KEY) {
+        match ron::from_str::<egui::Memory>(&memory) {
+            Ok(memory) => egui_context.memory_mut(|m| *m = memory),
+            Err(e) => tracing::warn!("failed to deserialize egui memory: {e}"),
+        }
+    }
+    #[cfg(not(feature = "egui_persistence"))]
+    let _ = egui_context;
+    if let Some(geometry) = geometry {
+        match geometry.parse::<WindowGeometry>() {
+            Ok(geometry) => geometry.apply(window_backend),
+            Err(e) => tracing::warn!("failed to parse window geometry: {e}"),
+        }
+    }
+}
+
+/// saves the window geometry and egui memory into the storage of the window backend and flushes it.
+/// does nothing if the window backend doesn't have a storage.
+pub fn save_state<U: UserApp>(user_app: &mut U) {
+    let (window_backend, _, egui_context) = user_app.get_all();
+    if window_backend.get_storage().is_none() {
+        return;
+    }
+    let mut geometry = WindowGeometry::from_window_backend(window_backend);
+    let Some(storage) = window_backend.get_storage() else {
+        return;
+    };
+    if geometry.maximized {
+        if let Some(Ok(previous)) = storage
+            .get_string(WINDOW_GEOMETRY_KEY)
+            .map(|g| g.parse::<WindowGeometry>())
+        {
+            geometry.position = previous.position;
+            geometry.size = previous.size;
+        }
+    }
+    storage.set_string(WINDOW_GEOMETRY_KEY, geometry.to_string());
+    #[cfg(feature = "egui_persistence")]
+    match egui_context.memory(ron::to_string) {
+        Ok(memory) => storage.set_string(EGUI_MEMORY_KEY, memory),
+        Err(e) => tracing::error!("failed to serialize egui memory: {e}"),
+    }
+    #[cfg(not(feature = "egui_persistence"))]
+    let _ = egui_context;
+    storage.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_round_trip() {
+        for s in [
+            "",
+            "plain",
+            "key=value",
+            "back\\slash",
+            "\\n is not a newline",
+            "line\nbreak\r\nwindows",
+            "\\=\n\r\\\\e",
+        ] {
+            let escaped = escape(s);
+            assert!(!escaped.contains(['=', '\n', '\r']), "{escaped:?}");
+            assert_eq!(unescape(&escaped), s);
+        }
+    }
+
+    #[test]
+    fn window_geometry_round_trip() {
+        for geometry in [
+            WindowGeometry {
+                position: Some([10.0, -20.5]),
+                size: Some([800.0, 600.25]),
+                maximized: false,
+            },
+            WindowGeometry {
+                position: None,
+                size: Some([320.0, 240.0]),
+                maximized: true,
+            },
+            WindowGeometry::default(),
+        ] {
+            assert_eq!(geometry.to_string().parse(), Ok(geometry));
+        }
+        assert!("size=1".parse::<WindowGeometry>().is_err());
+        assert!("unknown=1,2".parse::<WindowGeometry>().is_err());
+    }
+
+    #[test]
+    fn file_storage_flush_and_reload() {
+        let dir = std::env::temp_dir().join(format!("etk_storage_test_{}", std::process::id()));
+        let path = dir.join("nested").join("etk_storage.txt");
+        let mut storage = FileStorage::new(&path);
+        assert!(storage.entries.is_empty());
+        storage.set_string("multi=line", "a\nb=c\\d".to_string());
+        storage.set_string("removed", "value".to_string());
+        storage.remove("removed");
+        assert!(storage.dirty);
+        storage.flush();
+        assert!(!storage.dirty);
+
+        let reloaded = FileStorage::new(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reloaded.entries, storage.entries);
+        assert_eq!(
+            reloaded.get_string("multi=line").as_deref(),
+            Some("a\nb=c\\d")
+        );
+        assert_eq!(reloaded.get_string("removed"), None);
+    }
+}
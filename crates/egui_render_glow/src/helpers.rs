@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::{glow_error, WebGlConfig};
-use egui_backend::WindowBackend;
+use egui_backend::{BackendError, WindowBackend};
 use glow::*;
 use tracing::*;
 
@@ -9,17 +9,21 @@ use tracing::*;
 pub unsafe fn create_glow_wasm32_unknown(
     window_backend: &mut impl WindowBackend,
     webgl_config: WebGlConfig,
-) -> glow::Context {
+) -> Result<glow::Context, BackendError> {
     use egui_backend::raw_window_handle::HasRawWindowHandle;
     use wasm_bindgen::JsCast;
 
     let handle_id = match window_backend
         .get_window()
-        .expect("window backend doesn't have a window yet???")
+        .ok_or(BackendError::WindowNotAvailable)?
         .raw_window_handle()
     {
         crate::raw_window_handle::RawWindowHandle::Web(handle_id) => handle_id.id,
-        _ => unimplemented!("non web raw window handles are not supported on wasm32"),
+        _ => {
+            return Err(BackendError::IncompatibleWindow(
+                "non web raw window handles are not supported on wasm32".to_string(),
+            ))
+        }
     };
     let canvas_node: wasm_bindgen::JsValue = web_sys::window()
         .and_then(|win| win.document())
@@ -27,37 +31,57 @@ pub unsafe fn create_glow_wasm32_unknown(
             doc.query_selector(&format!("[data-raw-handle=\"{handle_id}\"]"))
                 .ok()
         })
-        .expect("expected to find single canvas")
+        .flatten()
+        .ok_or_else(|| {
+            BackendError::GlContextCreation(format!(
+                "failed to find canvas with raw handle {handle_id}"
+            ))
+        })?
         .into();
     let canvas_element: web_sys::HtmlCanvasElement = canvas_node.into();
     let context_options = create_context_options_from_webgl_config(webgl_config);
     let context = canvas_element
         .get_context_with_context_options("webgl2", &context_options)
-        .unwrap()
-        .unwrap()
-        .dyn_into()
-        .unwrap();
-    glow::Context::from_webgl2_context(context)
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into().ok())
+        .ok_or_else(|| {
+            BackendError::GlContextCreation("failed to get webgl2 context of canvas".to_string())
+        })?;
+    Ok(glow::Context::from_webgl2_context(context))
 }
+/// same as [`try_create_glow_context`], but panics on error
 pub unsafe fn create_glow_context(
     window_backend: &mut impl WindowBackend,
-    _webgl_config: WebGlConfig,
+    webgl_config: WebGlConfig,
 ) -> Arc<glow::Context> {
-    assert!(
-        window_backend.is_opengl(),
-        "egui render glow only works with windows which are opengl"
-    );
+    try_create_glow_context(window_backend, webgl_config)
+        .unwrap_or_else(|e| panic!("failed to create glow context: {e}"))
+}
+pub unsafe fn try_create_glow_context(
+    window_backend: &mut impl WindowBackend,
+    _webgl_config: WebGlConfig,
+) -> Result<Arc<glow::Context>, BackendError> {
+    if !window_backend.is_opengl() {
+        return Err(BackendError::IncompatibleWindow(
+            "egui render glow only works with windows which are opengl".to_string(),
+        ));
+    }
     // for wasm32-unknown-unknown, use glow's own constructor.
     #[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
-    let glow_context = create_glow_wasm32_unknown(window_backend, _webgl_config);
+    let glow_context = create_glow_wasm32_unknown(window_backend, _webgl_config)?;
     // for non-web and emscripten platforms, just use loader fn
     #[cfg(any(not(target_arch = "wasm32"), target_os = "emscripten"))]
-    let glow_context = glow::Context::from_loader_function(|s| window_backend.get_proc_address(s));
+    let glow_context = glow::Context::from_loader_function(|s| {
+        window_backend
+            .get_proc_address(s)
+            .unwrap_or(std::ptr::null())
+    });
 
     tracing::debug!("created glow context");
     let glow_context = Arc::new(glow_context);
     glow_error!(glow_context);
-    glow_context
+    Ok(glow_context)
 }
 pub unsafe fn create_program_from_src(
     glow_context: &glow::Context,
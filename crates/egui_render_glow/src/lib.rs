@@ -6,6 +6,7 @@ pub use glow;
 use glow::{Context as GlowContext, HasContext, *};
 use helpers::*;
 use intmap::IntMap;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
@@ -70,10 +71,61 @@ pub struct GlowBackend {
     pub glow_context: Arc<GlowContext>,
     pub framebuffer_size: [u32; 2],
     pub painter: Painter,
+    /// windows added with [`GfxBackend::add_window`].
+    /// The gl contexts of the windows are shared with the main window, so we reuse `glow_context`.
+    /// But vertex arrays are not shared between contexts. So, each window gets its own [`Painter`].
+    pub secondary_windows: BTreeMap<WindowId, GlowWindow>,
+    /// set by [`GfxBackend::request_screenshot`]. we read the pixels of the framebuffer before the next swap buffers.
+    pub screenshot_requested: bool,
+    pub screenshot: Option<egui::ColorImage>,
+}
+
+/// painter and size of a window other than the main window.
+/// These objects live in the window's gl context, so make it current before using them.
+pub struct GlowWindow {
+    pub painter: Painter,
+    pub framebuffer_size: [u32; 2],
+}
+
+impl GlowBackend {
+    /// reads the pixels of the default framebuffer (of the current context) into an image.
+    /// This must be called after rendering, but before swapping buffers.
+    pub fn read_pixels(&self) -> egui::ColorImage {
+        let [width, height] = self.framebuffer_size;
+        let row_len = width as usize * 4;
+        let mut data = vec![0u8; row_len * height as usize];
+        let glow_context = &self.glow_context;
+        unsafe {
+            glow_context.bind_framebuffer(glow::FRAMEBUFFER, None);
+            glow_context.read_pixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut data),
+            );
+            glow_error!(glow_context);
+        }
+        // opengl rows start from bottom. egui images start from top
+        let pixels = data
+            .chunks_exact(row_len)
+            .rev()
+            .flat_map(|row| row.chunks_exact(4))
+            // the painter writes srgb colors on all platforms (see [`Painter::new`]), so no conversion is needed.
+            .map(|p| egui::Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3]))
+            .collect();
+        egui::ColorImage {
+            size: [width as usize, height as usize],
+            pixels,
+        }
+    }
 }
 
 impl Drop for GlowBackend {
     fn drop(&mut self) {
+        // painters of secondary windows need their own context to be current. use `remove_window` to clean them up.
         unsafe { self.painter.destroy(&self.glow_context) };
     }
 }
@@ -87,9 +139,12 @@ pub struct GlowConfig {
 impl GfxBackend for GlowBackend {
     type Configuration = GlowConfig;
 
-    fn new(window_backend: &mut impl WindowBackend, config: Self::Configuration) -> Self {
+    fn try_new(
+        window_backend: &mut impl WindowBackend,
+        config: Self::Configuration,
+    ) -> Result<Self, BackendError> {
         let glow_context: Arc<glow::Context> =
-            unsafe { create_glow_context(window_backend, config.webgl_config) };
+            unsafe { try_create_glow_context(window_backend, config.webgl_config)? };
 
         if glow_context.supported_extensions().contains("EXT_sRGB")
             || glow_context.supported_extensions().contains("GL_EXT_sRGB")
@@ -102,12 +157,18 @@ impl GfxBackend for GlowBackend {
             warn!("no srgb support detected by egui glow");
         }
 
+        let framebuffer_size = window_backend
+            .get_live_physical_size_framebuffer()
+            .ok_or(BackendError::WindowNotAvailable)?;
         let painter = unsafe { Painter::new(&glow_context) };
-        Self {
+        Ok(Self {
             glow_context,
             painter,
-            framebuffer_size: window_backend.get_live_physical_size_framebuffer().unwrap(),
-        }
+            framebuffer_size,
+            secondary_windows: BTreeMap::new(),
+            screenshot_requested: false,
+            screenshot: None,
+        })
     }
 
     fn suspend(&mut self, _window_backend: &mut impl WindowBackend) {
@@ -126,9 +187,15 @@ impl GfxBackend for GlowBackend {
     }
 
     fn present(&mut self, _window_backend: &mut impl WindowBackend) {
+        // contents of the back buffer are undefined after swapping. so, we read them now.
+        if std::mem::take(&mut self.screenshot_requested) {
+            self.screenshot = Some(self.read_pixels());
+        }
         #[cfg(any(not(target_arch = "wasm32"), target_os = "emscripten"))]
         {
-            _window_backend.swap_buffers();
+            if let Err(e) = _window_backend.swap_buffers() {
+                tracing::error!("failed to swap buffers: {e}");
+            }
         }
         // on wasm, there's no swap buffers.. the browser takes care of it automatically.
     }
@@ -160,6 +227,127 @@ impl GfxBackend for GlowBackend {
             self.painter.render_egui(&self.glow_context);
         }
     }
+
+    fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+
+    fn take_screenshot(&mut self) -> Option<egui::ColorImage> {
+        self.screenshot.take()
+    }
+
+    fn add_window(
+        &mut self,
+        window_backend: &mut impl WindowBackend,
+        id: WindowId,
+    ) -> Result<(), BackendError> {
+        if !window_backend.is_opengl() {
+            return Err(BackendError::IncompatibleWindow(
+                "window doesn't have an opengl context".to_string(),
+            ));
+        }
+        let framebuffer_size = window_backend
+            .get_live_physical_size_framebuffer_by_id(id)
+            .ok_or(BackendError::WindowNotAvailable)?;
+        // the painter must be created while the window's context is current
+        window_backend.make_gl_context_current(id)?;
+        let mut painter = unsafe { Painter::new(&self.glow_context) };
+        painter.screen_size_physical = framebuffer_size;
+        window_backend.make_gl_context_current(WindowId::MAIN)?;
+        self.secondary_windows.insert(
+            id,
+            GlowWindow {
+                painter,
+                framebuffer_size,
+            },
+        );
+        Ok(())
+    }
+
+    fn remove_window(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if let Some(mut window) = self.secondary_windows.remove(&id) {
+            if let Err(e) = window_backend.make_gl_context_current(id) {
+                tracing::error!("failed to make gl context of window {id:?} current: {e}");
+                return;
+            }
+            unsafe { window.painter.destroy(&self.glow_context) };
+            if let Err(e) = window_backend.make_gl_context_current(WindowId::MAIN) {
+                tracing::error!("failed to make gl context of main window current: {e}");
+            }
+        }
+    }
+
+    fn resize_framebuffer_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            return self.resize_framebuffer(window_backend);
+        }
+        // viewport is set in `prepare_frame_by_id`, as we need the window's context to be current.
+        if let (Some(window), Some(fb_size)) = (
+            self.secondary_windows.get_mut(&id),
+            window_backend.get_live_physical_size_framebuffer_by_id(id),
+        ) {
+            window.framebuffer_size = fb_size;
+            window.painter.screen_size_physical = fb_size;
+        }
+    }
+
+    fn prepare_frame_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            return self.prepare_frame(window_backend);
+        }
+        let Some(window) = self.secondary_windows.get(&id) else {
+            return;
+        };
+        // we switch back to main window's context in `present_by_id`
+        if let Err(e) = window_backend.make_gl_context_current(id) {
+            tracing::error!("failed to make gl context of window {id:?} current: {e}");
+            return;
+        }
+        let [width, height] = window.framebuffer_size;
+        unsafe {
+            self.glow_context
+                .viewport(0, 0, width as i32, height as i32);
+        }
+        self.prepare_frame(window_backend);
+    }
+
+    fn render_egui_by_id(
+        &mut self,
+        id: WindowId,
+        meshes: Vec<egui::ClippedPrimitive>,
+        textures_delta: egui::TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        if id == WindowId::MAIN {
+            return self.render_egui(meshes, textures_delta, logical_screen_size);
+        }
+        if let Some(window) = self.secondary_windows.get_mut(&id) {
+            unsafe {
+                window.painter.prepare_render(
+                    &self.glow_context,
+                    meshes,
+                    textures_delta,
+                    logical_screen_size,
+                );
+                window.painter.render_egui(&self.glow_context);
+            }
+        }
+    }
+
+    fn present_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            return self.present(window_backend);
+        }
+        if !self.secondary_windows.contains_key(&id) {
+            return;
+        }
+        if let Err(e) = window_backend.swap_buffers_by_id(id) {
+            tracing::error!("failed to swap buffers of window {id:?}: {e}");
+        }
+        if let Err(e) = window_backend.make_gl_context_current(WindowId::MAIN) {
+            tracing::error!("failed to make gl context of main window current: {e}");
+        }
+    }
 }
 pub struct GpuTexture {
     handle: glow::Texture,
@@ -170,7 +358,7 @@ pub struct GpuTexture {
 
 /// Egui Painter using glow::Context
 /// Assumptions:
-/// 1. srgb framebuffer
+/// 1. non-srgb framebuffer. the shader outputs srgb colors.
 /// 2. opengl 3+ on desktop and webgl2 only on web.
 /// 3.
 pub struct Painter {
@@ -211,16 +399,9 @@ impl Painter {
             }
             glow_error!(gl);
             // compile shaders
-            let egui_program = create_program_from_src(
-                gl,
-                EGUI_VS,
-                if cfg!(wasm32) {
-                    // on wasm, we always assume srgb framebuffer
-                    EGUI_LINEAR_OUTPUT_FS
-                } else {
-                    EGUI_SRGB_OUTPUT_FS
-                },
-            );
+            // we never use srgb framebuffers (webgl doesn't have them for the canvas anyway),
+            // so the shader does the srgb conversion itself on all platforms.
+            let egui_program = create_program_from_src(gl, EGUI_VS, EGUI_SRGB_OUTPUT_FS);
             // shader verification
             glow_error!(gl);
             let u_screen_size = gl
@@ -470,6 +651,57 @@ impl Painter {
         }
         glow_error!(glow_context);
     }
+    /// renders egui into `framebuffer` instead of the window. `None` is the default framebuffer.
+    /// `framebuffer_size` is the size of the framebuffer's color attachment in pixels.
+    /// If `clear_color` is not `None`, the framebuffer is cleared with it (premultiplied rgba in 0..=1 range) before drawing egui.
+    ///
+    /// This binds the default framebuffer and restores the viewport of the window after rendering.
+    /// `textures_delta` is applied to the painter's textures. So, if you render the same egui output to the window too,
+    /// pass the delta only once.
+    /// # Safety
+    /// same as [`Self::prepare_render`] and [`Self::render_egui`].
+    ///
+    /// The shader always outputs srgb encoded colors. So, the color attachment of `framebuffer` should be `RGBA8`,
+    /// and it will hold srgb encoded bytes (just like the window's framebuffer).
+    /// An srgb attachment (eg: `SRGB8_ALPHA8`) only works on desktop gl, because we disable `FRAMEBUFFER_SRGB` there.
+    /// On gles/webgl, the colors would be encoded twice and look too bright.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn render_egui_to_fbo(
+        &mut self,
+        glow_context: &glow::Context,
+        framebuffer: Option<glow::Framebuffer>,
+        framebuffer_size: [u32; 2],
+        meshes: Vec<egui::ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        logical_screen_size: [f32; 2],
+        clear_color: Option<[f32; 4]>,
+    ) {
+        let window_size_physical = self.screen_size_physical;
+        let window_logical_size = self.logical_screen_size;
+        glow_context.bind_framebuffer(glow::FRAMEBUFFER, framebuffer);
+        glow_context.viewport(0, 0, framebuffer_size[0] as i32, framebuffer_size[1] as i32);
+        if let Some([r, g, b, a]) = clear_color {
+            glow_context.disable(glow::SCISSOR_TEST);
+            glow_context.clear_color(r, g, b, a);
+            glow_context.clear(glow::COLOR_BUFFER_BIT);
+            // GlowBackend clears the window with the default (transparent) clear color
+            glow_context.clear_color(0.0, 0.0, 0.0, 0.0);
+        }
+        self.screen_size_physical = framebuffer_size;
+        self.prepare_render(glow_context, meshes, textures_delta, logical_screen_size);
+        self.render_egui(glow_context);
+        glow_error!(glow_context);
+
+        self.screen_size_physical = window_size_physical;
+        self.logical_screen_size = window_logical_size;
+        glow_context.bind_framebuffer(glow::FRAMEBUFFER, None);
+        glow_context.viewport(
+            0,
+            0,
+            window_size_physical[0] as i32,
+            window_size_physical[1] as i32,
+        );
+    }
     /// # Safety
     /// This must be called only once.
     /// must not use it again because this destroys all the opengl objects.
@@ -0,0 +1,300 @@
+//! A [`GfxBackend`] which rasterizes egui meshes on the CPU into an RGBA8 framebuffer.
+//!
+//! This doesn't need a gpu, so it works on CI, inside containers or on machines without working drivers.
+//! You can read the rendered frame from [`SoftBackend::framebuffer`] after every frame.
+//!
+//! With the `softbuffer` feature, the framebuffer can be presented to the window using its raw window handles.
+//! This is useful as a fallback renderer when neither wgpu nor opengl work.
+
+mod painter;
+use egui_backend::{
+    egui::{ClippedPrimitive, Color32, ColorImage, TexturesDelta},
+    *,
+};
+pub use painter::*;
+use std::collections::BTreeMap;
+
+/// config for [`SoftBackend`]
+#[derive(Debug, Clone)]
+pub struct SoftConfig {
+    /// the framebuffer is cleared with this color at the start of every frame.
+    pub clear_color: Color32,
+    /// present the framebuffer to the window. only works if `softbuffer` feature is enabled.
+    /// If this is false (or the feature is disabled), the frame stays in [`SoftBackend::framebuffer`] and nothing is shown on screen.
+    pub present: bool,
+}
+
+impl Default for SoftConfig {
+    fn default() -> Self {
+        Self {
+            clear_color: Color32::TRANSPARENT,
+            present: cfg!(feature = "softbuffer"),
+        }
+    }
+}
+
+pub struct SoftBackend {
+    pub painter: SoftPainter,
+    /// the rendered frame. pixels are premultiplied srgba.
+    /// size is the physical framebuffer size of the window.
+    pub framebuffer: ColorImage,
+    pub clear_color: Color32,
+    /// whether windows added later should be presented. see [`SoftConfig::present`]
+    pub present: bool,
+    #[cfg(feature = "softbuffer")]
+    pub presenter: Option<SoftBufferPresenter>,
+    /// windows added with [`GfxBackend::add_window`]. each one is a soft backend with its own painter and framebuffer.
+    /// their own `secondary_windows` is always empty.
+    pub secondary_windows: BTreeMap<WindowId, SoftBackend>,
+    /// set by [`GfxBackend::request_screenshot`]. the framebuffer is copied into `screenshot` during present.
+    pub screenshot_requested: bool,
+    pub screenshot: Option<ColorImage>,
+}
+
+impl SoftBackend {
+    /// the rendered frame as tightly packed rgba8 bytes (premultiplied alpha).
+    pub fn framebuffer_rgba8(&self) -> &[u8] {
+        self.framebuffer.as_raw()
+    }
+    /// the rendered frame of any window. [`WindowId::MAIN`] returns [`Self::framebuffer`].
+    pub fn framebuffer_by_id(&self, id: WindowId) -> Option<&ColorImage> {
+        if id == WindowId::MAIN {
+            Some(&self.framebuffer)
+        } else {
+            self.secondary_windows.get(&id).map(|w| &w.framebuffer)
+        }
+    }
+    fn try_new_for_window(
+        window_backend: &mut impl WindowBackend,
+        id: WindowId,
+        clear_color: Color32,
+        present: bool,
+    ) -> Result<Self, BackendError> {
+        let framebuffer_size = window_backend
+            .get_live_physical_size_framebuffer_by_id(id)
+            .unwrap_or_default();
+        #[cfg(feature = "softbuffer")]
+        let presenter = if present {
+            window_backend
+                .get_window_by_id(id)
+                .and_then(|window| SoftBufferPresenter::new(window))
+        } else {
+            None
+        };
+        #[cfg(not(feature = "softbuffer"))]
+        if present {
+            tracing::warn!(
+                "softbuffer feature is not enabled. frames won't be presented to the window"
+            );
+        }
+        Ok(Self {
+            painter: SoftPainter::new(),
+            framebuffer: ColorImage::new(
+                [framebuffer_size[0] as usize, framebuffer_size[1] as usize],
+                clear_color,
+            ),
+            clear_color,
+            present,
+            #[cfg(feature = "softbuffer")]
+            presenter,
+            secondary_windows: BTreeMap::new(),
+            screenshot_requested: false,
+            screenshot: None,
+        })
+    }
+    /// recreates the framebuffer if the size changed.
+    fn resize_to(&mut self, fb_size: Option<[u32; 2]>) {
+        if let Some(fb_size) = fb_size {
+            let size = [fb_size[0] as usize, fb_size[1] as usize];
+            if self.framebuffer.size != size {
+                self.framebuffer = ColorImage::new(size, self.clear_color);
+            }
+        }
+    }
+}
+
+impl GfxBackend for SoftBackend {
+    type Configuration = SoftConfig;
+
+    fn try_new(
+        window_backend: &mut impl WindowBackend,
+        config: Self::Configuration,
+    ) -> Result<Self, BackendError> {
+        Self::try_new_for_window(
+            window_backend,
+            WindowId::MAIN,
+            config.clear_color,
+            config.present,
+        )
+    }
+
+    fn suspend(&mut self, _window_backend: &mut impl WindowBackend) {
+        #[cfg(feature = "softbuffer")]
+        {
+            self.presenter = None;
+        }
+    }
+
+    fn resume(&mut self, _window_backend: &mut impl WindowBackend) {
+        #[cfg(feature = "softbuffer")]
+        if self.presenter.is_none() {
+            self.presenter = _window_backend
+                .get_window()
+                .and_then(|window| SoftBufferPresenter::new(window));
+        }
+    }
+
+    fn resize_framebuffer(&mut self, window_backend: &mut impl WindowBackend) {
+        self.resize_to(window_backend.get_live_physical_size_framebuffer());
+    }
+
+    fn prepare_frame(&mut self, window_backend: &mut impl WindowBackend) {
+        // the window might have been resized without an event (eg: first frame)
+        self.resize_framebuffer(window_backend);
+        self.framebuffer.pixels.fill(self.clear_color);
+    }
+
+    fn render_egui(
+        &mut self,
+        meshes: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        self.painter.paint(
+            &mut self.framebuffer,
+            meshes,
+            textures_delta,
+            logical_screen_size,
+        );
+    }
+
+    fn present(&mut self, _window_backend: &mut impl WindowBackend) {
+        if std::mem::take(&mut self.screenshot_requested) {
+            self.screenshot = Some(self.framebuffer.clone());
+        }
+        #[cfg(feature = "softbuffer")]
+        if let Some(presenter) = self.presenter.as_mut() {
+            presenter.present(&self.framebuffer);
+        }
+    }
+
+    fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+
+    fn take_screenshot(&mut self) -> Option<ColorImage> {
+        self.screenshot.take()
+    }
+
+    fn add_window(
+        &mut self,
+        window_backend: &mut impl WindowBackend,
+        id: WindowId,
+    ) -> Result<(), BackendError> {
+        let window = Self::try_new_for_window(window_backend, id, self.clear_color, self.present)?;
+        self.secondary_windows.insert(id, window);
+        Ok(())
+    }
+
+    fn remove_window(&mut self, _window_backend: &mut impl WindowBackend, id: WindowId) {
+        self.secondary_windows.remove(&id);
+    }
+
+    fn resize_framebuffer_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            return self.resize_framebuffer(window_backend);
+        }
+        if let Some(window) = self.secondary_windows.get_mut(&id) {
+            window.resize_to(window_backend.get_live_physical_size_framebuffer_by_id(id));
+        }
+    }
+
+    fn prepare_frame_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            return self.prepare_frame(window_backend);
+        }
+        if let Some(window) = self.secondary_windows.get_mut(&id) {
+            window.resize_to(window_backend.get_live_physical_size_framebuffer_by_id(id));
+            window.framebuffer.pixels.fill(window.clear_color);
+        }
+    }
+
+    fn render_egui_by_id(
+        &mut self,
+        id: WindowId,
+        meshes: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        if id == WindowId::MAIN {
+            return self.render_egui(meshes, textures_delta, logical_screen_size);
+        }
+        if let Some(window) = self.secondary_windows.get_mut(&id) {
+            window.render_egui(meshes, textures_delta, logical_screen_size);
+        }
+    }
+
+    fn present_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if id == WindowId::MAIN {
+            return self.present(window_backend);
+        }
+        if let Some(window) = self.secondary_windows.get_mut(&id) {
+            // presenting only uses the presenter of that window, not the window backend.
+            window.present(window_backend);
+        }
+    }
+}
+
+/// presents the framebuffer to a window using softbuffer
+#[cfg(feature = "softbuffer")]
+pub struct SoftBufferPresenter {
+    pub surface: softbuffer::Surface,
+    /// must be kept alive as long as the surface is alive
+    pub context: softbuffer::Context,
+}
+
+#[cfg(feature = "softbuffer")]
+impl SoftBufferPresenter {
+    /// returns None if softbuffer doesn't support this window (eg: the headless window).
+    pub fn new(
+        window: &(impl raw_window_handle::HasRawWindowHandle + raw_window_handle::HasRawDisplayHandle),
+    ) -> Option<Self> {
+        // Safety: the window is owned by the window backend and will outlive the gfx backend.
+        let context = unsafe { softbuffer::Context::new(window) }
+            .map_err(|e| tracing::error!("failed to create softbuffer context: {e}"))
+            .ok()?;
+        let surface = unsafe { softbuffer::Surface::new(&context, window) }
+            .map_err(|e| tracing::error!("failed to create softbuffer surface: {e}"))
+            .ok()?;
+        Some(Self { surface, context })
+    }
+    /// copies the framebuffer into the window surface and presents it.
+    /// alpha is ignored, as most windows are opaque.
+    pub fn present(&mut self, framebuffer: &ColorImage) {
+        let [width, height] = framebuffer.size;
+        let (Some(nz_width), Some(nz_height)) = (
+            std::num::NonZeroU32::new(width as u32),
+            std::num::NonZeroU32::new(height as u32),
+        ) else {
+            return;
+        };
+        if let Err(e) = self.surface.resize(nz_width, nz_height) {
+            tracing::error!("failed to resize softbuffer surface: {e}");
+            return;
+        }
+        let mut buffer = match self.surface.buffer_mut() {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                tracing::error!("failed to get softbuffer buffer: {e}");
+                return;
+            }
+        };
+        // softbuffer wants 0RGB in native endian u32.
+        for (dst, src) in buffer.iter_mut().zip(framebuffer.pixels.iter()) {
+            *dst = (src.r() as u32) << 16 | (src.g() as u32) << 8 | src.b() as u32;
+        }
+        if let Err(e) = buffer.present() {
+            tracing::error!("failed to present softbuffer buffer: {e}");
+        }
+    }
+}
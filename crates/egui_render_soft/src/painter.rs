@@ -0,0 +1,467 @@
+use std::collections::BTreeMap;
+
+use egui_backend::egui::{
+    ecolor::{gamma_from_linear, linear_from_gamma},
+    epaint::{ImageDelta, Primitive, Vertex},
+    *,
+};
+
+/// A texture in RAM. pixels are premultiplied srgba, just like egui gives them to us.
+pub struct SoftTexture {
+    pub size: [usize; 2],
+    pub pixels: Vec<Color32>,
+    pub options: TextureOptions,
+    /// font texture needs to clamp to edge. all other linear textures will repeat.
+    pub is_font: bool,
+}
+
+impl SoftTexture {
+    /// samples the texture at the uv coordinates and returns the color in *linear* space (but still premultiplied)
+    /// This emulates an srgb texture being sampled by a gpu. texels are converted to linear and then filtered.
+    fn sample(&self, uv: Pos2) -> [f32; 4] {
+        let [width, height] = self.size;
+        if width == 0 || height == 0 {
+            return [0.0; 4];
+        }
+        // same as the samplers in wgpu/glow backends. nearest sampler clamps to edge, linear repeats.
+        let repeat = !self.is_font && self.options.magnification == TextureFilter::Linear;
+        let texel = |x: i64, y: i64| -> [f32; 4] {
+            let (x, y) = if repeat {
+                (x.rem_euclid(width as i64), y.rem_euclid(height as i64))
+            } else {
+                (x.clamp(0, width as i64 - 1), y.clamp(0, height as i64 - 1))
+            };
+            let c = self.pixels[y as usize * width + x as usize];
+            [
+                linear_from_gamma(c.r() as f32 / 255.0),
+                linear_from_gamma(c.g() as f32 / 255.0),
+                linear_from_gamma(c.b() as f32 / 255.0),
+                c.a() as f32 / 255.0,
+            ]
+        };
+        // texel centers are at 0.5 offsets
+        let x = uv.x * width as f32 - 0.5;
+        let y = uv.y * height as f32 - 0.5;
+        match self.options.magnification {
+            TextureFilter::Nearest => texel(x.round() as i64, y.round() as i64),
+            TextureFilter::Linear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let top_left = texel(x0, y0);
+                let top_right = texel(x0 + 1, y0);
+                let bottom_left = texel(x0, y0 + 1);
+                let bottom_right = texel(x0 + 1, y0 + 1);
+                let mut result = [0.0; 4];
+                for i in 0..4 {
+                    let top = top_left[i] + (top_right[i] - top_left[i]) * fx;
+                    let bottom = bottom_left[i] + (bottom_right[i] - bottom_left[i]) * fx;
+                    result[i] = top + (bottom - top) * fy;
+                }
+                result
+            }
+        }
+    }
+}
+
+/// Egui Painter which rasterizes the meshes on the CPU into a [`ColorImage`].
+/// This has no dependency on any gpu api, so you can use it anywhere. But it is *slow*.
+/// Paint callbacks are not supported and will be skipped.
+#[derive(Default)]
+pub struct SoftPainter {
+    /// Textures uploaded by egui itself.
+    pub managed_textures: BTreeMap<u64, SoftTexture>,
+    /// these are exposed to user so that they can insert any custom textures.
+    pub user_textures: BTreeMap<u64, SoftTexture>,
+}
+
+impl SoftPainter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    fn set_textures(&mut self, textures_delta_set: Vec<(TextureId, ImageDelta)>) {
+        for (tex_id, delta) in textures_delta_set {
+            let (pixels, size) = match delta.image {
+                ImageData::Color(color_image) => (color_image.pixels, color_image.size),
+                ImageData::Font(font_image) => (
+                    font_image.srgba_pixels(None).collect::<Vec<_>>(),
+                    font_image.size,
+                ),
+            };
+            let textures = match tex_id {
+                TextureId::Managed(_) => &mut self.managed_textures,
+                TextureId::User(_) => &mut self.user_textures,
+            };
+            let key = match tex_id {
+                TextureId::Managed(key) | TextureId::User(key) => key,
+            };
+            if let Some(pos) = delta.pos {
+                // we only update part of the texture, if the tex id refers to a live texture
+                if let Some(tex) = textures.get_mut(&key) {
+                    for row in 0..size[1] {
+                        let dst_start = (pos[1] + row) * tex.size[0] + pos[0];
+                        tex.pixels[dst_start..dst_start + size[0]]
+                            .copy_from_slice(&pixels[row * size[0]..(row + 1) * size[0]]);
+                    }
+                    tex.options = delta.options;
+                } else {
+                    tracing::warn!("partial update for texture {tex_id:?} which doesn't exist");
+                }
+            } else {
+                textures.insert(
+                    key,
+                    SoftTexture {
+                        size,
+                        pixels,
+                        options: delta.options,
+                        is_font: tex_id == TextureId::Managed(0),
+                    },
+                );
+            }
+        }
+    }
+    fn free_textures(&mut self, textures_to_free: Vec<TextureId>) {
+        for tid in textures_to_free {
+            match tid {
+                TextureId::Managed(key) => {
+                    self.managed_textures.remove(&key);
+                }
+                TextureId::User(key) => {
+                    self.user_textures.remove(&key);
+                }
+            }
+        }
+    }
+    /// uploads textures, draws the meshes into `framebuffer` and then frees the textures which egui asked us to free.
+    /// framebuffer's size is considered to be the physical size of the screen.
+    pub fn paint(
+        &mut self,
+        framebuffer: &mut ColorImage,
+        meshes: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        self.set_textures(textures_delta.set);
+        let physical_framebuffer_size = [framebuffer.size[0] as u32, framebuffer.size[1] as u32];
+        let scale = physical_framebuffer_size[0] as f32 / logical_screen_size[0];
+        for clipped_primitive in meshes {
+            let scissor = if let Some(scissor) = egui_backend::util::scissor_from_clip_rect(
+                &clipped_primitive.clip_rect,
+                scale,
+                physical_framebuffer_size,
+            ) {
+                scissor
+            } else {
+                continue;
+            };
+            match clipped_primitive.primitive {
+                Primitive::Mesh(mesh) => {
+                    let texture = match mesh.texture_id {
+                        TextureId::Managed(key) => self.managed_textures.get(&key),
+                        TextureId::User(key) => self.user_textures.get(&key),
+                    };
+                    if let Some(texture) = texture {
+                        for triangle in mesh.indices.chunks_exact(3) {
+                            rasterize_triangle(
+                                framebuffer,
+                                scissor,
+                                scale,
+                                texture,
+                                [
+                                    &mesh.vertices[triangle[0] as usize],
+                                    &mesh.vertices[triangle[1] as usize],
+                                    &mesh.vertices[triangle[2] as usize],
+                                ],
+                            );
+                        }
+                    } else {
+                        tracing::warn!("cannot find texture {:?}. skipping mesh", mesh.texture_id);
+                    }
+                }
+                Primitive::Callback(_) => {
+                    tracing::warn!("paint callbacks are not supported by soft renderer. skipping");
+                }
+            }
+        }
+        self.free_textures(textures_delta.free);
+    }
+}
+
+/// twice the signed area of the triangle (a, b, p). positive if p is to the left of a -> b
+fn edge_function(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// top-left fill rule of gpus: pixels exactly on an edge are only drawn if it is a top or left edge.
+/// Otherwise, pixels on the shared edge of two triangles (eg: the diagonal of a rect) would be blended twice.
+/// `edge` is the [`edge_function`] of `a -> b` and the triangle must have a positive area.
+fn is_inside(edge: f32, a: [f32; 2], b: [f32; 2]) -> bool {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    edge > 0.0 || (edge == 0.0 && ((dy == 0.0 && dx > 0.0) || dy < 0.0))
+}
+
+/// draws a single triangle with premultiplied alpha blending.
+///
+/// This emulates the `egui_srgb_output.frag` shader:
+/// vertex colors are interpolated in gamma space, texture is sampled in linear space and converted to gamma space
+/// before multiplying with the vertex color. The blending happens in gamma space as the framebuffer is not srgb.
+fn rasterize_triangle(
+    framebuffer: &mut ColorImage,
+    scissor: [u32; 4],
+    scale: f32,
+    texture: &SoftTexture,
+    vertices: [&Vertex; 3],
+) {
+    let mut vertices = vertices;
+    let mut positions = vertices.map(|v| [v.pos.x * scale, v.pos.y * scale]);
+    let mut area = edge_function(positions[0], positions[1], positions[2]);
+    if area == 0.0 {
+        return;
+    }
+    // make the winding order consistent, so that the fill rule below works for both orders
+    if area < 0.0 {
+        vertices.swap(1, 2);
+        positions.swap(1, 2);
+        area = -area;
+    }
+    let [scissor_x, scissor_y, scissor_width, scissor_height] = scissor;
+    // bounding box of the triangle clamped to the scissor rect
+    let min_x = positions.iter().map(|p| p[0]).fold(f32::MAX, f32::min);
+    let max_x = positions.iter().map(|p| p[0]).fold(f32::MIN, f32::max);
+    let min_y = positions.iter().map(|p| p[1]).fold(f32::MAX, f32::min);
+    let max_y = positions.iter().map(|p| p[1]).fold(f32::MIN, f32::max);
+    let min_x = (min_x.floor().max(0.0) as u32).max(scissor_x);
+    let min_y = (min_y.floor().max(0.0) as u32).max(scissor_y);
+    let max_x = (max_x.ceil().max(0.0) as u32).min(scissor_x + scissor_width);
+    let max_y = (max_y.ceil().max(0.0) as u32).min(scissor_y + scissor_height);
+
+    let colors = vertices.map(|v| v.color.to_array().map(|c| c as f32 / 255.0));
+    let width = framebuffer.size[0];
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            // sample at pixel center
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            let e0 = edge_function(positions[1], positions[2], p);
+            let e1 = edge_function(positions[2], positions[0], p);
+            let e2 = edge_function(positions[0], positions[1], p);
+            if !(is_inside(e0, positions[1], positions[2])
+                && is_inside(e1, positions[2], positions[0])
+                && is_inside(e2, positions[0], positions[1]))
+            {
+                continue;
+            }
+            let (w0, w1, w2) = (e0 / area, e1 / area, e2 / area);
+            let uv = Pos2::new(
+                w0 * vertices[0].uv.x + w1 * vertices[1].uv.x + w2 * vertices[2].uv.x,
+                w0 * vertices[0].uv.y + w1 * vertices[1].uv.y + w2 * vertices[2].uv.y,
+            );
+            let texel = texture.sample(uv);
+            let mut src = [0.0f32; 4];
+            for i in 0..4 {
+                let vertex_color = w0 * colors[0][i] + w1 * colors[1][i] + w2 * colors[2][i];
+                let texel = if i == 3 {
+                    texel[i]
+                } else {
+                    gamma_from_linear(texel[i])
+                };
+                src[i] = vertex_color * texel;
+            }
+            let dst_pixel = &mut framebuffer.pixels[y as usize * width + x as usize];
+            let dst = dst_pixel.to_array().map(|c| c as f32 / 255.0);
+            // same blend function as other egui renderers
+            // color: ONE, ONE_MINUS_SRC_ALPHA
+            // alpha: ONE_MINUS_DST_ALPHA, ONE
+            let blended = [
+                src[0] + dst[0] * (1.0 - src[3]),
+                src[1] + dst[1] * (1.0 - src[3]),
+                src[2] + dst[2] * (1.0 - src[3]),
+                src[3] * (1.0 - dst[3]) + dst[3],
+            ]
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+            *dst_pixel =
+                Color32::from_rgba_premultiplied(blended[0], blended[1], blended[2], blended[3]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color32 = Color32::from_rgb(255, 0, 0);
+
+    /// paints a single rect (in logical points) textured with `texture_id` at scale 1.
+    fn paint_rect(
+        painter: &mut SoftPainter,
+        framebuffer: &mut ColorImage,
+        texture_id: TextureId,
+        rect: Rect,
+        clip_rect: Rect,
+        color: Color32,
+        textures_delta: TexturesDelta,
+    ) {
+        let mut mesh = Mesh::with_texture(texture_id);
+        mesh.add_rect_with_uv(
+            rect,
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+            color,
+        );
+        let size = [framebuffer.size[0] as f32, framebuffer.size[1] as f32];
+        painter.paint(
+            framebuffer,
+            vec![ClippedPrimitive {
+                clip_rect,
+                primitive: Primitive::Mesh(mesh),
+            }],
+            textures_delta,
+            size,
+        );
+    }
+
+    fn full_texture(id: TextureId, image: ColorImage, options: TextureOptions) -> TexturesDelta {
+        TexturesDelta {
+            set: vec![(id, ImageDelta::full(image, options))],
+            free: vec![],
+        }
+    }
+
+    fn white_texture() -> TexturesDelta {
+        full_texture(
+            TextureId::User(0),
+            ColorImage::new([1, 1], Color32::WHITE),
+            TextureOptions::NEAREST,
+        )
+    }
+
+    #[test]
+    fn scissor() {
+        let mut painter = SoftPainter::new();
+        let mut framebuffer = ColorImage::new([4, 4], Color32::TRANSPARENT);
+        let screen = Rect::from_min_size(Pos2::ZERO, vec2(4.0, 4.0));
+        let clip_rect = Rect::from_min_max(pos2(1.0, 1.0), pos2(3.0, 3.0));
+        paint_rect(
+            &mut painter,
+            &mut framebuffer,
+            TextureId::User(0),
+            screen,
+            clip_rect,
+            RED,
+            white_texture(),
+        );
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if (1..3).contains(&x) && (1..3).contains(&y) {
+                    RED
+                } else {
+                    Color32::TRANSPARENT
+                };
+                assert_eq!(framebuffer[(x, y)], expected, "pixel at {x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn premultiplied_blending() {
+        let mut painter = SoftPainter::new();
+        let mut framebuffer = ColorImage::new([2, 2], Color32::BLUE);
+        let screen = Rect::from_min_size(Pos2::ZERO, vec2(2.0, 2.0));
+        // half transparent red
+        let color = Color32::from_rgba_premultiplied(128, 0, 0, 128);
+        paint_rect(
+            &mut painter,
+            &mut framebuffer,
+            TextureId::User(0),
+            screen,
+            screen,
+            color,
+            white_texture(),
+        );
+        // src + dst * (1 - src_alpha). alpha stays opaque as dst is opaque.
+        let expected = Color32::from_rgba_premultiplied(128, 0, 127, 255);
+        for pixel in framebuffer.pixels {
+            assert_eq!(pixel, expected);
+        }
+    }
+
+    #[test]
+    fn partial_texture_update() {
+        let mut painter = SoftPainter::new();
+        painter.set_textures(
+            full_texture(
+                TextureId::User(1),
+                ColorImage::new([3, 2], RED),
+                TextureOptions::NEAREST,
+            )
+            .set,
+        );
+        painter.set_textures(vec![(
+            TextureId::User(1),
+            ImageDelta::partial(
+                [1, 0],
+                ColorImage::new([2, 1], Color32::GREEN),
+                TextureOptions::LINEAR,
+            ),
+        )]);
+        let texture = &painter.user_textures[&1];
+        assert_eq!(texture.size, [3, 2]);
+        assert_eq!(
+            texture.pixels,
+            [RED, Color32::GREEN, Color32::GREEN, RED, RED, RED]
+        );
+        assert_eq!(texture.options, TextureOptions::LINEAR);
+        // partial updates of a missing texture are ignored
+        painter.set_textures(vec![(
+            TextureId::User(2),
+            ImageDelta::partial(
+                [0, 0],
+                ColorImage::new([1, 1], RED),
+                TextureOptions::NEAREST,
+            ),
+        )]);
+        assert!(!painter.user_textures.contains_key(&2));
+    }
+
+    #[test]
+    fn sampling() {
+        // a 2x1 black and white texture stretched over 4x1 pixels
+        let image = ColorImage {
+            size: [2, 1],
+            pixels: vec![Color32::BLACK, Color32::WHITE],
+        };
+        let screen = Rect::from_min_size(Pos2::ZERO, vec2(4.0, 1.0));
+        let render = |options| {
+            let mut painter = SoftPainter::new();
+            let mut framebuffer = ColorImage::new([4, 1], Color32::TRANSPARENT);
+            paint_rect(
+                &mut painter,
+                &mut framebuffer,
+                TextureId::User(0),
+                screen,
+                screen,
+                Color32::WHITE,
+                full_texture(TextureId::User(0), image.clone(), options),
+            );
+            framebuffer.pixels
+        };
+
+        let nearest = render(TextureOptions::NEAREST);
+        assert_eq!(
+            nearest,
+            [
+                Color32::BLACK,
+                Color32::BLACK,
+                Color32::WHITE,
+                Color32::WHITE
+            ]
+        );
+
+        // pixel centers at 0.125, 0.375, 0.625 and 0.875 of the texture. filtering is in linear space.
+        // linear sampler repeats, so the first and last pixels are blended with the opposite edge.
+        let gray = |linear: f32| {
+            let c = (gamma_from_linear(linear) * 255.0).round() as u8;
+            Color32::from_rgba_premultiplied(c, c, c, 255)
+        };
+        let linear = render(TextureOptions::LINEAR);
+        assert_eq!(linear, [gray(0.25), gray(0.25), gray(0.75), gray(0.75)]);
+    }
+}
@@ -1,6 +1,6 @@
 use egui_backend::{
     egui::{ClippedPrimitive, TexturesDelta},
-    GfxBackend, WindowBackend,
+    BackendError, GfxBackend, WindowBackend,
 };
 use egui_render_glow::{GlowBackend, GlowConfig};
 pub use three_d;
@@ -18,26 +18,34 @@ pub struct ThreeDConfig {
 impl GfxBackend for ThreeDBackend {
     type Configuration = ThreeDConfig;
 
-    fn new(window_backend: &mut impl WindowBackend, _config: Self::Configuration) -> Self {
-        let glow_backend = GlowBackend::new(window_backend, _config.glow_config);
+    fn try_new(
+        window_backend: &mut impl WindowBackend,
+        _config: Self::Configuration,
+    ) -> Result<Self, BackendError> {
+        let glow_backend = GlowBackend::try_new(window_backend, _config.glow_config)?;
 
         #[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
         {
             use three_d::HasContext;
             let supported_extension = (glow_backend.glow_context).supported_extensions();
-
-            assert!(supported_extension.contains("EXT_color_buffer_float"));
-
-            assert!(supported_extension.contains("OES_texture_float"));
-
-            assert!(supported_extension.contains("OES_texture_float_linear"));
+            for extension in [
+                "EXT_color_buffer_float",
+                "OES_texture_float",
+                "OES_texture_float_linear",
+            ] {
+                if !supported_extension.contains(extension) {
+                    return Err(BackendError::GlContextCreation(format!(
+                        "three-d requires {extension} extension"
+                    )));
+                }
+            }
         }
 
-        Self {
+        Ok(Self {
             context: Context::from_gl_context(glow_backend.glow_context.clone())
-                .expect("failed to create threed context"),
+                .map_err(|e| BackendError::GlContextCreation(e.to_string()))?,
             glow_backend,
-        }
+        })
     }
 
     fn suspend(&mut self, _window_backend: &mut impl WindowBackend) {}
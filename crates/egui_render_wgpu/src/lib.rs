@@ -1,14 +1,17 @@
 mod painter;
+mod screenshot;
 mod surface;
 
 use egui_backend::egui;
-use egui_backend::{GfxBackend, WindowBackend};
+use egui_backend::{BackendError, GfxBackend, WindowBackend, WindowId};
 use raw_window_handle::HasRawWindowHandle;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tracing::{debug, info};
 use wgpu::*;
 
 pub use painter::*;
+pub use screenshot::PendingScreenshot;
 pub use surface::SurfaceManager;
 pub use wgpu;
 
@@ -20,6 +23,9 @@ pub struct WgpuConfig {
     /// If this is empty or none of the formats in this vector are supported, we will just use the first supported format of the surface.
     pub surface_formats_priority: Vec<TextureFormat>,
     /// we will try to use this config if supported. otherwise, the surface recommended options will be used.   
+    ///
+    /// `present_mode` defaults to fifo, which lets [`egui_backend::BackendConfig::vsync`] choose the present mode.
+    /// Any other present mode takes precedence over vsync (if the surface supports it).
     pub surface_config: SurfaceConfiguration,
 }
 impl Default for WgpuConfig {
@@ -64,6 +70,13 @@ pub struct WgpuBackend {
     /// `wgpu::Queue::submit` is very expensive, so we will submit ALL command encoders at the same time during the `present_frame` method
     /// just before presenting the swapchain image (surface texture).
     pub command_encoders: Vec<CommandEncoder>,
+    /// windows added with [`GfxBackend::add_window`]. Each one is a wgpu backend of its own which shares the instance, device etc..
+    /// with us, but has its own surface and painter. their own `secondary_windows` is always empty.
+    pub secondary_windows: BTreeMap<WindowId, WgpuBackend>,
+    /// set by [`GfxBackend::request_screenshot`]. we copy the surface texture into a buffer during the next present.
+    pub screenshot_requested: bool,
+    /// screenshot which is being read back from the gpu. [`GfxBackend::take_screenshot`] takes it once it is ready.
+    pub pending_screenshot: Option<PendingScreenshot>,
 }
 impl Drop for WgpuBackend {
     fn drop(&mut self) {
@@ -71,10 +84,19 @@ impl Drop for WgpuBackend {
     }
 }
 impl WgpuBackend {
+    /// same as [`Self::try_new_async`], but panics on error
     pub async fn new_async(
         window_backend: &mut impl WindowBackend,
         config: <Self as GfxBackend>::Configuration,
     ) -> Self {
+        Self::try_new_async(window_backend, config)
+            .await
+            .unwrap_or_else(|e| panic!("failed to create wgpu backend: {e}"))
+    }
+    pub async fn try_new_async(
+        window_backend: &mut impl WindowBackend,
+        config: <Self as GfxBackend>::Configuration,
+    ) -> Result<Self, BackendError> {
         let WgpuConfig {
             power_preference,
             device_descriptor,
@@ -93,12 +115,17 @@ impl WgpuBackend {
             debug!("adapter: {:#?}", adapter.get_info());
         }
 
-        let surface = window_backend.get_window().map(|w| unsafe {
-            tracing::debug!("creating a surface with {:?}", w.raw_window_handle());
-            instance
-                .create_surface(w)
-                .expect("failed to create surface")
-        });
+        // without a real window, we render offscreen. see `SurfaceManager::offscreen_texture`
+        let window_surface = window_backend.capabilities().window_surface;
+        let surface = window_backend
+            .get_window()
+            .filter(|_| window_surface)
+            .map(|w| unsafe {
+                tracing::debug!("creating a surface with {:?}", w.raw_window_handle());
+                instance.create_surface(w)
+            })
+            .transpose()
+            .map_err(|e| BackendError::SurfaceCreation(e.to_string()))?;
 
         info!("is surfaced created at startup?: {}", surface.is_some());
 
@@ -111,19 +138,19 @@ impl WgpuBackend {
                     compatible_surface: surface.as_ref(),
                 })
                 .await
-                .expect("failed to get adapter"),
+                .ok_or(BackendError::AdapterNotFound)?,
         );
 
         info!("chosen adapter details: {:?}", adapter.get_info());
         let (device, queue) = adapter
             .request_device(&device_descriptor, Default::default())
             .await
-            .expect("failed to create wgpu device");
+            .map_err(|e| BackendError::DeviceCreation(e.to_string()))?;
 
         let device = Arc::new(device);
         let queue = Arc::new(queue);
 
-        let surface_manager = SurfaceManager::new(
+        let surface_manager = SurfaceManager::try_new(
             window_backend,
             &instance,
             &adapter,
@@ -131,14 +158,14 @@ impl WgpuBackend {
             surface,
             surface_formats_priority,
             surface_config,
-        );
+        )?;
 
         debug!("device features: {:#?}", device.features());
         debug!("device limits: {:#?}", device.limits());
 
         let painter = EguiPainter::new(&device, surface_manager.surface_config.format);
 
-        Self {
+        Ok(Self {
             instance,
             adapter,
             device,
@@ -146,14 +173,90 @@ impl WgpuBackend {
             painter,
             command_encoders: Vec::new(),
             surface_manager,
+            secondary_windows: BTreeMap::new(),
+            screenshot_requested: false,
+            pending_screenshot: None,
+        })
+    }
+    /// renders egui into `target` instead of the window surface. eg: a texture used by your 3d scene or a thumbnail.
+    ///
+    /// Unlike [`GfxBackend::render_egui`], this submits the commands right away, as the painter reuses its buffers for every draw.
+    /// Any pending `command_encoders` (eg: the egui pass of the window) are submitted first, so that they still draw their own data.
+    /// `textures_delta` is applied to the painter's textures. So, if you render the same egui output to the window too,
+    /// pass the delta only once.
+    pub fn render_egui_to_texture_view(
+        &mut self,
+        target: &EguiRenderTarget,
+        meshes: Vec<egui::ClippedPrimitive>,
+        textures_delta: egui::TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        // buffer writes of a submit happen before all of its command buffers. so, if we submitted the pending commands together
+        // with ours, they would draw with the data we are about to write.
+        self.queue.submit(
+            std::mem::take(&mut self.command_encoders)
+                .into_iter()
+                .map(|encoder| encoder.finish()),
+        );
+        let mut command_encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("egui render to texture command encoder"),
+            });
+        self.painter.render_egui_to_texture_view(
+            &self.device,
+            &self.queue,
+            &mut command_encoder,
+            target,
+            meshes,
+            textures_delta,
+            logical_screen_size,
+        );
+        self.queue.submit(std::iter::once(command_encoder.finish()));
+    }
+    /// records a copy of the current surface texture into a buffer. the command encoder is pushed into `command_encoders`.
+    fn copy_surface_for_screenshot(&mut self) -> Option<PendingScreenshot> {
+        let format = self.surface_manager.surface_config.format;
+        if !PendingScreenshot::is_format_supported(format) {
+            tracing::error!("can't take screenshot. surface format {format:?} is not supported");
+            return None;
+        }
+        if !self
+            .surface_manager
+            .surface_config
+            .usage
+            .contains(TextureUsages::COPY_SRC)
+        {
+            tracing::error!("can't take screenshot. surface doesn't support COPY_SRC usage");
+            return None;
+        }
+        let texture = self.surface_manager.current_texture()?;
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("screenshot command encoder"),
+            });
+        let screenshot = PendingScreenshot::new(&self.device, &mut encoder, texture, format);
+        self.command_encoders.push(encoder);
+        Some(screenshot)
+    }
+    /// returns the backend of the window with this id. [`WindowId::MAIN`] returns self.
+    pub fn window_by_id(&mut self, id: WindowId) -> Option<&mut Self> {
+        if id == WindowId::MAIN {
+            Some(self)
+        } else {
+            self.secondary_windows.get_mut(&id)
         }
     }
 }
 impl GfxBackend for WgpuBackend {
     type Configuration = WgpuConfig;
 
-    fn new(window_backend: &mut impl WindowBackend, config: Self::Configuration) -> Self {
-        pollster::block_on(Self::new_async(window_backend, config))
+    fn try_new(
+        window_backend: &mut impl WindowBackend,
+        config: Self::Configuration,
+    ) -> Result<Self, BackendError> {
+        pollster::block_on(Self::try_new_async(window_backend, config))
     }
 
     fn resume(&mut self, window_backend: &mut impl WindowBackend) {
@@ -210,59 +313,61 @@ impl GfxBackend for WgpuBackend {
             .create_command_encoder(&CommandEncoderDescriptor {
                 label: Some("egui command encoder"),
             });
-        let draw_calls = self.painter.upload_egui_data(
+        let view = self
+            .surface_manager
+            .surface_view
+            .as_ref()
+            .expect("failed ot get surface view for egui render pass creation");
+        self.painter.render_egui_to_texture_view(
             &self.device,
             &self.queue,
+            &mut command_encoder,
+            &EguiRenderTarget {
+                view,
+                format: self.painter.surface_format,
+                size: [
+                    self.surface_manager.surface_config.width,
+                    self.surface_manager.surface_config.height,
+                ],
+                clear_color: None,
+            },
             meshes,
             textures_delta,
             logical_screen_size,
-            [
-                self.surface_manager.surface_config.width,
-                self.surface_manager.surface_config.height,
-            ],
-            &mut command_encoder,
         );
-        {
-            let mut egui_pass = command_encoder.begin_render_pass(&RenderPassDescriptor {
-                label: Some("egui render pass"),
-                color_attachments: &[Some(RenderPassColorAttachment {
-                    view: self
-                        .surface_manager
-                        .surface_view
-                        .as_ref()
-                        .expect("failed ot get surface view for egui render pass creation"),
-                    resolve_target: None,
-                    ops: Operations {
-                        load: LoadOp::Load,
-                        store: true,
-                    },
-                })],
-                depth_stencil_attachment: None,
-            });
-            self.painter
-                .draw_egui_with_renderpass(&mut egui_pass, draw_calls);
-        }
         self.command_encoders.push(command_encoder);
     }
 
     fn present(&mut self, _window_backend: &mut impl WindowBackend) {
         assert!(self.surface_manager.surface_view.is_some());
+        let screenshot = if std::mem::take(&mut self.screenshot_requested) {
+            self.copy_surface_for_screenshot()
+        } else {
+            None
+        };
         self.queue.submit(
             std::mem::take(&mut self.command_encoders)
                 .into_iter()
                 .map(|encoder| encoder.finish()),
         );
+        if let Some(screenshot) = screenshot {
+            screenshot.map(&self.device);
+            self.pending_screenshot = Some(screenshot);
+        }
         {
             self.surface_manager
                 .surface_view
                 .take()
                 .expect("failed to get surface view to present");
         }
-        self.surface_manager
-            .surface_current_image
-            .take()
-            .expect("failed to surface texture to preset")
-            .present();
+        // offscreen frames just stay in the texture
+        if self.surface_manager.offscreen_texture.is_none() {
+            self.surface_manager
+                .surface_current_image
+                .take()
+                .expect("failed to surface texture to preset")
+                .present();
+        }
     }
 
     fn resize_framebuffer(&mut self, window_backend: &mut impl WindowBackend) {
@@ -273,4 +378,187 @@ impl GfxBackend for WgpuBackend {
     fn suspend(&mut self, _window_backend: &mut impl WindowBackend) {
         self.surface_manager.suspend();
     }
+
+    fn request_screenshot(&mut self) {
+        self.screenshot_requested = true;
+    }
+
+    fn take_screenshot(&mut self) -> Option<egui::ColorImage> {
+        if !self.pending_screenshot.as_ref()?.is_ready() {
+            return None;
+        }
+        self.pending_screenshot.take()?.read()
+    }
+
+    fn add_window(
+        &mut self,
+        window_backend: &mut impl WindowBackend,
+        id: WindowId,
+    ) -> Result<(), BackendError> {
+        // use the same settings as main window's surface. format might still be different, if the new surface doesn't support it.
+        let surface_manager = SurfaceManager::try_new_for_window(
+            id,
+            window_backend,
+            &self.instance,
+            &self.adapter,
+            &self.device,
+            None,
+            self.surface_manager.surface_formats_priority.clone(),
+            self.surface_manager.surface_config.clone(),
+        )?;
+        let painter = EguiPainter::new(&self.device, surface_manager.surface_config.format);
+        self.secondary_windows.insert(
+            id,
+            Self {
+                instance: self.instance.clone(),
+                adapter: self.adapter.clone(),
+                device: self.device.clone(),
+                queue: self.queue.clone(),
+                painter,
+                surface_manager,
+                command_encoders: Vec::new(),
+                secondary_windows: BTreeMap::new(),
+                screenshot_requested: false,
+                pending_screenshot: None,
+            },
+        );
+        Ok(())
+    }
+
+    fn remove_window(&mut self, _window_backend: &mut impl WindowBackend, id: WindowId) {
+        self.secondary_windows.remove(&id);
+    }
+
+    // the surface managers of secondary windows know their window id. So, we can just call the non-id fns on them.
+    fn resize_framebuffer_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if let Some(window) = self.window_by_id(id) {
+            window.resize_framebuffer(window_backend);
+        }
+    }
+
+    fn prepare_frame_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if let Some(window) = self.window_by_id(id) {
+            window.prepare_frame(window_backend);
+        }
+    }
+
+    fn render_egui_by_id(
+        &mut self,
+        id: WindowId,
+        meshes: Vec<egui::ClippedPrimitive>,
+        textures_delta: egui::TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        if let Some(window) = self.window_by_id(id) {
+            window.render_egui(meshes, textures_delta, logical_screen_size);
+        }
+    }
+
+    fn present_by_id(&mut self, window_backend: &mut impl WindowBackend, id: WindowId) {
+        if let Some(window) = self.window_by_id(id) {
+            window.present(window_backend);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Color32, LayerId, Pos2, RawInput, Rect};
+    use egui_window_headless::{HeadlessBackend, HeadlessConfig};
+
+    const SIZE: [u32; 2] = [64, 64];
+
+    /// tessellated output of an egui frame which fills the screen with `color`
+    fn fill_screen(
+        ctx: &egui::Context,
+        color: Color32,
+    ) -> (Vec<egui::ClippedPrimitive>, egui::TexturesDelta) {
+        let raw_input = RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                [SIZE[0] as f32, SIZE[1] as f32].into(),
+            )),
+            ..Default::default()
+        };
+        let output = ctx.run(raw_input, |ctx| {
+            ctx.layer_painter(LayerId::background()).rect_filled(
+                ctx.screen_rect().expand(1.0),
+                0.0,
+                color,
+            );
+        });
+        (ctx.tessellate(output.shapes), output.textures_delta)
+    }
+
+    #[test]
+    fn render_to_texture_and_window_in_the_same_frame() {
+        let mut window_backend = HeadlessBackend::try_new(
+            HeadlessConfig {
+                logical_size: [SIZE[0] as f32, SIZE[1] as f32],
+                ..Default::default()
+            },
+            Default::default(),
+        )
+        .unwrap();
+        let mut backend = match WgpuBackend::try_new(&mut window_backend, Default::default()) {
+            Ok(backend) => backend,
+            Err(BackendError::AdapterNotFound) => {
+                eprintln!("skipping wgpu test. no adapter found");
+                return;
+            }
+            Err(e) => panic!("failed to create wgpu backend: {e}"),
+        };
+        let format = TextureFormat::Rgba8UnormSrgb;
+        let texture = backend.device.create_texture(&TextureDescriptor {
+            label: Some("render to texture test"),
+            size: Extent3d {
+                width: SIZE[0],
+                height: SIZE[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&Default::default());
+        let ctx = egui::Context::default();
+        let (window_meshes, textures_delta) = fill_screen(&ctx, Color32::RED);
+        let (texture_meshes, _) = fill_screen(&ctx, Color32::BLUE);
+        let logical_size = [SIZE[0] as f32, SIZE[1] as f32];
+
+        // window first, then the texture. both use the buffers of the same painter.
+        backend.prepare_frame(&mut window_backend);
+        backend.render_egui(window_meshes, textures_delta, logical_size);
+        backend.render_egui_to_texture_view(
+            &EguiRenderTarget {
+                view: &view,
+                format,
+                size: SIZE,
+                clear_color: Some(wgpu::Color::BLACK),
+            },
+            texture_meshes,
+            Default::default(),
+            logical_size,
+        );
+        backend.request_screenshot();
+        backend.present(&mut window_backend);
+
+        let window_image = backend.take_screenshot().unwrap();
+        let mut encoder = backend
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor::default());
+        let texture_screenshot =
+            PendingScreenshot::new(&backend.device, &mut encoder, &texture, format);
+        backend.queue.submit(std::iter::once(encoder.finish()));
+        texture_screenshot.map(&backend.device);
+        let texture_image = texture_screenshot.read().unwrap();
+
+        assert_eq!(window_image.size, [SIZE[0] as usize, SIZE[1] as usize]);
+        assert!(window_image.pixels.iter().all(|&p| p == Color32::RED));
+        assert!(texture_image.pixels.iter().all(|&p| p == Color32::BLUE));
+    }
 }
@@ -1,4 +1,8 @@
-use std::{collections::BTreeMap, num::NonZeroU64, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    num::NonZeroU64,
+    sync::Arc,
+};
 
 use bytemuck::cast_slice;
 use egui_backend::egui::{
@@ -30,6 +34,8 @@ pub struct EguiPainter {
     pub surface_format: TextureFormat,
     /// egui render pipeline
     pub pipeline: RenderPipeline,
+    /// pipelines for render targets whose format is not `surface_format`. created by [`Self::create_pipeline_for_format`]
+    pub format_pipelines: HashMap<TextureFormat, RenderPipeline>,
     /// This is the sampler used for most textures that user uploads
     pub linear_sampler: Sampler,
     /// nearest sampler suitable for font textures (or any pixellated textures)
@@ -67,6 +73,17 @@ impl Default for CallbackFn {
     }
 }
 
+/// A texture view (or any other render attachment) to draw egui into. See [`EguiPainter::render_egui_to_texture_view`]
+pub struct EguiRenderTarget<'a> {
+    pub view: &'a TextureView,
+    /// format of the `view`. must be a format that can be used as a color render attachment
+    pub format: TextureFormat,
+    /// size of the view in pixels
+    pub size: [u32; 2],
+    /// clears the view with this color before drawing egui. `None` draws on top of the existing contents.
+    pub clear_color: Option<wgpu::Color>,
+}
+
 /// We take all the
 pub enum EguiDrawCalls {
     Mesh {
@@ -83,13 +100,30 @@ pub enum EguiDrawCalls {
     },
 }
 impl EguiPainter {
+    /// draws egui with the pipeline of [`Self::surface_format`]
     pub fn draw_egui_with_renderpass<'rpass>(
         &'rpass self,
         rpass: &mut RenderPass<'rpass>,
         draw_calls: Vec<EguiDrawCalls>,
     ) {
+        self.draw_egui_with_renderpass_and_format(rpass, draw_calls, self.surface_format);
+    }
+    /// same as [`Self::draw_egui_with_renderpass`], but for a render pass whose attachment has the given format.
+    /// call [`Self::create_pipeline_for_format`] with that format first. otherwise, nothing is drawn.
+    pub fn draw_egui_with_renderpass_and_format<'rpass>(
+        &'rpass self,
+        rpass: &mut RenderPass<'rpass>,
+        draw_calls: Vec<EguiDrawCalls>,
+        format: TextureFormat,
+    ) {
+        let Some(pipeline) = self.get_pipeline_for_format(format) else {
+            tracing::error!(
+                "no egui pipeline for format {format:?}. call create_pipeline_for_format first"
+            );
+            return;
+        };
         // rpass.set_viewport(0.0, 0.0, width as f32, height as f32, 0.0, 1.0);
-        rpass.set_pipeline(&self.pipeline);
+        rpass.set_pipeline(pipeline);
         rpass.set_bind_group(0, &self.screen_size_bind_group, &[]);
 
         rpass.set_vertex_buffer(0, self.vb.slice(..));
@@ -196,6 +230,69 @@ impl EguiPainter {
             multiview: None,
         })
     }
+    /// creates a pipeline which can render to `format`, unless we already have one.
+    pub fn create_pipeline_for_format(&mut self, dev: &Device, format: TextureFormat) {
+        if format == self.surface_format || self.format_pipelines.contains_key(&format) {
+            return;
+        }
+        let pipeline = Self::create_render_pipeline(
+            dev,
+            format,
+            &self.screen_size_bindgroup_layout,
+            &self.texture_bindgroup_layout,
+        );
+        self.format_pipelines.insert(format, pipeline);
+    }
+    pub fn get_pipeline_for_format(&self, format: TextureFormat) -> Option<&RenderPipeline> {
+        if format == self.surface_format {
+            Some(&self.pipeline)
+        } else {
+            self.format_pipelines.get(&format)
+        }
+    }
+    /// uploads egui data and records a render pass which draws egui into `target.view`.
+    ///
+    /// The vertex/index/uniform buffers of the painter are written using `queue`, and those writes happen at the next submit.
+    /// So, if you draw multiple egui frames with the same painter, submit the `encoder` before drawing the next one.
+    /// And if you draw the output of a different egui context, use a different painter, as texture ids will clash.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_egui_to_texture_view(
+        &mut self,
+        dev: &Device,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        target: &EguiRenderTarget,
+        meshes: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        self.create_pipeline_for_format(dev, target.format);
+        let draw_calls = self.upload_egui_data(
+            dev,
+            queue,
+            meshes,
+            textures_delta,
+            logical_screen_size,
+            target.size,
+            encoder,
+        );
+        let mut egui_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("egui render pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target.view,
+                resolve_target: None,
+                ops: Operations {
+                    load: match target.clear_color {
+                        Some(color) => LoadOp::Clear(color),
+                        None => LoadOp::Load,
+                    },
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        self.draw_egui_with_renderpass_and_format(&mut egui_pass, draw_calls, target.format);
+    }
     pub fn new(dev: &Device, surface_format: TextureFormat) -> Self {
         // create uniform buffer for screen size
         let screen_size_buffer = dev.create_buffer(&BufferDescriptor {
@@ -354,6 +451,7 @@ impl EguiPainter {
             custom_data: IdTypeMap::default(),
             screen_size_bindgroup_layout,
             surface_format,
+            format_pipelines: HashMap::new(),
             mipmap_pipeline,
             mipmap_bgl,
             mipmap_sampler,
@@ -362,12 +460,22 @@ impl EguiPainter {
     }
     pub fn on_resume(&mut self, dev: &Device, surface_format: TextureFormat) {
         if self.surface_format != surface_format {
-            self.pipeline = Self::create_render_pipeline(
-                dev,
-                surface_format,
-                &self.screen_size_bindgroup_layout,
-                &self.texture_bindgroup_layout,
-            );
+            let pipeline = self
+                .format_pipelines
+                .remove(&surface_format)
+                .unwrap_or_else(|| {
+                    Self::create_render_pipeline(
+                        dev,
+                        surface_format,
+                        &self.screen_size_bindgroup_layout,
+                        &self.texture_bindgroup_layout,
+                    )
+                });
+            // keep the old pipeline around, in case user still renders to textures of that format.
+            let old_pipeline = std::mem::replace(&mut self.pipeline, pipeline);
+            self.format_pipelines
+                .insert(self.surface_format, old_pipeline);
+            self.surface_format = surface_format;
         }
     }
     fn set_textures(
@@ -390,7 +498,7 @@ impl EguiPainter {
             let mut is_this_font_texure = false;
             // no need for mipmaps if we are dealing with font texture
             let mip_level_count = match tex_id {
-                TextureId::Managed(tid) if tid == 0 => {
+                TextureId::Managed(0) => {
                     is_this_font_texure = true;
                     1
                 }
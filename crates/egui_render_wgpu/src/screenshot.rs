@@ -0,0 +1,128 @@
+use egui_backend::egui::{Color32, ColorImage};
+use std::sync::{Arc, Mutex};
+use wgpu::*;
+
+/// a copy of the surface texture which is being read back from the gpu.
+pub struct PendingScreenshot {
+    pub buffer: Buffer,
+    /// size of the surface texture in pixels
+    pub size: [u32; 2],
+    /// bytes per row in the buffer. rows are padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`]
+    pub padded_bytes_per_row: u32,
+    pub format: TextureFormat,
+    /// set by the `map_async` callback. `Some(true)` if the buffer is mapped.
+    pub mapped: Arc<Mutex<Option<bool>>>,
+}
+
+impl PendingScreenshot {
+    /// returns true if we know how to convert this format into [`ColorImage`].
+    pub fn is_format_supported(format: TextureFormat) -> bool {
+        matches!(
+            format,
+            TextureFormat::Rgba8Unorm
+                | TextureFormat::Rgba8UnormSrgb
+                | TextureFormat::Bgra8Unorm
+                | TextureFormat::Bgra8UnormSrgb
+        )
+    }
+    /// records a copy of `texture` into a new buffer. The texture must have [`TextureUsages::COPY_SRC`].
+    /// call [`Self::map`] after the encoder is submitted.
+    pub fn new(
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        texture: &Texture,
+        format: TextureFormat,
+    ) -> Self {
+        let size = [texture.width(), texture.height()];
+        let unpadded_bytes_per_row = size[0] * 4;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            + (COPY_BYTES_PER_ROW_ALIGNMENT
+                - unpadded_bytes_per_row % COPY_BYTES_PER_ROW_ALIGNMENT)
+                % COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("screenshot buffer"),
+            size: padded_bytes_per_row as u64 * size[1] as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size[1]),
+                },
+            },
+            Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+        );
+        Self {
+            buffer,
+            size,
+            padded_bytes_per_row,
+            format,
+            mapped: Default::default(),
+        }
+    }
+    /// starts mapping the buffer. On native, we just wait for it to finish.
+    /// On web, it will be mapped some time later. so, check [`Self::is_ready`] before reading.
+    pub fn map(&self, device: &Device) {
+        let mapped = self.mapped.clone();
+        self.buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                if let Err(e) = &result {
+                    tracing::error!("failed to map screenshot buffer: {e}");
+                }
+                *mapped.lock().expect("failed to lock screenshot mutex") = Some(result.is_ok());
+            });
+        device.poll(Maintain::Wait);
+    }
+    /// true if mapping is done (or failed).
+    pub fn is_ready(&self) -> bool {
+        self.mapped
+            .lock()
+            .expect("failed to lock screenshot mutex")
+            .is_some()
+    }
+    /// returns `None` if the buffer is not mapped (yet).
+    pub fn read(&self) -> Option<ColorImage> {
+        if *self.mapped.lock().expect("failed to lock screenshot mutex") != Some(true) {
+            return None;
+        }
+        let [width, height] = self.size;
+        let bgra = matches!(
+            self.format,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+        );
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        {
+            let data = self.buffer.slice(..).get_mapped_range();
+            for row in data.chunks_exact(self.padded_bytes_per_row as usize) {
+                // egui uses srgb output shader for non-srgb formats. so, both srgb and linear formats contain srgb encoded colors.
+                pixels.extend(row[..width as usize * 4].chunks_exact(4).map(|p| {
+                    if bgra {
+                        Color32::from_rgba_premultiplied(p[2], p[1], p[0], p[3])
+                    } else {
+                        Color32::from_rgba_premultiplied(p[0], p[1], p[2], p[3])
+                    }
+                }));
+            }
+        }
+        self.buffer.unmap();
+        Some(ColorImage {
+            size: [width as usize, height as usize],
+            pixels,
+        })
+    }
+}
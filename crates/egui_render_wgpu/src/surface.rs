@@ -1,4 +1,4 @@
-use egui_backend::WindowBackend;
+use egui_backend::{BackendError, WindowBackend, WindowId};
 use raw_window_handle::HasRawWindowHandle;
 use tracing::{debug, info};
 use wgpu::*;
@@ -12,13 +12,19 @@ pub struct SurfaceManager {
     pub surface_current_image: Option<SurfaceTexture>,
     /// this is the window surface
     pub surface: Option<Surface>,
+    /// used instead of `surface` if the window backend has no real window (eg: headless).
+    /// see [`egui_backend::WindowCapabilities::window_surface`]. frames are rendered into this texture and stay there,
+    /// so use screenshots to read them back.
+    pub offscreen_texture: Option<Texture>,
     /// this configuration needs to be updated with the latest resize
     pub surface_config: SurfaceConfiguration,
     /// Surface manager will iterate over this and find the first format that is supported by surface.
     /// if we find one, we will set surface configuration to that format.
     /// if we don't find one, we will just use the first surface format support.
     /// so, if you don't care about the surface format, just set this to an empty vector.
-    surface_formats_priority: Vec<TextureFormat>,
+    pub(crate) surface_formats_priority: Vec<TextureFormat>,
+    /// the window of this surface. [`WindowId::MAIN`] unless created with [`Self::try_new_for_window`]
+    pub window_id: WindowId,
 }
 impl Drop for SurfaceManager {
     fn drop(&mut self) {
@@ -26,6 +32,7 @@ impl Drop for SurfaceManager {
     }
 }
 impl SurfaceManager {
+    /// same as [`Self::try_new`], but panics on error
     pub fn new(
         window_backend: &mut impl WindowBackend,
         instance: &Instance,
@@ -35,24 +42,83 @@ impl SurfaceManager {
         surface_formats_priority: Vec<TextureFormat>,
         surface_config: SurfaceConfiguration,
     ) -> Self {
+        Self::try_new(
+            window_backend,
+            instance,
+            adapter,
+            device,
+            surface,
+            surface_formats_priority,
+            surface_config,
+        )
+        .unwrap_or_else(|e| panic!("failed to create surface manager: {e}"))
+    }
+    pub fn try_new(
+        window_backend: &mut impl WindowBackend,
+        instance: &Instance,
+        adapter: &Adapter,
+        device: &Device,
+        surface: Option<Surface>,
+        surface_formats_priority: Vec<TextureFormat>,
+        surface_config: SurfaceConfiguration,
+    ) -> Result<Self, BackendError> {
+        Self::try_new_for_window(
+            WindowId::MAIN,
+            window_backend,
+            instance,
+            adapter,
+            device,
+            surface,
+            surface_formats_priority,
+            surface_config,
+        )
+    }
+    /// same as [`Self::try_new`], but for the surface of any window. surface will be created if it is `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new_for_window(
+        window_id: WindowId,
+        window_backend: &mut impl WindowBackend,
+        instance: &Instance,
+        adapter: &Adapter,
+        device: &Device,
+        surface: Option<Surface>,
+        surface_formats_priority: Vec<TextureFormat>,
+        surface_config: SurfaceConfiguration,
+    ) -> Result<Self, BackendError> {
         let mut surface_manager = Self {
             surface_view: None,
             surface_current_image: None,
             surface,
+            offscreen_texture: None,
             surface_config,
             surface_formats_priority,
+            window_id,
         };
-        surface_manager.reconfigure_surface(window_backend, instance, adapter, device);
-        surface_manager
+        surface_manager.try_reconfigure_surface(window_backend, instance, adapter, device)?;
+        Ok(surface_manager)
     }
     pub fn create_current_surface_texture_view(
         &mut self,
         window_backend: &mut impl WindowBackend,
         device: &Device,
     ) {
-        if let Some(surface) = self.surface.as_ref() {
+        if self.offscreen_texture.is_some() {
+            // we might not get resize events without a real window. so, check the size every frame.
+            if window_backend.get_live_physical_size_framebuffer_by_id(self.window_id)
+                != Some([self.surface_config.width, self.surface_config.height])
+            {
+                self.resize_framebuffer(device, window_backend);
+            }
+            let texture = self.offscreen_texture.as_ref().unwrap();
+            self.surface_view = Some(texture.create_view(&TextureViewDescriptor {
+                label: Some("offscreen view"),
+                ..Default::default()
+            }));
+        } else if let Some(surface) = self.surface.as_ref() {
             let current_surface_image = surface.get_current_texture().unwrap_or_else(|_| {
-                let phy_fb_size = window_backend.get_live_physical_size_framebuffer().unwrap();
+                let phy_fb_size = window_backend
+                    .get_live_physical_size_framebuffer_by_id(self.window_id)
+                    .unwrap();
                 self.surface_config.width = phy_fb_size[0];
                 self.surface_config.height = phy_fb_size[1];
                 surface.configure(device, &self.surface_config);
@@ -84,23 +150,41 @@ impl SurfaceManager {
             );
         }
     }
+    /// same as [`Self::try_reconfigure_surface`], but panics on error
+    pub fn reconfigure_surface(
+        &mut self,
+        window_backend: &mut impl WindowBackend,
+        instance: &Instance,
+        adapter: &Adapter,
+        device: &Device,
+    ) {
+        self.try_reconfigure_surface(window_backend, instance, adapter, device)
+            .unwrap_or_else(|e| panic!("failed to reconfigure surface: {e}"))
+    }
     /// This basically checks if the surface needs creating. and then if needed, creates surface if window exists.
     /// then, it does all the work of configuring the surface.
     /// this is used during resume events to create a surface.
-    pub fn reconfigure_surface(
+    pub fn try_reconfigure_surface(
         &mut self,
         window_backend: &mut impl WindowBackend,
         instance: &Instance,
         adapter: &Adapter,
         device: &Device,
-    ) {
-        if let Some(window) = window_backend.get_window() {
+    ) -> Result<(), BackendError> {
+        if !window_backend.capabilities().window_surface {
+            if window_backend.get_window_by_id(self.window_id).is_some() {
+                self.configure_offscreen(adapter);
+                self.resize_framebuffer(device, window_backend);
+            }
+            return Ok(());
+        }
+        if let Some(window) = window_backend.get_window_by_id(self.window_id) {
             if self.surface.is_none() {
                 self.surface = Some(unsafe {
                     tracing::debug!("creating a surface with {:?}", window.raw_window_handle());
                     instance
                         .create_surface(window)
-                        .expect("failed to create surface")
+                        .map_err(|e| BackendError::SurfaceCreation(e.to_string()))?
                 });
             }
 
@@ -121,7 +205,38 @@ impl SurfaceManager {
                     }
                 }
             }
+            debug!(
+                "supported present modes: {:#?}",
+                &capabilities.present_modes[..]
+            );
+            // vsync only chooses the present mode if the user left the default (fifo).
+            // fifo is always supported. without vsync, prefer mailbox (no tearing) over immediate.
+            match self.surface_config.present_mode {
+                PresentMode::Fifo => {
+                    if !window_backend.get_config().vsync {
+                        self.surface_config.present_mode =
+                            [PresentMode::Mailbox, PresentMode::Immediate]
+                                .into_iter()
+                                .find(|mode| capabilities.present_modes.contains(mode))
+                                .unwrap_or(PresentMode::Fifo);
+                    }
+                }
+                // wgpu resolves the auto modes itself
+                PresentMode::AutoVsync | PresentMode::AutoNoVsync => {}
+                mode => {
+                    if !capabilities.present_modes.contains(&mode) {
+                        tracing::warn!("present mode {mode:?} is not supported. using fifo");
+                        self.surface_config.present_mode = PresentMode::Fifo;
+                    }
+                }
+            }
             debug!("supported formats of the surface: {supported_formats:#?}");
+            // needed to copy the surface texture for screenshots
+            if capabilities.usages.contains(TextureUsages::COPY_SRC) {
+                self.surface_config.usage |= TextureUsages::COPY_SRC;
+            } else {
+                debug!("surface doesn't support COPY_SRC usage. screenshots won't work");
+            }
 
             let mut compatible_format_found = false;
             for sfmt in self.surface_formats_priority.iter() {
@@ -142,13 +257,13 @@ impl SurfaceManager {
                 self.surface_config.format = supported_formats
                     .iter()
                     .find(|f| f.is_srgb())
+                    .or_else(|| supported_formats.first())
                     .copied()
-                    .unwrap_or_else(|| {
-                        supported_formats
-                            .first()
-                            .copied()
-                            .expect("surface has zero supported texture formats")
-                    })
+                    .ok_or_else(|| {
+                        BackendError::SurfaceCreation(
+                            "surface has zero supported texture formats. adapter is incompatible with surface".to_string(),
+                        )
+                    })?;
             }
             let view_format = if self.surface_config.format.is_srgb() {
                 self.surface_config.format
@@ -176,12 +291,54 @@ impl SurfaceManager {
             );
             self.resize_framebuffer(device, window_backend);
         }
+        Ok(())
+    }
+
+    /// chooses the format and usage of the offscreen texture. first format of `surface_formats_priority` which can be rendered to
+    /// and copied from is used. otherwise, [`TextureFormat::Rgba8UnormSrgb`].
+    fn configure_offscreen(&mut self, adapter: &Adapter) {
+        let usage = TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC;
+        self.surface_config.format = self
+            .surface_formats_priority
+            .iter()
+            .copied()
+            .find(|format| {
+                adapter
+                    .get_texture_format_features(*format)
+                    .allowed_usages
+                    .contains(usage)
+            })
+            .unwrap_or(TextureFormat::Rgba8UnormSrgb);
+        self.surface_config.usage = usage;
+        self.surface_config.view_formats = vec![self.surface_config.format];
+        debug!(
+            "no window surface. rendering offscreen with format {:?}",
+            self.surface_config.format
+        );
     }
 
     pub fn resize_framebuffer(&mut self, device: &Device, window_backend: &mut impl WindowBackend) {
-        if let Some(size) = window_backend.get_live_physical_size_framebuffer() {
+        if let Some(size) = window_backend.get_live_physical_size_framebuffer_by_id(self.window_id)
+        {
             self.surface_config.width = size[0];
             self.surface_config.height = size[1];
+            if !window_backend.capabilities().window_surface {
+                self.offscreen_texture = Some(device.create_texture(&TextureDescriptor {
+                    label: Some("offscreen texture"),
+                    size: Extent3d {
+                        width: size[0].max(1),
+                        height: size[1].max(1),
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: TextureDimension::D2,
+                    format: self.surface_config.format,
+                    usage: self.surface_config.usage,
+                    view_formats: &[],
+                }));
+                return;
+            }
             info!(
                 "reconfiguring surface with config: {:#?}",
                 &self.surface_config
@@ -192,8 +349,16 @@ impl SurfaceManager {
                 .configure(device, &self.surface_config);
         }
     }
+    /// the texture which is rendered to in this frame. either the offscreen texture or the acquired surface texture.
+    pub fn current_texture(&self) -> Option<&Texture> {
+        self.offscreen_texture.as_ref().or(self
+            .surface_current_image
+            .as_ref()
+            .map(|image| &image.texture))
+    }
     pub fn suspend(&mut self) {
         self.surface = None;
+        self.offscreen_texture = None;
         self.surface_current_image = None;
         self.surface_view = None;
     }
@@ -0,0 +1,388 @@
+//! Golden image snapshot testing for egui.
+//!
+//! [`check_snapshot`] runs your gui closure for a few frames using [`HeadlessBackend`] and [`SoftBackend`],
+//! and compares the last frame with a png stored in [`SnapshotConfig::snapshot_dir`].
+//! [`check_snapshot_with`] does the same with any [`GfxBackend`] which can render without a real window and
+//! supports [`GfxBackend::take_screenshot`] (eg: wgpu).
+//! If they differ by more than the tolerance, `<name>.new.png` and `<name>.diff.png` are written next to the reference
+//! so that you can inspect what changed.
+//!
+//! Set the env variable [`UPDATE_SNAPSHOTS_ENV`] to `1` to (re)write the reference images instead of comparing.
+//!
+//! ```rust,no_run
+//! #[test]
+//! fn hello_window() {
+//!     egui_snapshot::assert_snapshot("hello_window", Default::default(), |ctx| {
+//!         egui_backend::egui::Window::new("hello").show(ctx, |ui| {
+//!             ui.label("world");
+//!         });
+//!     });
+//! }
+//! ```
+//!
+//! NOTE: by default, the frames are rendered by the cpu rasterizer, which emulates the srgb output shaders of wgpu/glow renderers.
+//! So, snapshot tests work without a gpu. Other renderers rasterize slightly differently, so give them their own reference images.
+
+use egui_backend::egui::{self, Color32, ColorImage, LayerId, RawInput};
+use egui_backend::*;
+use egui_render_soft::{SoftBackend, SoftConfig};
+use egui_window_headless::{HeadlessBackend, HeadlessConfig};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// if this env variable is set to `1` or `true`, snapshots are written to disk instead of being compared.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_SNAPSHOTS";
+
+pub struct SnapshotConfig {
+    /// size of the window in logical points
+    pub logical_size: [f32; 2],
+    pub pixels_per_point: f32,
+    /// number of frames to run before taking the snapshot. egui often needs a couple of frames to settle
+    /// layout (eg: sizing windows). defaults to 3.
+    pub frames: u64,
+    /// input for each frame. first element is used for the first frame and so on.
+    pub scripted_input: VecDeque<RawInput>,
+    /// painted behind the gui, so that the background is the same with every renderer.
+    pub clear_color: Color32,
+    /// max allowed difference between a channel of the reference and the new image.
+    pub tolerance: u8,
+    /// number of pixels which may exceed the `tolerance` before the comparison fails.
+    pub max_failed_pixels: usize,
+    /// directory where the reference pngs live. relative to the current dir (which is crate root for `cargo test`).
+    pub snapshot_dir: PathBuf,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            logical_size: [400.0, 300.0],
+            pixels_per_point: 1.0,
+            frames: 3,
+            scripted_input: VecDeque::new(),
+            clear_color: Color32::BLACK,
+            tolerance: 2,
+            max_failed_pixels: 0,
+            snapshot_dir: PathBuf::from("tests/snapshots"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// failed to create the headless window or renderer. or the renderer doesn't support screenshots.
+    Backend(BackendError),
+    Io(std::io::Error),
+    PngEncoding(png::EncodingError),
+    PngDecoding(png::DecodingError),
+    /// only 8 bit rgb/rgba pngs are supported
+    UnsupportedPngFormat(png::ColorType, png::BitDepth),
+    /// reference image doesn't exist. the rendered image is written to `new_path`.
+    MissingSnapshot {
+        new_path: PathBuf,
+    },
+    /// the sizes of the reference image and rendered image are different. the rendered image is written to `new_path`.
+    SizeMismatch {
+        expected: [usize; 2],
+        actual: [usize; 2],
+        new_path: PathBuf,
+    },
+    /// too many pixels are different.
+    Mismatch {
+        failed_pixels: usize,
+        max_difference: u8,
+        new_path: PathBuf,
+        diff_path: PathBuf,
+    },
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Backend(e) => write!(f, "backend error: {e}"),
+            SnapshotError::Io(e) => write!(f, "io error: {e}"),
+            SnapshotError::PngEncoding(e) => write!(f, "failed to encode png: {e}"),
+            SnapshotError::PngDecoding(e) => write!(f, "failed to decode png: {e}"),
+            SnapshotError::UnsupportedPngFormat(color, depth) => {
+                write!(f, "unsupported png format: {color:?} {depth:?}")
+            }
+            SnapshotError::MissingSnapshot { new_path } => write!(
+                f,
+                "snapshot doesn't exist. new image written to {}. run with {UPDATE_SNAPSHOTS_ENV}=1 to accept it",
+                new_path.display()
+            ),
+            SnapshotError::SizeMismatch {
+                expected,
+                actual,
+                new_path,
+            } => write!(
+                f,
+                "snapshot size mismatch. expected {expected:?}, got {actual:?}. new image written to {}",
+                new_path.display()
+            ),
+            SnapshotError::Mismatch {
+                failed_pixels,
+                max_difference,
+                new_path,
+                diff_path,
+            } => write!(
+                f,
+                "{failed_pixels} pixels are different (max difference {max_difference}). new image: {}, diff image: {}",
+                new_path.display(),
+                diff_path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<BackendError> for SnapshotError {
+    fn from(value: BackendError) -> Self {
+        Self::Backend(value)
+    }
+}
+impl From<std::io::Error> for SnapshotError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+impl From<png::EncodingError> for SnapshotError {
+    fn from(value: png::EncodingError) -> Self {
+        Self::PngEncoding(value)
+    }
+}
+impl From<png::DecodingError> for SnapshotError {
+    fn from(value: png::DecodingError) -> Self {
+        Self::PngDecoding(value)
+    }
+}
+
+struct SnapshotApp<G: GfxBackend, F: FnMut(&egui::Context)> {
+    window_backend: HeadlessBackend,
+    gfx_backend: G,
+    egui_context: egui::Context,
+    clear_color: Color32,
+    gui: F,
+}
+
+impl<G: GfxBackend, F: FnMut(&egui::Context)> UserApp for SnapshotApp<G, F> {
+    type UserGfxBackend = G;
+    type UserWindowBackend = HeadlessBackend;
+
+    fn get_all(
+        &mut self,
+    ) -> (
+        &mut Self::UserWindowBackend,
+        &mut Self::UserGfxBackend,
+        &egui::Context,
+    ) {
+        (
+            &mut self.window_backend,
+            &mut self.gfx_backend,
+            &self.egui_context,
+        )
+    }
+
+    fn gui_run(&mut self) {
+        // expand to avoid the feathered edges of the rect
+        let screen_rect = self.egui_context.screen_rect().expand(1.0);
+        self.egui_context
+            .layer_painter(LayerId::background())
+            .rect_filled(screen_rect, 0.0, self.clear_color);
+        (self.gui)(&self.egui_context);
+        // we don't know which frame is the last one. so, capture all of them and keep the last one.
+        self.gfx_backend.request_screenshot();
+    }
+}
+
+/// the soft renderer config used by [`render_frames`] and [`check_snapshot`]
+fn soft_config() -> SoftConfig {
+    SoftConfig {
+        clear_color: Color32::TRANSPARENT,
+        present: false,
+    }
+}
+
+/// runs `gui` for `config.frames` frames with [`SoftBackend`] and returns the last rendered frame (premultiplied alpha).
+pub fn render_frames(
+    config: &mut SnapshotConfig,
+    gui: impl FnMut(&egui::Context),
+) -> Result<ColorImage, BackendError> {
+    render_frames_with::<SoftBackend>(config, soft_config(), gui)
+}
+
+/// same as [`render_frames`], but renders with `G`. The frame is read back with [`GfxBackend::take_screenshot`].
+/// returns [`BackendError::Unsupported`] if `G` doesn't support screenshots.
+pub fn render_frames_with<G: GfxBackend>(
+    config: &mut SnapshotConfig,
+    gfx_config: G::Configuration,
+    gui: impl FnMut(&egui::Context),
+) -> Result<ColorImage, BackendError> {
+    let mut window_backend = HeadlessBackend::try_new(
+        HeadlessConfig {
+            logical_size: config.logical_size,
+            scale: config.pixels_per_point,
+            scripted_input: std::mem::take(&mut config.scripted_input),
+            max_frames: Some(config.frames),
+            ..Default::default()
+        },
+        BackendConfig::default(),
+    )?;
+    let gfx_backend = G::try_new(&mut window_backend, gfx_config)?;
+    let mut app = SnapshotApp {
+        window_backend,
+        gfx_backend,
+        egui_context: Default::default(),
+        clear_color: config.clear_color,
+        gui,
+    };
+    HeadlessBackend::run_frames(&mut app);
+    app.gfx_backend
+        .take_screenshot()
+        .ok_or(BackendError::Unsupported("take_screenshot"))
+}
+
+/// renders the gui with [`SoftBackend`] and compares the result with `<snapshot_dir>/<name>.png`
+pub fn check_snapshot(
+    name: &str,
+    config: SnapshotConfig,
+    gui: impl FnMut(&egui::Context),
+) -> Result<(), SnapshotError> {
+    check_snapshot_with::<SoftBackend>(name, config, soft_config(), gui)
+}
+
+/// same as [`check_snapshot`], but renders with `G`. see [`render_frames_with`]
+pub fn check_snapshot_with<G: GfxBackend>(
+    name: &str,
+    mut config: SnapshotConfig,
+    gfx_config: G::Configuration,
+    gui: impl FnMut(&egui::Context),
+) -> Result<(), SnapshotError> {
+    let image = render_frames_with::<G>(&mut config, gfx_config, gui)?;
+    let size = image.size;
+    let rendered: Vec<u8> = image
+        .pixels
+        .iter()
+        .flat_map(|c| c.to_srgba_unmultiplied())
+        .collect();
+
+    std::fs::create_dir_all(&config.snapshot_dir)?;
+    let reference_path = config.snapshot_dir.join(format!("{name}.png"));
+    let new_path = config.snapshot_dir.join(format!("{name}.new.png"));
+    let diff_path = config.snapshot_dir.join(format!("{name}.diff.png"));
+
+    let update = std::env::var(UPDATE_SNAPSHOTS_ENV)
+        .map(|value| value == "1" || value == "true")
+        .unwrap_or_default();
+    if update {
+        tracing::info!("updating snapshot {}", reference_path.display());
+        write_png(&reference_path, size, &rendered)?;
+        // remove leftovers from previous failures
+        let _ = std::fs::remove_file(&new_path);
+        let _ = std::fs::remove_file(&diff_path);
+        return Ok(());
+    }
+    if !reference_path.exists() {
+        write_png(&new_path, size, &rendered)?;
+        return Err(SnapshotError::MissingSnapshot { new_path });
+    }
+    let (expected_size, expected) = read_png(&reference_path)?;
+    if expected_size != size {
+        write_png(&new_path, size, &rendered)?;
+        return Err(SnapshotError::SizeMismatch {
+            expected: expected_size,
+            actual: size,
+            new_path,
+        });
+    }
+
+    let mut failed_pixels = 0;
+    let mut max_difference = 0;
+    let mut diff = Vec::with_capacity(rendered.len());
+    for (expected, actual) in expected.chunks_exact(4).zip(rendered.chunks_exact(4)) {
+        let difference = expected
+            .iter()
+            .zip(actual)
+            .map(|(e, a)| e.abs_diff(*a))
+            .max()
+            .unwrap_or_default();
+        max_difference = max_difference.max(difference);
+        if difference > config.tolerance {
+            failed_pixels += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            // faded grayscale version of the reference, so that the red pixels stand out
+            let gray = ((expected[0] as u32 + expected[1] as u32 + expected[2] as u32) / 12) as u8;
+            diff.extend_from_slice(&[gray, gray, gray, 255]);
+        }
+    }
+    if failed_pixels > config.max_failed_pixels {
+        write_png(&new_path, size, &rendered)?;
+        write_png(&diff_path, size, &diff)?;
+        return Err(SnapshotError::Mismatch {
+            failed_pixels,
+            max_difference,
+            new_path,
+            diff_path,
+        });
+    }
+    Ok(())
+}
+
+/// same as [`check_snapshot`], but panics if the snapshot doesn't match. Use this inside tests.
+#[track_caller]
+pub fn assert_snapshot(name: &str, config: SnapshotConfig, gui: impl FnMut(&egui::Context)) {
+    if let Err(e) = check_snapshot(name, config, gui) {
+        panic!("snapshot {name} failed: {e}");
+    }
+}
+
+/// same as [`check_snapshot_with`], but panics if the snapshot doesn't match.
+#[track_caller]
+pub fn assert_snapshot_with<G: GfxBackend>(
+    name: &str,
+    config: SnapshotConfig,
+    gfx_config: G::Configuration,
+    gui: impl FnMut(&egui::Context),
+) {
+    if let Err(e) = check_snapshot_with::<G>(name, config, gfx_config, gui) {
+        panic!("snapshot {name} failed: {e}");
+    }
+}
+
+/// writes 8 bit rgba pixels to a png file
+pub fn write_png(path: &Path, size: [usize; 2], rgba: &[u8]) -> Result<(), SnapshotError> {
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(File::create(path)?),
+        size[0] as u32,
+        size[1] as u32,
+    );
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// reads a png file as 8 bit rgba pixels. rgb pngs are expanded with opaque alpha.
+pub fn read_png(path: &Path) -> Result<([usize; 2], Vec<u8>), SnapshotError> {
+    let mut decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+    let size = [info.width as usize, info.height as usize];
+    let rgba = match (info.color_type, info.bit_depth) {
+        (png::ColorType::Rgba, png::BitDepth::Eight) => buffer,
+        (png::ColorType::Rgb, png::BitDepth::Eight) => buffer
+            .chunks_exact(3)
+            .flat_map(|c| [c[0], c[1], c[2], 255])
+            .collect(),
+        (color, depth) => return Err(SnapshotError::UnsupportedPngFormat(color, depth)),
+    };
+    Ok((size, rgba))
+}
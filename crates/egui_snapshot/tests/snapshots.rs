@@ -0,0 +1,88 @@
+use egui_backend::egui::{self, Color32};
+use egui_backend::BackendError;
+use egui_render_wgpu::{WgpuBackend, WgpuConfig};
+use egui_snapshot::*;
+
+fn small_config() -> SnapshotConfig {
+    SnapshotConfig {
+        logical_size: [200.0, 120.0],
+        ..Default::default()
+    }
+}
+
+fn hello_window(ctx: &egui::Context) {
+    egui::Window::new("hello")
+        .default_pos([10.0, 10.0])
+        .show(ctx, |ui| {
+            ui.label("world");
+        });
+}
+
+#[test]
+fn hello_window_snapshot() {
+    assert_snapshot("hello_window", small_config(), hello_window);
+}
+
+#[test]
+fn hello_window_wgpu_snapshot() {
+    let config = SnapshotConfig {
+        // gpus (and drivers) rasterize and filter slightly differently
+        tolerance: 8,
+        max_failed_pixels: 100,
+        ..small_config()
+    };
+    match check_snapshot_with::<WgpuBackend>(
+        "hello_window_wgpu",
+        config,
+        WgpuConfig::default(),
+        hello_window,
+    ) {
+        Ok(()) => {}
+        Err(SnapshotError::Backend(BackendError::AdapterNotFound)) => {
+            eprintln!("skipping wgpu snapshot test. no adapter found");
+        }
+        Err(e) => panic!("snapshot hello_window_wgpu failed: {e}"),
+    }
+}
+
+#[test]
+fn mismatch_writes_new_and_diff_images() {
+    if std::env::var(UPDATE_SNAPSHOTS_ENV).is_ok() {
+        // nothing is compared while updating snapshots
+        return;
+    }
+    let snapshot_dir =
+        std::env::temp_dir().join(format!("egui_snapshot_test_{}", std::process::id()));
+    std::fs::create_dir_all(&snapshot_dir).unwrap();
+    // a plain white reference, while the render is the window on a black background
+    let [width, height] = [200, 120];
+    write_png(
+        &snapshot_dir.join("mismatch.png"),
+        [width, height],
+        &vec![255; width * height * 4],
+    )
+    .unwrap();
+    let config = SnapshotConfig {
+        snapshot_dir: snapshot_dir.clone(),
+        clear_color: Color32::BLACK,
+        ..small_config()
+    };
+    let result = check_snapshot("mismatch", config, hello_window);
+    let new_exists = snapshot_dir.join("mismatch.new.png").exists();
+    let diff_exists = snapshot_dir.join("mismatch.diff.png").exists();
+    std::fs::remove_dir_all(&snapshot_dir).unwrap();
+
+    match result {
+        Err(SnapshotError::Mismatch {
+            failed_pixels,
+            max_difference,
+            ..
+        }) => {
+            assert!(failed_pixels > 0);
+            assert_eq!(max_difference, 255);
+        }
+        other => panic!("expected a mismatch, got {other:?}"),
+    }
+    assert!(new_exists);
+    assert!(diff_exists);
+}
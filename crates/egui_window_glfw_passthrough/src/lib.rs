@@ -1,6 +1,7 @@
 //! This crate uses `glfw-passthrough` crate as a window backend for egui.
 
 use egui::{Event, Key, PointerButton, Pos2, RawInput};
+use egui_backend::util::FrameScheduler;
 use egui_backend::*;
 pub use glfw;
 use glfw::Action;
@@ -10,7 +11,15 @@ use glfw::Glfw;
 use glfw::StandardCursor;
 use glfw::WindowEvent;
 use glfw::WindowHint;
+use std::collections::BTreeMap;
 use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+/// glfw has no gamepad events, so [`GlfwBackend::run_event_loop`] never waits longer than this for events
+/// while a gamepad is connected. Otherwise, button presses would only be seen after some other event (or the
+/// [`RepaintPolicy`]) wakes up the event loop. This also means that we keep running frames at about 60 fps while
+/// a gamepad is connected.
+pub const GAMEPAD_POLL_INTERVAL: Duration = Duration::from_millis(16);
 /// This is the window backend for egui using [`glfw`]
 /// Most of the startup configuration is done inside [`default_glfw_callback()`] and [`default_window_callback()`]
 /// These are passed to the `new` function using [`GlfwConfig`].
@@ -57,6 +66,39 @@ pub struct GlfwBackend {
     /// in logical points
     pub cursor_pos: [f32; 2],
     pub cursor_inside_bounds: bool,
+    /// collected in [`Self::tick`] and dispatched to the hooks of [`UserApp`] at the start of [`WindowBackend::run_frame`].
+    pub lifecycle_events: Vec<LifecycleEvent>,
+    /// created from [`BackendConfig::persistence_id`]. always `None` for secondary windows. see [`storage::load_state`]
+    pub storage: Option<Box<dyn storage::Storage>>,
+    /// [`GlfwConfig::clipboard`]. `None` uses the system clipboard through the glfw window.
+    /// The backend itself implements [`clipboard::ClipboardProvider`] to pick one of them. see [`clipboard`]
+    pub clipboard: Option<Box<dyn clipboard::ClipboardProvider>>,
+    /// windows created with [`WindowBackend::create_window`]. Each one is a glfw backend of its own,
+    /// so that all the event handling is reused. their own `secondary_windows` is always empty.
+    /// opengl windows share their gl context with the main window's context.
+    pub secondary_windows: BTreeMap<WindowId, GlfwBackend>,
+    /// id of the next window created with [`WindowBackend::create_window`]
+    pub next_window_id: u64,
+    /// glfw can't move/resize windows with the mouse by itself. So, we do it in [`Self::tick`] while this is `Some`.
+    /// see [`WindowBackend::begin_drag_move`]
+    pub window_drag: Option<util::WindowDrag>,
+    /// the mode set by [`WindowBackend::set_fullscreen`]. monitor is always `Some`.
+    pub fullscreen: FullscreenMode,
+    /// `[x, y, width, height]` in virtual units before going fullscreen. restored when we go back to windowed mode.
+    pub windowed_geometry: Option<[i32; 4]>,
+    /// set by [`WindowBackend::set_cursor_grab`]
+    pub cursor_grab: CursorGrab,
+    /// set by [`WindowBackend::set_cursor_visible`]
+    pub cursor_visible: bool,
+    /// set by [`WindowBackend::set_relative_mouse_mode`]
+    pub relative_mouse_mode: bool,
+    /// cursor position in virtual units from the last cursor event. used to calculate the mouse movement in relative mouse mode.
+    pub last_virtual_cursor_pos: [f64; 2],
+    /// mouse movement of this frame in relative mouse mode. reset after every frame. see [`WindowBackend::mouse_motion`]
+    pub mouse_motion: [f32; 2],
+    /// state of the connected gamepads by joystick id. glfw has no gamepad events, so we poll them in [`Self::tick_all_windows`].
+    /// always empty for secondary windows. see [`GAMEPAD_POLL_INTERVAL`]
+    pub gamepads: BTreeMap<u32, gamepad::GamepadState>,
 }
 impl Drop for GlfwBackend {
     fn drop(&mut self) {
@@ -78,12 +120,15 @@ pub struct GlfwConfig {
     /// This will be called right after window creation and setting event polling.
     /// you can use this to do things at startup like resizing, changing title, changing to fullscreen etc..
     pub window_callback: WindowCallback,
+    /// `None` (default) uses the system clipboard. see [`GlfwBackend::clipboard`]
+    pub clipboard: Option<Box<dyn clipboard::ClipboardProvider>>,
 }
 impl Default for GlfwConfig {
     fn default() -> Self {
         Self {
             glfw_callback: Box::new(|_| {}),
             window_callback: Box::new(|_| {}),
+            clipboard: None,
         }
     }
 }
@@ -91,15 +136,20 @@ impl WindowBackend for GlfwBackend {
     type Configuration = GlfwConfig;
     type WindowType = glfw::Window;
 
-    fn new(config: Self::Configuration, backend_config: BackendConfig) -> Self {
-        let mut glfw_context =
-            glfw::init(glfw::FAIL_ON_ERRORS).expect("failed to create glfw context");
+    fn try_new(
+        config: Self::Configuration,
+        backend_config: BackendConfig,
+    ) -> Result<Self, BackendError> {
+        // we log errors instead of panicking, so that failures can be returned as `BackendError`
+        let mut glfw_context = glfw::init(glfw::LOG_ERRORS)
+            .map_err(|e| BackendError::WindowBackendInit(format!("{e:?}")))?;
         glfw_context.window_hint(WindowHint::ScaleToMonitor(true));
 
         let BackendConfig {
             is_opengl,
             opengl_config,
             transparent,
+            ..
         } = &backend_config;
 
         if let Some(transparent) = *transparent {
@@ -155,115 +205,37 @@ impl WindowBackend for GlfwBackend {
         } else {
             glfw_context.window_hint(WindowHint::ClientApi(ClientApiHint::NoApi));
         }
-        (config.glfw_callback)(&mut glfw_context);
-
-        // create a window
-        let (mut window, events_receiver) = glfw_context
-            .create_window(800, 600, "Overlay Window", glfw::WindowMode::Windowed)
-            .expect("failed to create glfw window");
-        let api = window.get_client_api();
-        if api == glfw::ffi::OPENGL_API || api == glfw::ffi::OPENGL_ES_API {
-            window.make_current();
-        }
-        let should_poll = true;
-        // set which events you care about
-        window.set_pos_polling(should_poll);
-        window.set_size_polling(should_poll);
-        window.set_close_polling(should_poll);
-        window.set_refresh_polling(should_poll);
-        window.set_focus_polling(should_poll);
-        window.set_iconify_polling(should_poll);
-        window.set_framebuffer_size_polling(should_poll);
-        window.set_key_polling(should_poll);
-        window.set_char_polling(should_poll);
-        window.set_mouse_button_polling(should_poll);
-        window.set_cursor_pos_polling(should_poll);
-        window.set_cursor_enter_polling(should_poll);
-        window.set_scroll_polling(should_poll);
-        window.set_drag_and_drop_polling(should_poll);
-        #[cfg(not(target_os = "emscripten"))]
-        {
-            // emscripten doesn't have support for these yet. will get support for content scaling in 3.1.33
-            window.set_char_mods_polling(should_poll);
-            window.set_maximize_polling(should_poll);
-            window.set_content_scale_polling(should_poll);
-            window.set_store_lock_key_mods(should_poll);
+        glfw_context.window_hint(WindowHint::Decorated(backend_config.decorations));
+        glfw_context.window_hint(WindowHint::Resizable(backend_config.resizable));
+        if let Some(app_id) = backend_config.app_id.as_ref() {
+            glfw_context.window_hint(WindowHint::X11ClassName(Some(app_id.clone())));
+            glfw_context.window_hint(WindowHint::X11InstanceName(Some(app_id.clone())));
         }
-        #[cfg(not(target_os = "emscripten"))]
-        let scale = window.get_content_scale().0;
-        #[cfg(target_os = "emscripten")]
-        let scale = {
-            let scale = unsafe { emscripten_get_device_pixel_ratio() } as f32;
-            if scale != 1.0 {
-                let width = (800.0 * scale) as i32;
-                let height = (600.0 * scale) as i32;
-                window.set_size(width, height);
-            }
-            unsafe { emscripten_set_element_css_size(CANVAS_ELEMENT_NAME, 800.0, 600.0) };
-            scale
-        };
-
-        (config.window_callback)(&mut window);
-
-        // collect details and keep them updated
-        let (physical_width, physical_height) = window.get_framebuffer_size();
-        let (logical_width, logical_height) = (
-            physical_width as f32 / scale,
-            physical_height as f32 / scale,
-        );
-        let (virtual_width, virtual_height) = window.get_size();
-        let pixels_per_virtual_unit = physical_width as f32 / virtual_width as f32;
-        let cursor_pos_virtual_units = window.get_cursor_pos();
-        // #[cfg(not(target_os = "emscripten"))]
-        let logical_cursor_position = (
-            cursor_pos_virtual_units.0 as f32 * pixels_per_virtual_unit / scale,
-            cursor_pos_virtual_units.1 as f32 * pixels_per_virtual_unit / scale,
-        );
+        (config.glfw_callback)(&mut glfw_context);
 
-        let size_physical_pixels = [physical_width as u32, physical_height as u32];
-        // set raw input screen rect details so that first frame
-        // will have correct size even without any resize event
-        let raw_input = RawInput {
-            screen_rect: Some(egui::Rect::from_points(&[
-                Default::default(),
-                [
-                    physical_width as f32 / scale,
-                    physical_height as f32 / scale,
-                ]
-                .into(),
-            ])),
-            pixels_per_point: Some(scale),
-            ..Default::default()
-        };
-        tracing::info!(
-            "GlfwBackend created. 
-        physical_size: {physical_width}, {physical_height};
-        logical_size: {logical_width}, {logical_height};
-        virtual_size: {virtual_width}, {virtual_height};
-        content_scale: {scale};
-        pixels_per_virtual_unit: {pixels_per_virtual_unit};
-        "
-        );
-        Self {
-            glfw: glfw_context,
-            events_receiver,
+        // create a window. with `ScaleToMonitor` hint, glfw scales the size by the content scale. so, we can just use logical size.
+        let (window, events_receiver) = glfw_context
+            .create_window(
+                backend_config.size[0] as u32,
+                backend_config.size[1] as u32,
+                &backend_config.title,
+                glfw::WindowMode::Windowed,
+            )
+            .ok_or_else(|| {
+                BackendError::WindowCreation("glfw failed to create window".to_string())
+            })?;
+        let storage = storage::storage_from_config(&backend_config);
+        let mut backend = Self::from_window(
+            glfw_context,
             window,
-            framebuffer_size_physical: size_physical_pixels,
-            scale,
-            cursor_pos: [logical_cursor_position.0, logical_cursor_position.1],
-            raw_input,
-            frame_events: vec![],
-            resized_event_pending: true, // provide so that on first prepare frame, renderers can set their viewport sizes
+            events_receiver,
             backend_config,
-            cursor_icon: StandardCursor::Arrow,
-            cursor_inside_bounds: false,
-            window_size_logical: [logical_width, logical_height],
-            window_size_virtual: [
-                virtual_width.try_into().unwrap(),
-                virtual_height.try_into().unwrap(),
-            ],
-            physical_pixels_per_virtual_unit: pixels_per_virtual_unit,
-        }
+            config.window_callback,
+        )?;
+        backend.storage = storage;
+        backend.clipboard = config.clipboard;
+        backend.apply_backend_config();
+        Ok(backend)
     }
 
     fn take_raw_input(&mut self) -> RawInput {
@@ -281,38 +253,37 @@ impl WindowBackend for GlfwBackend {
 
     fn run_event_loop<U: UserApp<UserWindowBackend = Self> + 'static>(mut user_app: U) {
         tracing::info!("entering glfw event loop");
-        let mut wait_events_duration = std::time::Duration::ZERO;
+        let mut frame_scheduler = FrameScheduler::new(user_app.get_all().0.get_config());
+        storage::load_state(&mut user_app);
         let callback = move || {
             let window_backend = user_app.get_all().0;
-            window_backend
-                .glfw
-                .wait_events_timeout(wait_events_duration.as_secs_f64());
+            let wait = if window_backend.gamepads.is_empty() {
+                frame_scheduler.wait
+            } else {
+                Some(frame_scheduler.wait.map_or(GAMEPAD_POLL_INTERVAL, |wait| {
+                    wait.min(GAMEPAD_POLL_INTERVAL)
+                }))
+            };
+            match wait {
+                Some(wait) if wait.is_zero() => window_backend.glfw.poll_events(),
+                Some(wait) => window_backend.glfw.wait_events_timeout(wait.as_secs_f64()),
+                None => window_backend.glfw.wait_events(),
+            }
+            frame_scheduler.start_frame();
 
             // gather events
-            window_backend.tick();
-
-            if window_backend.resized_event_pending {
-                user_app.resize_framebuffer();
-                user_app.get_all().0.resized_event_pending = false;
-            }
-            let window_backend = user_app.get_all().0;
-            let logical_size = window_backend.window_size_logical;
-            // run userapp gui function. let user do anything he wants with window or gfx backends
-            if let Some((platform_output, timeout)) = user_app.run(logical_size) {
-                wait_events_duration = timeout.min(std::time::Duration::from_secs(1));
-                if !platform_output.copied_text.is_empty() {
-                    user_app
-                        .get_all()
-                        .0
-                        .window
-                        .set_clipboard_string(&platform_output.copied_text);
+            window_backend.tick_all_windows();
+            let output = Self::run_frame(&mut user_app);
+            frame_scheduler.end_frame(output.map(|(_, repaint_after)| repaint_after));
+            #[cfg(not(target_os = "emscripten"))]
+            {
+                let should_close = user_app.get_all().0.should_close();
+                if should_close {
+                    storage::save_state(&mut user_app);
+                    user_app.on_exit();
                 }
-                user_app.get_all().0.set_cursor(platform_output.cursor_icon);
-            } else {
-                wait_events_duration = std::time::Duration::ZERO;
+                should_close
             }
-            #[cfg(not(target_os = "emscripten"))]
-            user_app.get_all().0.window.should_close()
         };
         // on emscripten, just keep calling forever i guess.
         #[cfg(target_os = "emscripten")]
@@ -331,12 +302,138 @@ impl WindowBackend for GlfwBackend {
         }
     }
 
+    fn poll_events(&mut self) -> Result<(), BackendError> {
+        self.glfw.poll_events();
+        self.tick_all_windows();
+        Ok(())
+    }
+
+    fn run_frame<U: UserApp<UserWindowBackend = Self>>(
+        user_app: &mut U,
+    ) -> Option<(egui::PlatformOutput, std::time::Duration)> {
+        let events = std::mem::take(&mut user_app.get_all().0.lifecycle_events);
+        if user_app.handle_lifecycle_events(events) {
+            user_app.get_all().0.window.set_should_close(true);
+        }
+        let window_backend = user_app.get_all().0;
+        if window_backend.resized_event_pending {
+            user_app.resize_framebuffer();
+            user_app.get_all().0.resized_event_pending = false;
+        }
+        let logical_size = user_app.get_all().0.window_size_logical;
+        // run userapp gui function. let user do anything he wants with window or gfx backends
+        let mut output = user_app.run(logical_size);
+        if let Some((platform_output, _)) = output.as_ref() {
+            user_app.get_all().0.handle_platform_output(platform_output);
+        }
+        user_app.get_all().0.mouse_motion = [0.0; 2];
+        // windows created with `create_window`
+        let ids: Vec<WindowId> = user_app
+            .get_all()
+            .0
+            .secondary_windows
+            .keys()
+            .copied()
+            .collect();
+        for id in ids {
+            let Some(window) = user_app.get_all().0.secondary_windows.get_mut(&id) else {
+                continue;
+            };
+            // hooks are only for the main window. secondary windows just close.
+            let close_requested = std::mem::take(&mut window.lifecycle_events)
+                .contains(&LifecycleEvent::CloseRequested);
+            if close_requested || window.window.should_close() {
+                // reset it, in case user app wants to keep the window open
+                window.window.set_should_close(false);
+                user_app.close_window(id);
+                continue;
+            }
+            let resized = std::mem::take(&mut window.resized_event_pending);
+            let logical_size = window.window_size_logical;
+            if resized {
+                user_app.resize_framebuffer_by_id(id);
+            }
+            if let Some((platform_output, timeout)) = user_app.run_window(id, logical_size) {
+                if let Some((_, repaint_after)) = output.as_mut() {
+                    *repaint_after = (*repaint_after).min(timeout);
+                }
+                if let Some(window) = user_app.get_all().0.secondary_windows.get_mut(&id) {
+                    window.handle_platform_output(&platform_output);
+                }
+            }
+        }
+        output
+    }
+
+    fn should_close(&self) -> bool {
+        self.window.should_close()
+    }
+
+    fn set_should_close(&mut self, should_close: bool) {
+        self.window.set_should_close(should_close);
+    }
+
+    fn get_storage(&mut self) -> Option<&mut dyn storage::Storage> {
+        // shorten the `'static` lifetime of the boxed trait object
+        self.storage
+            .as_deref_mut()
+            .map(|s| s as &mut dyn storage::Storage)
+    }
+
+    fn get_clipboard(&mut self) -> Option<&mut dyn clipboard::ClipboardProvider> {
+        Some(self)
+    }
+
     fn get_config(&self) -> &BackendConfig {
         &self.backend_config
     }
 
-    fn swap_buffers(&mut self) {
-        self.window.swap_buffers()
+    fn capabilities(&self) -> WindowCapabilities {
+        let is_opengl = self.is_opengl();
+        // emscripten's glfw implementation can't move the canvas or change its stacking order
+        let is_native = !cfg!(target_os = "emscripten");
+        WindowCapabilities {
+            swap_buffers: is_opengl,
+            get_proc_address: is_opengl,
+            get_window_position: true,
+            set_window_position: is_native,
+            get_window_size: true,
+            set_window_size: true,
+            get_window_minimized: true,
+            set_minimize_window: true,
+            get_window_maximized: true,
+            set_maximize_window: true,
+            get_window_visibility: true,
+            set_window_visibility: true,
+            get_always_on_top: true,
+            set_always_on_top: is_native,
+            get_passthrough: true,
+            set_passthrough: is_native,
+            get_decorations: is_native,
+            set_decorations: is_native,
+            get_resizable: is_native,
+            set_resizable: is_native,
+            begin_drag_move: is_native,
+            begin_drag_resize: is_native,
+            monitors: is_native,
+            get_fullscreen: is_native,
+            set_fullscreen: is_native,
+            set_cursor_grab: is_native,
+            set_cursor_visible: true,
+            relative_mouse_mode: is_native,
+            gamepads: true,
+            // emscripten only has a single canvas
+            multi_window: is_native,
+            window_surface: true,
+        }
+    }
+
+    fn swap_buffers(&mut self) -> Result<(), BackendError> {
+        if !self.is_opengl() {
+            return Err(BackendError::Unsupported("swap_buffers"));
+        }
+        self.window.swap_buffers();
+        Ok(())
     }
 
     fn is_opengl(&self) -> bool {
@@ -348,12 +445,9 @@ impl WindowBackend for GlfwBackend {
         }
     }
 
-    fn get_proc_address(&mut self, symbol: &str) -> *const core::ffi::c_void {
-        if self.is_opengl() {
-            self.window.get_proc_address(symbol)
-        } else {
-            unimplemented!("window is not opengl. cannot use get_proc_address.");
-        }
+    fn get_proc_address(&mut self, symbol: &str) -> Option<*const core::ffi::c_void> {
+        self.is_opengl()
+            .then(|| self.window.get_proc_address(symbol))
     }
 
     fn set_window_title(&mut self, title: &str) {
@@ -365,13 +459,15 @@ impl WindowBackend for GlfwBackend {
         [pos.0 as f32, pos.1 as f32].into()
     }
 
-    fn set_window_position(&mut self, pos: [f32; 2]) {
+    fn set_window_position(&mut self, pos: [f32; 2]) -> Result<(), BackendError> {
         self.window.set_pos(pos[0] as i32, pos[1] as i32);
+        Ok(())
     }
 
     fn get_window_size(&mut self) -> Option<[f32; 2]> {
+        // css size of the canvas is already in logical points (see `set_window_size`)
         #[cfg(target_os = "emscripten")]
-        let (width, height) = {
+        let logical_size = {
             let mut width = 0.0;
             let mut height = 0.0;
             unsafe {
@@ -384,15 +480,15 @@ impl WindowBackend for GlfwBackend {
                     0
                 );
             }
-            (width as f32, height as f32)
+            [width as f32, height as f32]
         };
         #[cfg(not(target_os = "emscripten"))]
-        let (width, height) = {
+        let logical_size = {
             let (width, height) = self.window.get_framebuffer_size();
-            (width as f32, height as f32)
+            [width as f32 / self.scale, height as f32 / self.scale]
         };
-        self.window_size_logical = [width / self.scale, height / self.scale];
-        [width, height].into()
+        self.window_size_logical = logical_size;
+        Some(logical_size)
     }
 
     fn set_window_size(&mut self, size: [f32; 2]) {
@@ -459,20 +555,623 @@ impl WindowBackend for GlfwBackend {
         self.window.is_floating().into()
     }
 
-    fn set_always_on_top(&mut self, always_on_top: bool) {
+    fn set_always_on_top(&mut self, always_on_top: bool) -> Result<(), BackendError> {
         self.window.set_floating(always_on_top);
+        Ok(())
     }
 
     fn get_passthrough(&mut self) -> Option<bool> {
         self.window.is_mouse_passthrough().into()
     }
 
-    fn set_passthrough(&mut self, passthrough: bool) {
+    fn set_passthrough(&mut self, passthrough: bool) -> Result<(), BackendError> {
         self.window.set_mouse_passthrough(passthrough);
+        Ok(())
+    }
+
+    fn get_decorations(&mut self) -> Option<bool> {
+        self.window.is_decorated().into()
+    }
+
+    fn set_decorations(&mut self, decorations: bool) -> Result<(), BackendError> {
+        self.window.set_decorated(decorations);
+        Ok(())
+    }
+
+    fn get_resizable(&mut self) -> Option<bool> {
+        self.window.is_resizable().into()
+    }
+
+    fn set_resizable(&mut self, resizable: bool) -> Result<(), BackendError> {
+        self.window.set_resizable(resizable);
+        Ok(())
+    }
+
+    fn begin_drag_move(&mut self) -> Result<(), BackendError> {
+        self.begin_drag(None, "begin_drag_move")
+    }
+
+    fn begin_drag_resize(&mut self, edge: ResizeEdge) -> Result<(), BackendError> {
+        self.begin_drag(Some(edge), "begin_drag_resize")
+    }
+
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        self.glfw
+            .with_connected_monitors(|_, monitors| monitors.iter().map(glfw_monitor_info).collect())
+    }
+
+    fn current_monitor(&mut self) -> Option<usize> {
+        // glfw only tells us the monitor of fullscreen windows. So, we use the monitor which contains the center of the window.
+        let (x, y) = self.window.get_pos();
+        let (width, height) = self.window.get_size();
+        let center = [x + width / 2, y + height / 2];
+        self.glfw.with_connected_monitors(|_, monitors| {
+            monitors.iter().position(|monitor| {
+                let (x, y, width, height) = monitor.get_workarea();
+                (x..x + width).contains(&center[0]) && (y..y + height).contains(&center[1])
+            })
+        })
+    }
+
+    fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<(), BackendError> {
+        // glfw can only lock the cursor (by disabling it)
+        if grab == CursorGrab::Confined || cfg!(target_os = "emscripten") {
+            return Err(BackendError::Unsupported("set_cursor_grab"));
+        }
+        self.cursor_grab = grab;
+        self.update_cursor_mode();
+        Ok(())
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<(), BackendError> {
+        self.cursor_visible = visible;
+        self.update_cursor_mode();
+        Ok(())
+    }
+
+    fn get_relative_mouse_mode(&mut self) -> Option<bool> {
+        Some(self.relative_mouse_mode)
+    }
+
+    fn mouse_motion(&mut self) -> Option<[f32; 2]> {
+        Some(self.mouse_motion)
+    }
+
+    fn set_relative_mouse_mode(&mut self, relative: bool) -> Result<(), BackendError> {
+        if cfg!(target_os = "emscripten") {
+            return Err(BackendError::Unsupported("set_relative_mouse_mode"));
+        }
+        self.relative_mouse_mode = relative;
+        self.update_cursor_mode();
+        // raw motion skips the os mouse acceleration. only works with a disabled cursor.
+        if self.glfw.supports_raw_motion() {
+            self.window.set_raw_mouse_motion(relative);
+        }
+        let (x, y) = self.window.get_cursor_pos();
+        self.last_virtual_cursor_pos = [x, y];
+        Ok(())
+    }
+
+    fn gamepads(&mut self) -> Vec<gamepad::GamepadState> {
+        self.gamepads.values().cloned().collect()
+    }
+
+    fn get_fullscreen(&mut self) -> Option<FullscreenMode> {
+        let is_fullscreen = self
+            .window
+            .with_window_mode(|mode| matches!(mode, glfw::WindowMode::FullScreen(_)));
+        Some(if is_fullscreen {
+            self.fullscreen
+        } else {
+            FullscreenMode::Windowed
+        })
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), BackendError> {
+        if cfg!(target_os = "emscripten") {
+            return Err(BackendError::Unsupported("set_fullscreen"));
+        }
+        let (monitor, video_mode) = match mode {
+            FullscreenMode::Windowed => {
+                let [x, y, width, height] = self.windowed_geometry.take().unwrap_or_else(|| {
+                    let (x, y) = self.window.get_pos();
+                    let (width, height) = self.window.get_size();
+                    [x, y, width, height]
+                });
+                self.window.set_monitor(
+                    glfw::WindowMode::Windowed,
+                    x,
+                    y,
+                    width as u32,
+                    height as u32,
+                    None,
+                );
+                self.fullscreen = mode;
+                return Ok(());
+            }
+            FullscreenMode::Borderless { monitor } => (monitor, None),
+            FullscreenMode::Exclusive {
+                monitor,
+                video_mode,
+            } => (monitor, Some(video_mode)),
+        };
+        let index = monitor.or_else(|| self.current_monitor()).unwrap_or(0);
+        // remember the windowed geometry, so that we can restore it later
+        if self.windowed_geometry.is_none() {
+            let (x, y) = self.window.get_pos();
+            let (width, height) = self.window.get_size();
+            self.windowed_geometry = Some([x, y, width, height]);
+        }
+        let window = &mut self.window;
+        self.glfw.with_connected_monitors(|_, monitors| {
+            let monitor = monitors
+                .get(index)
+                .ok_or_else(|| BackendError::Other(format!("monitor {index} doesn't exist")))?;
+            // glfw does "windowed fullscreen" if we use the current video mode of the monitor
+            let (width, height, refresh_rate) = match video_mode {
+                Some(video_mode) => (
+                    video_mode.size[0],
+                    video_mode.size[1],
+                    video_mode.refresh_rate.map(|r| r.round() as u32),
+                ),
+                None => {
+                    let current = monitor.get_video_mode().ok_or_else(|| {
+                        BackendError::Other(format!("monitor {index} has no video mode"))
+                    })?;
+                    (current.width, current.height, Some(current.refresh_rate))
+                }
+            };
+            window.set_monitor(
+                glfw::WindowMode::FullScreen(monitor),
+                0,
+                0,
+                width,
+                height,
+                refresh_rate,
+            );
+            Ok(())
+        })?;
+        self.fullscreen = match mode {
+            FullscreenMode::Borderless { .. } => FullscreenMode::Borderless {
+                monitor: Some(index),
+            },
+            FullscreenMode::Exclusive { video_mode, .. } => FullscreenMode::Exclusive {
+                monitor: Some(index),
+                video_mode,
+            },
+            FullscreenMode::Windowed => unreachable!(),
+        };
+        Ok(())
+    }
+
+    fn handle_platform_output(&mut self, platform_output: &egui::PlatformOutput) {
+        if !platform_output.copied_text.is_empty() {
+            clipboard::ClipboardProvider::set_text(self, &platform_output.copied_text);
+        }
+        self.set_cursor(platform_output.cursor_icon);
+        if let Some(open_url) = platform_output.open_url.as_ref() {
+            open_url_in_browser(&open_url.url, open_url.new_tab);
+        }
+        // glfw has no IME api. It always sends `Char` events whether a text field is focused or not,
+        // so there's nothing to do for `mutable_text_under_cursor` or `text_cursor_pos`.
+    }
+
+    fn create_window(&mut self, attributes: WindowAttributes) -> Result<WindowId, BackendError> {
+        if cfg!(target_os = "emscripten") {
+            return Err(BackendError::Unsupported("create_window"));
+        }
+        // glfw wants the size in virtual units. we assume that the new window will have the same scale as the main window.
+        let virtual_units_per_point = self.scale / self.physical_pixels_per_virtual_unit;
+        let width = (attributes.size[0] * virtual_units_per_point) as u32;
+        let height = (attributes.size[1] * virtual_units_per_point) as u32;
+        // window hints set during `try_new` are still active. so, the new window will have the same settings as main window.
+        let (window, events_receiver) = if self.is_opengl() {
+            // share the gl context, so that the gl objects like textures can be used with the new window too.
+            self.window
+                .create_shared(width, height, &attributes.title, glfw::WindowMode::Windowed)
+        } else {
+            self.glfw
+                .create_window(width, height, &attributes.title, glfw::WindowMode::Windowed)
+        }
+        .ok_or_else(|| BackendError::WindowCreation("glfw failed to create window".to_string()))?;
+        // Glfw is just a zero sized token
+        #[allow(clippy::clone_on_copy)]
+        let glfw_context = self.glfw.clone();
+        let mut backend = Self::from_window(
+            glfw_context,
+            window,
+            events_receiver,
+            self.backend_config.clone(),
+            Box::new(|_| {}),
+        )?;
+        if let Some(pos) = attributes.position {
+            let _ = backend.set_window_position(pos);
+        }
+        // `from_window` made the new window's context current.
+        if self.is_opengl() {
+            self.window.make_current();
+        }
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+        self.secondary_windows.insert(id, backend);
+        Ok(id)
+    }
+
+    fn destroy_window(&mut self, id: WindowId) -> Result<(), BackendError> {
+        // dropping the glfw window destroys it.
+        self.secondary_windows
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| BackendError::Other(format!("window {id:?} doesn't exist")))
+    }
+
+    fn window_ids(&self) -> Vec<WindowId> {
+        std::iter::once(WindowId::MAIN)
+            .chain(self.secondary_windows.keys().copied())
+            .collect()
+    }
+
+    fn get_window_by_id(&mut self, id: WindowId) -> Option<&mut Self::WindowType> {
+        self.window_by_id(id).map(|backend| &mut backend.window)
+    }
+
+    fn get_live_physical_size_framebuffer_by_id(&mut self, id: WindowId) -> Option<[u32; 2]> {
+        self.window_by_id(id)?.get_live_physical_size_framebuffer()
+    }
+
+    fn take_raw_input_by_id(&mut self, id: WindowId) -> RawInput {
+        self.window_by_id(id)
+            .map(|backend| backend.take_raw_input())
+            .unwrap_or_default()
+    }
+
+    fn swap_buffers_by_id(&mut self, id: WindowId) -> Result<(), BackendError> {
+        self.window_by_id(id)
+            .ok_or(BackendError::WindowNotAvailable)?
+            .swap_buffers()
+    }
+
+    fn make_gl_context_current(&mut self, id: WindowId) -> Result<(), BackendError> {
+        if !self.is_opengl() {
+            return Err(BackendError::Unsupported("make_gl_context_current"));
+        }
+        self.window_by_id(id)
+            .ok_or(BackendError::WindowNotAvailable)?
+            .window
+            .make_current();
+        Ok(())
+    }
+}
+
+/// uses [`GlfwBackend::clipboard`] if set, or the system clipboard through the glfw window.
+impl clipboard::ClipboardProvider for GlfwBackend {
+    fn get_text(&mut self) -> Option<String> {
+        match self.clipboard.as_mut() {
+            Some(clipboard) => clipboard.get_text(),
+            None => self.window.get_clipboard_string(),
+        }
+    }
+
+    fn set_text(&mut self, text: &str) {
+        match self.clipboard.as_mut() {
+            Some(clipboard) => clipboard.set_text(text),
+            None => self.window.set_clipboard_string(text),
+        }
+    }
+}
+
+/// opens the url using the default browser. on emscripten, we just use `window.open` from js.
+/// on native, `new_tab` is ignored as the browser decides that.
+pub fn open_url_in_browser(url: &str, new_tab: bool) {
+    #[cfg(not(target_os = "emscripten"))]
+    {
+        let _ = new_tab;
+        if let Err(e) = webbrowser::open(url) {
+            tracing::error!("failed to open url {url}: {e}");
+        }
+    }
+    #[cfg(target_os = "emscripten")]
+    {
+        // escape the url, so that it can't break out of the js string literal
+        let mut escaped_url = String::with_capacity(url.len());
+        for c in url.chars() {
+            match c {
+                '\\' | '"' => {
+                    escaped_url.push('\\');
+                    escaped_url.push(c);
+                }
+                '\n' | '\r' => {}
+                c => escaped_url.push(c),
+            }
+        }
+        let target = if new_tab { "_blank" } else { "_self" };
+        let script = match std::ffi::CString::new(format!(
+            "window.open(\"{escaped_url}\", \"{target}\"); 0"
+        )) {
+            Ok(script) => script,
+            Err(e) => {
+                tracing::error!("failed to open url {url}: {e}");
+                return;
+            }
+        };
+        unsafe {
+            emscripten_run_script_int(script.as_ptr() as _);
+        }
     }
 }
 
 impl GlfwBackend {
+    /// returns the backend of the window with this id. [`WindowId::MAIN`] returns self.
+    pub fn window_by_id(&mut self, id: WindowId) -> Option<&mut Self> {
+        if id == WindowId::MAIN {
+            Some(self)
+        } else {
+            self.secondary_windows.get_mut(&id)
+        }
+    }
+    /// sets up event polling, content scale etc.. for a newly created window and creates a backend for it.
+    /// For opengl windows, this makes the window's context current.
+    fn from_window(
+        glfw_context: Glfw,
+        mut window: glfw::Window,
+        events_receiver: Receiver<(f64, WindowEvent)>,
+        backend_config: BackendConfig,
+        window_callback: WindowCallback,
+    ) -> Result<Self, BackendError> {
+        let api = window.get_client_api();
+        if api == glfw::ffi::OPENGL_API || api == glfw::ffi::OPENGL_ES_API {
+            window.make_current();
+        }
+        let should_poll = true;
+        // set which events you care about
+        window.set_pos_polling(should_poll);
+        window.set_size_polling(should_poll);
+        window.set_close_polling(should_poll);
+        window.set_refresh_polling(should_poll);
+        window.set_focus_polling(should_poll);
+        window.set_iconify_polling(should_poll);
+        window.set_framebuffer_size_polling(should_poll);
+        window.set_key_polling(should_poll);
+        window.set_char_polling(should_poll);
+        window.set_mouse_button_polling(should_poll);
+        window.set_cursor_pos_polling(should_poll);
+        window.set_cursor_enter_polling(should_poll);
+        window.set_scroll_polling(should_poll);
+        window.set_drag_and_drop_polling(should_poll);
+        #[cfg(not(target_os = "emscripten"))]
+        {
+            // emscripten doesn't have support for these yet. will get support for content scaling in 3.1.33
+            window.set_char_mods_polling(should_poll);
+            window.set_maximize_polling(should_poll);
+            window.set_content_scale_polling(should_poll);
+            window.set_store_lock_key_mods(should_poll);
+        }
+        #[cfg(not(target_os = "emscripten"))]
+        let scale = window.get_content_scale().0;
+        #[cfg(target_os = "emscripten")]
+        let scale = {
+            let scale = unsafe { emscripten_get_device_pixel_ratio() } as f32;
+            if scale != 1.0 {
+                let width = (800.0 * scale) as i32;
+                let height = (600.0 * scale) as i32;
+                window.set_size(width, height);
+            }
+            unsafe { emscripten_set_element_css_size(CANVAS_ELEMENT_NAME, 800.0, 600.0) };
+            scale
+        };
+
+        (window_callback)(&mut window);
+
+        // collect details and keep them updated
+        let (physical_width, physical_height) = window.get_framebuffer_size();
+        let (logical_width, logical_height) = (
+            physical_width as f32 / scale,
+            physical_height as f32 / scale,
+        );
+        let (virtual_width, virtual_height) = window.get_size();
+        let pixels_per_virtual_unit = physical_width as f32 / virtual_width as f32;
+        let cursor_pos_virtual_units = window.get_cursor_pos();
+        // #[cfg(not(target_os = "emscripten"))]
+        let logical_cursor_position = (
+            cursor_pos_virtual_units.0 as f32 * pixels_per_virtual_unit / scale,
+            cursor_pos_virtual_units.1 as f32 * pixels_per_virtual_unit / scale,
+        );
+
+        let size_physical_pixels = [physical_width as u32, physical_height as u32];
+        // set raw input screen rect details so that first frame
+        // will have correct size even without any resize event
+        let raw_input = RawInput {
+            screen_rect: Some(egui::Rect::from_points(&[
+                Default::default(),
+                [
+                    physical_width as f32 / scale,
+                    physical_height as f32 / scale,
+                ]
+                .into(),
+            ])),
+            pixels_per_point: Some(scale),
+            ..Default::default()
+        };
+        tracing::info!(
+            "GlfwBackend created. 
+        physical_size: {physical_width}, {physical_height};
+        logical_size: {logical_width}, {logical_height};
+        virtual_size: {virtual_width}, {virtual_height};
+        content_scale: {scale};
+        pixels_per_virtual_unit: {pixels_per_virtual_unit};
+        "
+        );
+        Ok(Self {
+            glfw: glfw_context,
+            events_receiver,
+            window,
+            framebuffer_size_physical: size_physical_pixels,
+            scale,
+            cursor_pos: [logical_cursor_position.0, logical_cursor_position.1],
+            raw_input,
+            frame_events: vec![],
+            resized_event_pending: true, // provide so that on first prepare frame, renderers can set their viewport sizes
+            backend_config,
+            cursor_icon: StandardCursor::Arrow,
+            cursor_inside_bounds: false,
+            lifecycle_events: Vec::new(),
+            storage: None,
+            clipboard: None,
+            window_size_logical: [logical_width, logical_height],
+            window_size_virtual: [
+                virtual_width.try_into().unwrap(),
+                virtual_height.try_into().unwrap(),
+            ],
+            physical_pixels_per_virtual_unit: pixels_per_virtual_unit,
+            secondary_windows: BTreeMap::new(),
+            next_window_id: WindowId::MAIN.0 + 1,
+            window_drag: None,
+            fullscreen: FullscreenMode::Windowed,
+            windowed_geometry: None,
+            cursor_grab: CursorGrab::None,
+            cursor_visible: true,
+            relative_mouse_mode: false,
+            mouse_motion: [0.0; 2],
+            last_virtual_cursor_pos: [0.0, 0.0],
+            gamepads: BTreeMap::new(),
+        })
+    }
+
+    /// starts a [`util::WindowDrag`] from the current cursor position. `op` is the name of the trait fn for the error.
+    fn begin_drag(
+        &mut self,
+        edge: Option<ResizeEdge>,
+        op: &'static str,
+    ) -> Result<(), BackendError> {
+        if cfg!(target_os = "emscripten") {
+            return Err(BackendError::Unsupported(op));
+        }
+        // all in virtual units (screen coordinates)
+        let (x, y) = self.window.get_pos();
+        let (width, height) = self.window.get_size();
+        let (cursor_x, cursor_y) = self.window.get_cursor_pos();
+        self.window_drag = Some(util::WindowDrag {
+            edge,
+            start_cursor: [x as f32 + cursor_x as f32, y as f32 + cursor_y as f32],
+            start_position: [x as f32, y as f32],
+            start_size: [width as f32, height as f32],
+        });
+        Ok(())
+    }
+    /// moves/resizes the window if there's a [`util::WindowDrag`] in progress. ends it once the left mouse button is released.
+    fn update_window_drag(&mut self) {
+        let Some(drag) = self.window_drag else {
+            return;
+        };
+        if self.window.get_mouse_button(glfw::MouseButtonLeft) == glfw::Action::Release {
+            self.window_drag = None;
+            return;
+        }
+        let (x, y) = self.window.get_pos();
+        let (cursor_x, cursor_y) = self.window.get_cursor_pos();
+        let min_size = self
+            .backend_config
+            .min_size
+            .map(|[width, height]| {
+                let ratio = self.scale / self.physical_pixels_per_virtual_unit;
+                [width * ratio, height * ratio]
+            })
+            .unwrap_or([1.0, 1.0]);
+        let (position, size) = drag.update(
+            [x as f32 + cursor_x as f32, y as f32 + cursor_y as f32],
+            min_size,
+        );
+        if [position[0] as i32, position[1] as i32] != [x, y] {
+            self.window.set_pos(position[0] as i32, position[1] as i32);
+        }
+        if drag.edge.is_some() {
+            self.window.set_size(size[0] as i32, size[1] as i32);
+        }
+    }
+
+    /// applies the settings of [`BackendConfig`] which can only be set after the window is created.
+    fn apply_backend_config(&mut self) {
+        if let Some(position) = self.backend_config.position {
+            let _ = self.set_window_position(position);
+        }
+        if self.backend_config.min_size.is_some() || self.backend_config.max_size.is_some() {
+            // logical points to virtual units
+            let ratio = self.scale / self.physical_pixels_per_virtual_unit;
+            let to_virtual = |size: Option<[f32; 2]>| {
+                (
+                    size.map(|s| (s[0] * ratio) as u32),
+                    size.map(|s| (s[1] * ratio) as u32),
+                )
+            };
+            let (min_width, min_height) = to_virtual(self.backend_config.min_size);
+            let (max_width, max_height) = to_virtual(self.backend_config.max_size);
+            self.window
+                .set_size_limits(min_width, min_height, max_width, max_height);
+        }
+        // `from_window` made the context current
+        if self.is_opengl() {
+            self.glfw.set_swap_interval(if self.backend_config.vsync {
+                glfw::SwapInterval::Sync(1)
+            } else {
+                glfw::SwapInterval::None
+            });
+        }
+    }
+    /// [`Self::tick`] for the main window and all the secondary windows
+    fn tick_all_windows(&mut self) {
+        self.tick();
+        for window in self.secondary_windows.values_mut() {
+            window.tick();
+        }
+        self.poll_gamepads();
+    }
+    /// updates [`Self::gamepads`] and pushes the navigation events of buttons which changed since the last poll.
+    pub fn poll_gamepads(&mut self) {
+        // only the focused window should navigate
+        let navigation = self.backend_config.gamepad_navigation && self.window.is_focused();
+        for index in 0..=glfw::ffi::JOYSTICK_LAST {
+            let Some(joystick_id) = glfw::JoystickId::from_i32(index) else {
+                continue;
+            };
+            let id = index as u32;
+            let joystick = self.glfw.get_joystick(joystick_id);
+            // joysticks without a gamepad mapping are ignored
+            let Some(glfw_state) = joystick
+                .is_gamepad()
+                .then(|| joystick.get_gamepad_state())
+                .flatten()
+            else {
+                self.gamepads.remove(&id);
+                continue;
+            };
+            let state = self.gamepads.entry(id).or_insert_with(|| {
+                let name = joystick.get_gamepad_name().unwrap_or_default();
+                tracing::info!("gamepad connected: {name}");
+                gamepad::GamepadState::new(id, name)
+            });
+            for (glfw_button, button) in GLFW_GAMEPAD_BUTTONS {
+                let pressed = glfw_state.get_button_state(glfw_button) == Action::Press;
+                if state.buttons[button as usize] != pressed {
+                    state.buttons[button as usize] = pressed;
+                    if navigation {
+                        self.raw_input
+                            .events
+                            .extend(gamepad::navigation_event(button, pressed));
+                    }
+                }
+            }
+            for (glfw_axis, axis) in GLFW_GAMEPAD_AXES {
+                let value = glfw_state.get_axis(glfw_axis);
+                state.axes[axis as usize] = match axis {
+                    // glfw triggers are in -1.0..=1.0
+                    gamepad::GamepadAxis::LeftTrigger | gamepad::GamepadAxis::RightTrigger => {
+                        (value + 1.0) / 2.0
+                    }
+                    _ => value,
+                };
+            }
+        }
+    }
     #[allow(unused)]
     pub fn tick(&mut self) {
         self.frame_events.clear();
@@ -542,51 +1241,30 @@ impl GlfwBackend {
                 glfw::WindowEvent::Scroll(x, y) => {
                     Some(Event::Scroll([x as f32 * 25.0, y as f32 * 25.0].into()))
                 }
-                glfw::WindowEvent::Key(k, _, a, m) => match k {
-                    glfw::Key::C => {
-                        if glfw_to_egui_action(a).unwrap_or_default()
-                            && m.contains(glfw::Modifiers::Control)
-                        {
-                            Some(Event::Copy)
-                        } else {
-                            None
-                        }
-                    }
-                    glfw::Key::X => {
-                        if glfw_to_egui_action(a).unwrap_or_default()
-                            && m.contains(glfw::Modifiers::Control)
-                        {
-                            Some(Event::Cut)
-                        } else {
-                            None
-                        }
-                    }
-                    glfw::Key::V => {
-                        if glfw_to_egui_action(a).unwrap_or_default()
-                            && m.contains(glfw::Modifiers::Control)
-                        {
-                            Some(Event::Text(
-                                self.window.get_clipboard_string().unwrap_or_default(),
-                            ))
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                }
-                .or_else(|| {
-                    glfw_to_egui_key(k).map(|key| {
-                        let pressed = glfw_to_egui_action(a);
-                        let repeat = pressed.is_none();
-                        Event::Key {
+                glfw::WindowEvent::Key(k, _, a, m) => {
+                    if let Some(key) = glfw_to_egui_key(k) {
+                        let input = keyboard::KeyInput {
                             key,
-                            pressed: pressed.unwrap_or_default(),
+                            pressed: a != Action::Release,
+                            repeat: a == Action::Repeat,
                             modifiers: glfw_to_egui_modifers(m),
-                            repeat,
-                        }
-                    })
-                }),
-                glfw::WindowEvent::Char(c) => Some(Event::Text(c.to_string())),
+                        };
+                        let (clipboard, window) = (&mut self.clipboard, &self.window);
+                        self.backend_config.shortcuts.push_key_events(
+                            input,
+                            &mut self.raw_input.events,
+                            || match clipboard {
+                                Some(clipboard) => clipboard.get_text(),
+                                None => window.get_clipboard_string(),
+                            },
+                        );
+                    }
+                    None
+                }
+                // glfw doesn't send chars for shortcuts, but mac sends private use chars for function keys.
+                glfw::WindowEvent::Char(c) => {
+                    keyboard::text_event(c.encode_utf8(&mut [0; 4]), Default::default())
+                }
                 glfw::WindowEvent::ContentScale(x, _) => {
                     tracing::info!("content scale changed to {x}");
                     self.raw_input.pixels_per_point = Some(x);
@@ -605,22 +1283,49 @@ impl GlfwBackend {
                     None
                 }
                 glfw::WindowEvent::Close => {
-                    self.window.set_should_close(true);
+                    // glfw already set the flag. reset it until the user app accepts the close request.
+                    self.window.set_should_close(false);
+                    self.lifecycle_events.push(LifecycleEvent::CloseRequested);
+                    None
+                }
+                glfw::WindowEvent::Focus(focused) => {
+                    self.lifecycle_events
+                        .push(LifecycleEvent::FocusChanged(focused));
                     None
                 }
-
                 glfw::WindowEvent::FileDrop(f) => {
-                    self.raw_input
-                        .dropped_files
-                        .extend(f.into_iter().map(|p| egui::DroppedFile {
+                    let files: Vec<egui::DroppedFile> = f
+                        .into_iter()
+                        .map(|p| egui::DroppedFile {
                             path: Some(p),
                             name: "".to_string(),
                             last_modified: None,
                             bytes: None,
-                        }));
+                        })
+                        .collect();
+                    self.raw_input.dropped_files.extend(files.iter().cloned());
+                    self.lifecycle_events
+                        .push(LifecycleEvent::FilesDropped(files));
                     None
                 }
                 // this is in physical coords for some reason
+                glfw::WindowEvent::CursorPos(x, y) if self.relative_mouse_mode => {
+                    cursor_event = true;
+                    // the cursor is disabled, so glfw gives us an unbounded virtual position. we only need the difference.
+                    // converted to logical points, just like the cursor position below.
+                    let delta = [
+                        (x - self.last_virtual_cursor_pos[0]) as f32
+                            * self.physical_pixels_per_virtual_unit
+                            / self.scale,
+                        (y - self.last_virtual_cursor_pos[1]) as f32
+                            * self.physical_pixels_per_virtual_unit
+                            / self.scale,
+                    ];
+                    self.last_virtual_cursor_pos = [x, y];
+                    self.mouse_motion[0] += delta[0];
+                    self.mouse_motion[1] += delta[1];
+                    None
+                }
                 glfw::WindowEvent::CursorPos(x, y) => {
                     self.cursor_inside_bounds = true;
                     cursor_event = true;
@@ -694,8 +1399,32 @@ impl GlfwBackend {
             }
         }
         self.cursor_pos = logical_cursor_pos;
+        self.update_window_drag();
+    }
+    /// sets the cursor mode from [`Self::cursor_grab`], [`Self::cursor_visible`] and [`Self::relative_mouse_mode`]
+    fn update_cursor_mode(&mut self) {
+        let cursor_mode = if self.relative_mouse_mode || self.cursor_grab == CursorGrab::Locked {
+            glfw::CursorMode::Disabled
+        } else if self.cursor_visible {
+            glfw::CursorMode::Normal
+        } else {
+            glfw::CursorMode::Hidden
+        };
+        self.window.set_cursor_mode(cursor_mode);
     }
     fn set_cursor(&mut self, cursor: egui::CursorIcon) {
+        // don't touch the cursor mode if user disabled (grabbed) the cursor
+        let cursor_mode = self.window.get_cursor_mode();
+        if cursor == egui::CursorIcon::None {
+            if cursor_mode == glfw::CursorMode::Normal {
+                self.window.set_cursor_mode(glfw::CursorMode::Hidden);
+            }
+            return;
+        }
+        // user wants the cursor to stay hidden
+        if cursor_mode == glfw::CursorMode::Hidden && self.cursor_visible {
+            self.window.set_cursor_mode(glfw::CursorMode::Normal);
+        }
         let cursor = egui_to_glfw_cursor(cursor);
         if cursor != self.cursor_icon {
             self.cursor_icon = cursor;
@@ -758,18 +1487,50 @@ fn glfw_to_egui_key(key: glfw::Key) -> Option<Key> {
         glfw::Key::PageDown => Some(Key::PageDown),
         glfw::Key::Home => Some(Key::Home),
         glfw::Key::End => Some(Key::End),
+        glfw::Key::Minus | glfw::Key::KpSubtract => Some(Key::Minus),
+        glfw::Key::Equal | glfw::Key::KpAdd => Some(Key::PlusEquals),
+        glfw::Key::Kp0 => Some(Key::Num0),
+        glfw::Key::Kp1 => Some(Key::Num1),
+        glfw::Key::Kp2 => Some(Key::Num2),
+        glfw::Key::Kp3 => Some(Key::Num3),
+        glfw::Key::Kp4 => Some(Key::Num4),
+        glfw::Key::Kp5 => Some(Key::Num5),
+        glfw::Key::Kp6 => Some(Key::Num6),
+        glfw::Key::Kp7 => Some(Key::Num7),
+        glfw::Key::Kp8 => Some(Key::Num8),
+        glfw::Key::Kp9 => Some(Key::Num9),
+        glfw::Key::KpEnter => Some(Key::Enter),
+        glfw::Key::F1 => Some(Key::F1),
+        glfw::Key::F2 => Some(Key::F2),
+        glfw::Key::F3 => Some(Key::F3),
+        glfw::Key::F4 => Some(Key::F4),
+        glfw::Key::F5 => Some(Key::F5),
+        glfw::Key::F6 => Some(Key::F6),
+        glfw::Key::F7 => Some(Key::F7),
+        glfw::Key::F8 => Some(Key::F8),
+        glfw::Key::F9 => Some(Key::F9),
+        glfw::Key::F10 => Some(Key::F10),
+        glfw::Key::F11 => Some(Key::F11),
+        glfw::Key::F12 => Some(Key::F12),
+        glfw::Key::F13 => Some(Key::F13),
+        glfw::Key::F14 => Some(Key::F14),
+        glfw::Key::F15 => Some(Key::F15),
+        glfw::Key::F16 => Some(Key::F16),
+        glfw::Key::F17 => Some(Key::F17),
+        glfw::Key::F18 => Some(Key::F18),
+        glfw::Key::F19 => Some(Key::F19),
+        glfw::Key::F20 => Some(Key::F20),
         _ => None,
     }
 }
 
 pub fn glfw_to_egui_modifers(modifiers: glfw::Modifiers) -> egui::Modifiers {
-    egui::Modifiers {
-        alt: modifiers.contains(glfw::Modifiers::Alt),
-        ctrl: modifiers.contains(glfw::Modifiers::Control),
-        shift: modifiers.contains(glfw::Modifiers::Shift),
-        mac_cmd: false,
-        command: modifiers.contains(glfw::Modifiers::Control),
-    }
+    keyboard::modifiers(
+        modifiers.contains(glfw::Modifiers::Alt),
+        modifiers.contains(glfw::Modifiers::Control),
+        modifiers.contains(glfw::Modifiers::Shift),
+        modifiers.contains(glfw::Modifiers::Super),
+    )
 }
 
 pub fn glfw_to_egui_pointer_button(mb: glfw::MouseButton) -> PointerButton {
@@ -782,6 +1543,97 @@ pub fn glfw_to_egui_pointer_button(mb: glfw::MouseButton) -> PointerButton {
         _ => PointerButton::Secondary,
     }
 }
+/// glfw gamepad buttons and their egui_backend equivalents
+pub const GLFW_GAMEPAD_BUTTONS: [(glfw::GamepadButton, gamepad::GamepadButton);
+    gamepad::GamepadButton::COUNT] = [
+    (glfw::GamepadButton::ButtonA, gamepad::GamepadButton::South),
+    (glfw::GamepadButton::ButtonB, gamepad::GamepadButton::East),
+    (glfw::GamepadButton::ButtonX, gamepad::GamepadButton::West),
+    (glfw::GamepadButton::ButtonY, gamepad::GamepadButton::North),
+    (
+        glfw::GamepadButton::ButtonLeftBumper,
+        gamepad::GamepadButton::LeftBumper,
+    ),
+    (
+        glfw::GamepadButton::ButtonRightBumper,
+        gamepad::GamepadButton::RightBumper,
+    ),
+    (
+        glfw::GamepadButton::ButtonBack,
+        gamepad::GamepadButton::Back,
+    ),
+    (
+        glfw::GamepadButton::ButtonStart,
+        gamepad::GamepadButton::Start,
+    ),
+    (
+        glfw::GamepadButton::ButtonGuide,
+        gamepad::GamepadButton::Guide,
+    ),
+    (
+        glfw::GamepadButton::ButtonLeftThumb,
+        gamepad::GamepadButton::LeftThumb,
+    ),
+    (
+        glfw::GamepadButton::ButtonRightThumb,
+        gamepad::GamepadButton::RightThumb,
+    ),
+    (
+        glfw::GamepadButton::ButtonDpadUp,
+        gamepad::GamepadButton::DPadUp,
+    ),
+    (
+        glfw::GamepadButton::ButtonDpadDown,
+        gamepad::GamepadButton::DPadDown,
+    ),
+    (
+        glfw::GamepadButton::ButtonDpadLeft,
+        gamepad::GamepadButton::DPadLeft,
+    ),
+    (
+        glfw::GamepadButton::ButtonDpadRight,
+        gamepad::GamepadButton::DPadRight,
+    ),
+];
+/// glfw gamepad axes and their egui_backend equivalents
+pub const GLFW_GAMEPAD_AXES: [(glfw::GamepadAxis, gamepad::GamepadAxis);
+    gamepad::GamepadAxis::COUNT] = [
+    (glfw::GamepadAxis::AxisLeftX, gamepad::GamepadAxis::LeftX),
+    (glfw::GamepadAxis::AxisLeftY, gamepad::GamepadAxis::LeftY),
+    (glfw::GamepadAxis::AxisRightX, gamepad::GamepadAxis::RightX),
+    (glfw::GamepadAxis::AxisRightY, gamepad::GamepadAxis::RightY),
+    (
+        glfw::GamepadAxis::AxisLeftTrigger,
+        gamepad::GamepadAxis::LeftTrigger,
+    ),
+    (
+        glfw::GamepadAxis::AxisRightTrigger,
+        gamepad::GamepadAxis::RightTrigger,
+    ),
+];
+pub fn glfw_to_video_mode(mode: glfw::VidMode) -> VideoMode {
+    VideoMode {
+        size: [mode.width, mode.height],
+        bit_depth: (mode.red_bits + mode.green_bits + mode.blue_bits) as u16,
+        refresh_rate: Some(mode.refresh_rate as f32),
+    }
+}
+pub fn glfw_monitor_info(monitor: &glfw::Monitor) -> MonitorInfo {
+    let (x, y) = monitor.get_pos();
+    let current = monitor.get_video_mode();
+    MonitorInfo {
+        name: monitor.get_name().unwrap_or_default(),
+        position: [x as f32, y as f32],
+        size: current.map(|m| [m.width, m.height]).unwrap_or_default(),
+        scale: monitor.get_content_scale().0,
+        refresh_rate: current.map(|m| m.refresh_rate as f32),
+        video_modes: monitor
+            .get_video_modes()
+            .into_iter()
+            .map(glfw_to_video_mode)
+            .collect(),
+    }
+}
 /// will return true if pressed, false if released and None if repeat
 /// this allows us to use `unwrap_or_default` to get pressed as false when we get a key repeat event
 pub fn glfw_to_egui_action(a: glfw::Action) -> Option<bool> {
@@ -798,7 +1650,9 @@ pub fn egui_to_glfw_cursor(cursor: egui::CursorIcon) -> glfw::StandardCursor {
         egui::CursorIcon::Default => StandardCursor::Arrow,
         egui::CursorIcon::Crosshair => StandardCursor::Crosshair,
         egui::CursorIcon::VerticalText | egui::CursorIcon::Text => StandardCursor::IBeam,
-        egui::CursorIcon::Grab | egui::CursorIcon::Grabbing => StandardCursor::Hand,
+        egui::CursorIcon::PointingHand | egui::CursorIcon::Grab | egui::CursorIcon::Grabbing => {
+            StandardCursor::Hand
+        }
         egui::CursorIcon::ResizeColumn
         | egui::CursorIcon::ResizeWest
         | egui::CursorIcon::ResizeEast
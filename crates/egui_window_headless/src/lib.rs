@@ -0,0 +1,803 @@
+//! This crate provides a [`WindowBackend`] which doesn't create any OS window.
+//!
+//! It is primarily meant for CI and tests, where there's no display server to create a window with.
+//! The window has a fixed logical size and scale (unless you change it with [`WindowBackend::set_window_size`]),
+//! and the [`RawInput`] of every frame comes from a script provided in [`HeadlessConfig`].
+//!
+//! The event loop doesn't wait for anything. It just runs frames back to back until
+//! [`HeadlessConfig::max_frames`] frames are done or the [`HeadlessConfig::stop_predicate`] returns true.
+//!
+//! NOTE: the window handles are placeholders. So, gfx backends which need a real surface (wgpu / glow) will fail
+//! to create one. use a renderer which can draw offscreen.
+
+use egui_backend::egui::{PlatformOutput, Pos2, RawInput, Rect};
+use egui_backend::raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle, WebDisplayHandle,
+    WebWindowHandle,
+};
+use egui_backend::*;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+/// Called after every frame with the frame number (starting from zero) and the platform output of that frame.
+/// return true to stop the event loop.
+pub type StopPredicate = Box<dyn FnMut(u64, &PlatformOutput) -> bool>;
+
+/// The configuration struct for Headless Backend
+/// passed in to [`WindowBackend::try_new()`] of [`HeadlessBackend`]
+pub struct HeadlessConfig {
+    /// size of the window in logical points
+    pub logical_size: [f32; 2],
+    /// pixels per point. physical framebuffer size is `logical_size * scale`.
+    pub scale: f32,
+    /// input for each frame. first element is used for the first frame and so on.
+    /// once this runs out, we will use empty input.
+    /// `screen_rect`, `pixels_per_point` and `time` will be filled in by us if they are `None`.
+    pub scripted_input: VecDeque<RawInput>,
+    /// the (fake) duration between two frames. used to fill in `RawInput::time` and `RawInput::predicted_dt`
+    /// so that animations are deterministic.
+    pub frame_time: Duration,
+    /// event loop will stop after these many frames. `None` means that we will run until the stop predicate returns true.
+    /// defaults to a single frame.
+    pub max_frames: Option<u64>,
+    /// optional predicate to stop the event loop early.
+    pub stop_predicate: Option<StopPredicate>,
+    /// `None` (default) uses a [`clipboard::MemoryClipboard`]. see [`HeadlessBackend::clipboard`]
+    pub clipboard: Option<Box<dyn clipboard::ClipboardProvider>>,
+}
+
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        Self {
+            logical_size: [800.0, 600.0],
+            scale: 1.0,
+            scripted_input: VecDeque::new(),
+            frame_time: Duration::from_secs_f64(1.0 / 60.0),
+            max_frames: Some(1),
+            stop_predicate: None,
+            clipboard: None,
+        }
+    }
+}
+
+/// A placeholder "window". It provides web handles with an id of zero, as there's no real window.
+#[derive(Debug, Default)]
+pub struct HeadlessWindow;
+
+unsafe impl HasRawWindowHandle for HeadlessWindow {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Web(WebWindowHandle::empty())
+    }
+}
+unsafe impl HasRawDisplayHandle for HeadlessWindow {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        RawDisplayHandle::Web(WebDisplayHandle::empty())
+    }
+}
+
+/// This is the window backend for egui without any OS window.
+/// All the window state like position, title or visibility is just stored in the struct and returned when asked.
+pub struct HeadlessBackend {
+    pub window: HeadlessWindow,
+    /// in logical points
+    pub logical_size: [f32; 2],
+    /// ratio between physical pixels and logical points
+    pub scale: f32,
+    /// remaining input for the upcoming frames
+    pub scripted_input: VecDeque<RawInput>,
+    pub frame_time: Duration,
+    /// number of frames which have been run so far
+    pub frame_count: u64,
+    pub max_frames: Option<u64>,
+    pub stop_predicate: Option<StopPredicate>,
+    /// should be true if the size changed.
+    /// will be set to false after calling `UserApp::resize_framebuffer`
+    pub latest_resize_event: bool,
+    /// in logical points
+    pub window_position: [f32; 2],
+    pub title: String,
+    pub minimized: bool,
+    pub maximized: bool,
+    pub visible: bool,
+    pub always_on_top: bool,
+    pub passthrough: bool,
+    pub decorations: bool,
+    pub resizable: bool,
+    pub fullscreen: FullscreenMode,
+    pub cursor_grab: CursorGrab,
+    pub cursor_visible: bool,
+    /// doesn't change the input. set [`Self::mouse_motion`] yourself.
+    pub relative_mouse_mode: bool,
+    /// returned by [`WindowBackend::mouse_motion`]. set it before a frame to test mouse controls. reset after every frame.
+    pub mouse_motion: [f32; 2],
+    /// returned by [`WindowBackend::gamepads`]. set them to test gamepad controls.
+    /// navigation events are not generated, so push them into the scripted input too.
+    pub gamepads: Vec<gamepad::GamepadState>,
+    /// set this to true to stop the event loop after the current frame
+    /// For secondary windows, this means that the window wants to be closed. see [`UserApp::close_window`]
+    pub should_close: bool,
+    /// events for the lifecycle hooks of [`UserApp`]. There's no real window to generate them,
+    /// so push them here (eg: [`LifecycleEvent::CloseRequested`]) to test your hooks. They are handled in the next frame.
+    pub lifecycle_events: Vec<LifecycleEvent>,
+    /// created from [`BackendConfig::persistence_id`]. You can also set any storage (eg: a `BTreeMap`) to test persistence.
+    pub storage: Option<Box<dyn storage::Storage>>,
+    /// [`HeadlessConfig::clipboard`] or a [`clipboard::MemoryClipboard`] by default. The copied text of egui is written here,
+    /// so tests can check it with [`clipboard::ClipboardProvider::get_text`].
+    pub clipboard: Box<dyn clipboard::ClipboardProvider>,
+    pub backend_config: BackendConfig,
+    /// windows created with [`WindowBackend::create_window`]. They are just headless backends themselves,
+    /// and their own `secondary_windows` is always empty.
+    pub secondary_windows: BTreeMap<WindowId, HeadlessBackend>,
+    /// id of the next window created with [`WindowBackend::create_window`]
+    pub next_window_id: u64,
+}
+
+impl WindowBackend for HeadlessBackend {
+    type Configuration = HeadlessConfig;
+    type WindowType = HeadlessWindow;
+
+    fn try_new(
+        config: Self::Configuration,
+        backend_config: BackendConfig,
+    ) -> Result<Self, BackendError> {
+        let HeadlessConfig {
+            logical_size,
+            scale,
+            scripted_input,
+            frame_time,
+            max_frames,
+            stop_predicate,
+            clipboard,
+        } = config;
+        tracing::info!(
+            "HeadlessBackend created. logical_size: {logical_size:?}; scale: {scale}; scripted frames: {}",
+            scripted_input.len()
+        );
+        Ok(Self {
+            window: HeadlessWindow,
+            logical_size,
+            scale,
+            scripted_input,
+            frame_time,
+            frame_count: 0,
+            max_frames,
+            stop_predicate,
+            latest_resize_event: true, // provide so that on first prepare frame, renderers can set their viewport sizes
+            // size comes from `logical_size`, so that snapshots don't depend on the default window size
+            window_position: backend_config.position.unwrap_or_default(),
+            title: backend_config.title.clone(),
+            minimized: false,
+            maximized: false,
+            visible: true,
+            always_on_top: false,
+            passthrough: false,
+            decorations: backend_config.decorations,
+            resizable: backend_config.resizable,
+            fullscreen: FullscreenMode::Windowed,
+            cursor_grab: CursorGrab::None,
+            cursor_visible: true,
+            relative_mouse_mode: false,
+            mouse_motion: [0.0; 2],
+            gamepads: Vec::new(),
+            should_close: false,
+            lifecycle_events: Vec::new(),
+            storage: storage::storage_from_config(&backend_config),
+            clipboard: clipboard.unwrap_or_else(|| Box::<clipboard::MemoryClipboard>::default()),
+            backend_config,
+            secondary_windows: BTreeMap::new(),
+            next_window_id: WindowId::MAIN.0 + 1,
+        })
+    }
+
+    fn take_raw_input(&mut self) -> RawInput {
+        let mut raw_input = self.scripted_input.pop_front().unwrap_or_default();
+        raw_input
+            .screen_rect
+            .get_or_insert(Rect::from_min_size(Pos2::ZERO, self.logical_size.into()));
+        raw_input.pixels_per_point.get_or_insert(self.scale);
+        raw_input
+            .time
+            .get_or_insert(self.frame_count as f64 * self.frame_time.as_secs_f64());
+        raw_input.predicted_dt = self.frame_time.as_secs_f32();
+        raw_input
+    }
+
+    fn get_window(&mut self) -> Option<&mut Self::WindowType> {
+        Some(&mut self.window)
+    }
+
+    fn get_live_physical_size_framebuffer(&mut self) -> Option<[u32; 2]> {
+        Some([
+            (self.logical_size[0] * self.scale).round() as u32,
+            (self.logical_size[1] * self.scale).round() as u32,
+        ])
+    }
+
+    fn run_event_loop<U: UserApp<UserWindowBackend = Self> + 'static>(mut user_app: U) {
+        Self::run_frames(&mut user_app);
+    }
+
+    /// there are no events. input comes from the script when the frame is run.
+    fn poll_events(&mut self) -> Result<(), BackendError> {
+        Ok(())
+    }
+
+    /// secondary windows are run after the main window, but only the main window's platform output is returned.
+    /// This doesn't check `max_frames` or the stop predicate. see [`Self::run_frames`] for that.
+    fn run_frame<U: UserApp<UserWindowBackend = Self>>(
+        user_app: &mut U,
+    ) -> Option<(PlatformOutput, Duration)> {
+        let events = std::mem::take(&mut user_app.get_all().0.lifecycle_events);
+        if user_app.handle_lifecycle_events(events) {
+            user_app.get_all().0.should_close = true;
+        }
+        if user_app.get_all().0.latest_resize_event {
+            user_app.resize_framebuffer();
+            user_app.get_all().0.latest_resize_event = false;
+        }
+        let logical_size = user_app.get_all().0.logical_size;
+        let mut output = user_app.run(logical_size);
+        if let Some((platform_output, _)) = output.as_ref() {
+            user_app.get_all().0.handle_platform_output(platform_output);
+        }
+        user_app.get_all().0.mouse_motion = [0.0; 2];
+        let ids: Vec<WindowId> = user_app
+            .get_all()
+            .0
+            .secondary_windows
+            .keys()
+            .copied()
+            .collect();
+        for id in ids {
+            let Some(window) = user_app.get_all().0.secondary_windows.get_mut(&id) else {
+                continue;
+            };
+            if window.should_close {
+                user_app.close_window(id);
+                continue;
+            }
+            let resized = std::mem::take(&mut window.latest_resize_event);
+            let logical_size = window.logical_size;
+            if resized {
+                user_app.resize_framebuffer_by_id(id);
+            }
+            if let Some((_, timeout)) = user_app.run_window(id, logical_size) {
+                if let Some((_, repaint_after)) = output.as_mut() {
+                    *repaint_after = (*repaint_after).min(timeout);
+                }
+            }
+            if let Some(window) = user_app.get_all().0.secondary_windows.get_mut(&id) {
+                window.frame_count += 1;
+            }
+        }
+        user_app.get_all().0.frame_count += 1;
+        output
+    }
+
+    /// true if `should_close` is set or `max_frames` are done.
+    fn should_close(&self) -> bool {
+        self.should_close
+            || self
+                .max_frames
+                .map(|max_frames| self.frame_count >= max_frames)
+                .unwrap_or_default()
+    }
+
+    fn set_should_close(&mut self, should_close: bool) {
+        self.should_close = should_close;
+    }
+
+    fn get_storage(&mut self) -> Option<&mut dyn storage::Storage> {
+        // shorten the `'static` lifetime of the boxed trait object
+        self.storage
+            .as_deref_mut()
+            .map(|s| s as &mut dyn storage::Storage)
+    }
+
+    fn get_clipboard(&mut self) -> Option<&mut dyn clipboard::ClipboardProvider> {
+        Some(self.clipboard.as_mut())
+    }
+
+    fn get_config(&self) -> &BackendConfig {
+        &self.backend_config
+    }
+
+    fn capabilities(&self) -> WindowCapabilities {
+        // everything is just stored in the struct. except gl context and surface, as there's no real window.
+        WindowCapabilities {
+            swap_buffers: false,
+            get_proc_address: false,
+            get_window_position: true,
+            set_window_position: true,
+            get_window_size: true,
+            set_window_size: true,
+            get_window_minimized: true,
+            set_minimize_window: true,
+            get_window_maximized: true,
+            set_maximize_window: true,
+            get_window_visibility: true,
+            set_window_visibility: true,
+            get_always_on_top: true,
+            set_always_on_top: true,
+            get_passthrough: true,
+            set_passthrough: true,
+            get_decorations: true,
+            set_decorations: true,
+            get_resizable: true,
+            set_resizable: true,
+            // there's no mouse to drag the window with
+            begin_drag_move: false,
+            begin_drag_resize: false,
+            monitors: true,
+            get_fullscreen: true,
+            set_fullscreen: true,
+            set_cursor_grab: true,
+            set_cursor_visible: true,
+            relative_mouse_mode: true,
+            gamepads: true,
+            multi_window: true,
+            window_surface: false,
+        }
+    }
+
+    fn is_opengl(&self) -> bool {
+        false
+    }
+
+    fn set_window_title(&mut self, title: &str) {
+        self.title = title.to_string();
+    }
+
+    fn get_window_position(&mut self) -> Option<[f32; 2]> {
+        Some(self.window_position)
+    }
+
+    fn set_window_position(&mut self, pos: [f32; 2]) -> Result<(), BackendError> {
+        self.window_position = pos;
+        Ok(())
+    }
+
+    fn get_window_size(&mut self) -> Option<[f32; 2]> {
+        Some(self.logical_size)
+    }
+
+    fn set_window_size(&mut self, size: [f32; 2]) {
+        if size != self.logical_size {
+            self.logical_size = size;
+            self.latest_resize_event = true;
+        }
+    }
+
+    fn get_window_minimized(&mut self) -> Option<bool> {
+        Some(self.minimized)
+    }
+
+    fn set_minimize_window(&mut self, min: bool) {
+        self.minimized = min;
+    }
+
+    fn get_window_maximized(&mut self) -> Option<bool> {
+        Some(self.maximized)
+    }
+
+    fn set_maximize_window(&mut self, max: bool) {
+        self.maximized = max;
+    }
+
+    fn get_window_visibility(&mut self) -> Option<bool> {
+        Some(self.visible)
+    }
+
+    fn set_window_visibility(&mut self, vis: bool) {
+        self.visible = vis;
+    }
+
+    fn get_always_on_top(&mut self) -> Option<bool> {
+        Some(self.always_on_top)
+    }
+
+    fn set_always_on_top(&mut self, always_on_top: bool) -> Result<(), BackendError> {
+        self.always_on_top = always_on_top;
+        Ok(())
+    }
+
+    fn get_passthrough(&mut self) -> Option<bool> {
+        Some(self.passthrough)
+    }
+
+    fn set_passthrough(&mut self, passthrough: bool) -> Result<(), BackendError> {
+        self.passthrough = passthrough;
+        Ok(())
+    }
+
+    fn get_decorations(&mut self) -> Option<bool> {
+        Some(self.decorations)
+    }
+
+    fn set_decorations(&mut self, decorations: bool) -> Result<(), BackendError> {
+        self.decorations = decorations;
+        Ok(())
+    }
+
+    fn get_resizable(&mut self) -> Option<bool> {
+        Some(self.resizable)
+    }
+
+    fn set_resizable(&mut self, resizable: bool) -> Result<(), BackendError> {
+        self.resizable = resizable;
+        Ok(())
+    }
+
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        // a single monitor which is exactly as big as the window
+        let video_mode = VideoMode {
+            size: [
+                (self.logical_size[0] * self.scale).round() as u32,
+                (self.logical_size[1] * self.scale).round() as u32,
+            ],
+            bit_depth: 24,
+            refresh_rate: (!self.frame_time.is_zero()).then(|| 1.0 / self.frame_time.as_secs_f32()),
+        };
+        vec![MonitorInfo {
+            name: "headless".to_string(),
+            position: [0.0, 0.0],
+            size: video_mode.size,
+            scale: self.scale,
+            refresh_rate: video_mode.refresh_rate,
+            video_modes: vec![video_mode],
+        }]
+    }
+
+    fn current_monitor(&mut self) -> Option<usize> {
+        Some(0)
+    }
+
+    fn get_fullscreen(&mut self) -> Option<FullscreenMode> {
+        Some(self.fullscreen)
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), BackendError> {
+        self.fullscreen = mode;
+        Ok(())
+    }
+
+    fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<(), BackendError> {
+        self.cursor_grab = grab;
+        Ok(())
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<(), BackendError> {
+        self.cursor_visible = visible;
+        Ok(())
+    }
+
+    fn get_relative_mouse_mode(&mut self) -> Option<bool> {
+        Some(self.relative_mouse_mode)
+    }
+
+    fn mouse_motion(&mut self) -> Option<[f32; 2]> {
+        Some(self.mouse_motion)
+    }
+
+    fn set_relative_mouse_mode(&mut self, relative: bool) -> Result<(), BackendError> {
+        self.relative_mouse_mode = relative;
+        Ok(())
+    }
+
+    fn gamepads(&mut self) -> Vec<gamepad::GamepadState> {
+        self.gamepads.clone()
+    }
+
+    fn handle_platform_output(&mut self, platform_output: &PlatformOutput) {
+        if !platform_output.copied_text.is_empty() {
+            self.clipboard.set_text(&platform_output.copied_text);
+        }
+    }
+
+    fn create_window(&mut self, attributes: WindowAttributes) -> Result<WindowId, BackendError> {
+        let mut window = Self::try_new(
+            HeadlessConfig {
+                logical_size: attributes.size,
+                scale: self.scale,
+                frame_time: self.frame_time,
+                max_frames: None,
+                ..Default::default()
+            },
+            self.backend_config.clone(),
+        )?;
+        // only the main window is persisted
+        window.storage = None;
+        window.title = attributes.title;
+        window.window_position = attributes.position.unwrap_or_default();
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+        self.secondary_windows.insert(id, window);
+        Ok(id)
+    }
+
+    fn destroy_window(&mut self, id: WindowId) -> Result<(), BackendError> {
+        self.secondary_windows
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| BackendError::Other(format!("window {id:?} doesn't exist")))
+    }
+
+    fn window_ids(&self) -> Vec<WindowId> {
+        std::iter::once(WindowId::MAIN)
+            .chain(self.secondary_windows.keys().copied())
+            .collect()
+    }
+
+    fn get_window_by_id(&mut self, id: WindowId) -> Option<&mut Self::WindowType> {
+        if id == WindowId::MAIN {
+            return self.get_window();
+        }
+        self.secondary_windows.get_mut(&id)?.get_window()
+    }
+
+    fn get_live_physical_size_framebuffer_by_id(&mut self, id: WindowId) -> Option<[u32; 2]> {
+        if id == WindowId::MAIN {
+            return self.get_live_physical_size_framebuffer();
+        }
+        self.secondary_windows
+            .get_mut(&id)?
+            .get_live_physical_size_framebuffer()
+    }
+
+    fn take_raw_input_by_id(&mut self, id: WindowId) -> RawInput {
+        if id == WindowId::MAIN {
+            return self.take_raw_input();
+        }
+        self.secondary_windows
+            .get_mut(&id)
+            .map(|window| window.take_raw_input())
+            .unwrap_or_default()
+    }
+}
+
+impl HeadlessBackend {
+    /// Adds input for an upcoming frame. It will be used after all the previously queued input is used up.
+    pub fn push_input(&mut self, raw_input: RawInput) {
+        self.scripted_input.push_back(raw_input);
+    }
+    /// Same as [`WindowBackend::run_event_loop`], but borrows the user app instead of consuming it.
+    /// So, tests can inspect the app after running the frames.
+    pub fn run_frames<U: UserApp<UserWindowBackend = Self>>(user_app: &mut U) {
+        tracing::info!("entering headless event loop");
+        storage::load_state(user_app);
+        loop {
+            if user_app.get_all().0.should_close() {
+                break;
+            }
+            let platform_output = Self::run_frame(user_app)
+                .map(|(platform_output, _)| platform_output)
+                .unwrap_or_default();
+
+            let window_backend = user_app.get_all().0;
+            let frame_number = window_backend.frame_count - 1;
+            if let Some(stop_predicate) = window_backend.stop_predicate.as_mut() {
+                if stop_predicate(frame_number, &platform_output) {
+                    break;
+                }
+            }
+        }
+        tracing::debug!(
+            "headless event loop is exiting after {} frames",
+            user_app.get_all().0.frame_count
+        );
+        storage::save_state(user_app);
+        user_app.on_exit();
+    }
+    /// returns the window with this id. [`WindowId::MAIN`] returns self.
+    pub fn window_by_id(&mut self, id: WindowId) -> Option<&mut Self> {
+        if id == WindowId::MAIN {
+            Some(self)
+        } else {
+            self.secondary_windows.get_mut(&id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_backend::egui::{self, ClippedPrimitive, Event, TexturesDelta};
+
+    /// draws nothing. headless tests only care about the input.
+    struct NoopGfx;
+    impl GfxBackend for NoopGfx {
+        type Configuration = ();
+        fn try_new(_: &mut impl WindowBackend, _: ()) -> Result<Self, BackendError> {
+            Ok(Self)
+        }
+        fn resize_framebuffer(&mut self, _: &mut impl WindowBackend) {}
+        fn prepare_frame(&mut self, _: &mut impl WindowBackend) {}
+        fn render_egui(&mut self, _: Vec<ClippedPrimitive>, _: TexturesDelta, _: [f32; 2]) {}
+        fn present(&mut self, _: &mut impl WindowBackend) {}
+    }
+
+    /// records the input that egui got in every frame
+    struct App {
+        window_backend: HeadlessBackend,
+        gfx_backend: NoopGfx,
+        egui_context: egui::Context,
+        frames: Vec<(RawInput, Rect, f32)>,
+    }
+    impl App {
+        fn new(config: HeadlessConfig) -> Self {
+            Self {
+                window_backend: HeadlessBackend::new(config, Default::default()),
+                gfx_backend: NoopGfx,
+                egui_context: Default::default(),
+                frames: vec![],
+            }
+        }
+    }
+    impl UserApp for App {
+        type UserGfxBackend = NoopGfx;
+        type UserWindowBackend = HeadlessBackend;
+        fn get_all(&mut self) -> (&mut HeadlessBackend, &mut NoopGfx, &egui::Context) {
+            (
+                &mut self.window_backend,
+                &mut self.gfx_backend,
+                &self.egui_context,
+            )
+        }
+        fn gui_run(&mut self) {
+            let frame = self
+                .egui_context
+                .input(|i| (i.raw.clone(), i.screen_rect(), i.pixels_per_point()));
+            self.frames.push(frame);
+        }
+    }
+
+    #[test]
+    fn max_frames() {
+        let mut app = App::new(HeadlessConfig {
+            max_frames: Some(5),
+            ..Default::default()
+        });
+        HeadlessBackend::run_frames(&mut app);
+        assert_eq!(app.frames.len(), 5);
+        assert_eq!(app.window_backend.frame_count, 5);
+    }
+
+    #[test]
+    fn stop_predicate() {
+        let mut app = App::new(HeadlessConfig {
+            max_frames: Some(100),
+            stop_predicate: Some(Box::new(|frame_number, _| frame_number == 2)),
+            ..Default::default()
+        });
+        HeadlessBackend::run_frames(&mut app);
+        assert_eq!(app.frames.len(), 3);
+    }
+
+    #[test]
+    fn scripted_input() {
+        let scripted_input = (0..3)
+            .map(|i| RawInput {
+                events: vec![Event::Text(i.to_string())],
+                ..Default::default()
+            })
+            .collect();
+        let mut app = App::new(HeadlessConfig {
+            scripted_input,
+            max_frames: Some(4),
+            ..Default::default()
+        });
+        HeadlessBackend::run_frames(&mut app);
+        let events: Vec<Vec<Event>> = app
+            .frames
+            .iter()
+            .map(|(raw_input, _, _)| raw_input.events.clone())
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                vec![Event::Text("0".to_string())],
+                vec![Event::Text("1".to_string())],
+                vec![Event::Text("2".to_string())],
+                // script ran out
+                vec![],
+            ]
+        );
+        // time is filled in from the frame time
+        let frame_time = HeadlessConfig::default().frame_time.as_secs_f64();
+        for (i, (raw_input, _, _)) in app.frames.iter().enumerate() {
+            assert_eq!(raw_input.time, Some(i as f64 * frame_time));
+        }
+    }
+
+    #[test]
+    fn window_geometry_round_trip() {
+        let mut first = App::new(HeadlessConfig {
+            logical_size: [320.0, 240.0],
+            scale: 2.0,
+            ..Default::default()
+        });
+        first.window_backend.storage = Some(Box::<BTreeMap<String, String>>::default());
+        first
+            .window_backend
+            .set_window_position([10.0, 20.0])
+            .unwrap();
+        // saves the geometry on exit
+        HeadlessBackend::run_frames(&mut first);
+
+        let mut second = App::new(HeadlessConfig {
+            scale: 2.0,
+            ..Default::default()
+        });
+        second.window_backend.storage = first.window_backend.storage.take();
+        // loads the geometry before the first frame
+        HeadlessBackend::run_frames(&mut second);
+        let window_backend = &mut second.window_backend;
+        assert_eq!(window_backend.get_window_size(), Some([320.0, 240.0]));
+        assert_eq!(window_backend.get_window_position(), Some([10.0, 20.0]));
+        assert_eq!(
+            second.frames[0].1,
+            Rect::from_min_size(Pos2::ZERO, [320.0, 240.0].into())
+        );
+    }
+
+    #[test]
+    fn clipboard_from_config() {
+        let clipboard = clipboard::MemoryClipboard {
+            text: Some("from config".to_string()),
+        };
+        let mut app = App::new(HeadlessConfig {
+            clipboard: Some(Box::new(clipboard)),
+            ..Default::default()
+        });
+        let clipboard = app.window_backend.get_clipboard().unwrap();
+        assert_eq!(clipboard.get_text().as_deref(), Some("from config"));
+        // egui's copied text goes into the same clipboard
+        app.window_backend.handle_platform_output(&PlatformOutput {
+            copied_text: "copied".to_string(),
+            ..Default::default()
+        });
+        let clipboard = app.window_backend.get_clipboard().unwrap();
+        assert_eq!(clipboard.get_text().as_deref(), Some("copied"));
+    }
+
+    #[test]
+    fn screen_rect_and_scale() {
+        let mut app = App::new(HeadlessConfig {
+            logical_size: [320.0, 240.0],
+            scale: 2.0,
+            ..Default::default()
+        });
+        HeadlessBackend::run_frames(&mut app);
+        let (_, screen_rect, pixels_per_point) = app.frames[0];
+        assert_eq!(
+            screen_rect,
+            Rect::from_min_size(Pos2::ZERO, [320.0, 240.0].into())
+        );
+        assert_eq!(pixels_per_point, 2.0);
+        assert_eq!(
+            app.window_backend.get_live_physical_size_framebuffer(),
+            Some([640, 480])
+        );
+    }
+
+    #[test]
+    fn monitor_size_matches_framebuffer() {
+        // 101 * 1.5 = 151.5 must round the same way in both places
+        let mut window_backend = HeadlessBackend::try_new(
+            HeadlessConfig {
+                logical_size: [101.0, 75.0],
+                scale: 1.5,
+                ..Default::default()
+            },
+            Default::default(),
+        )
+        .unwrap();
+        let monitors = window_backend.monitors();
+        assert_eq!(monitors.len(), 1);
+        assert_eq!(
+            Some(monitors[0].size),
+            window_backend.get_live_physical_size_framebuffer()
+        );
+        assert_eq!(monitors[0].size, [152, 113]);
+    }
+}
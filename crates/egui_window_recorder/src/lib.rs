@@ -0,0 +1,621 @@
+//! This crate provides [`RecordingBackend`], which wraps any other [`WindowBackend`] and
+//! records the [`RawInput`] of every frame to a file, or replays a previously recorded file instead of the live input.
+//!
+//! The file is in json lines format. each line is a [`RecordedFrame`] with the `RawInput` returned by
+//! [`WindowBackend::take_raw_input`] and the logical size passed to [`UserApp::run`] in that frame.
+//!
+//! Just use `RecordingBackend<GlfwBackend>` (or any other backend) as your `UserWindowBackend` and set the
+//! [`RecorderMode`] in [`RecorderConfig`]. everything else is forwarded to the inner backend.
+//!
+//! NOTE: replay is only deterministic if the app itself is deterministic. For an exact reproduction,
+//! use the same window size (eg: `egui_window_headless` with the recorded logical size and scale)
+//! because the renderer still uses the live framebuffer size.
+
+use egui_backend::egui::{self, PlatformOutput, RawInput};
+use egui_backend::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A single line of the recording file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// logical size which was passed to [`UserApp::run`] in this frame
+    pub logical_size: [f32; 2],
+    pub raw_input: RawInput,
+}
+
+/// reads all frames from a recording file.
+pub fn read_recording(path: &Path) -> std::io::Result<Vec<RecordedFrame>> {
+    let mut frames = vec![];
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        frames.push(serde_json::from_str(&line)?);
+    }
+    Ok(frames)
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum RecorderMode {
+    /// just forward everything to the inner backend
+    #[default]
+    Passthrough,
+    /// record the input of every frame to this file. It will be truncated if it already exists.
+    Record(PathBuf),
+    /// replay the frames from this file instead of live input.
+    /// once all the frames are used up, we go back to live input.
+    Replay(PathBuf),
+}
+
+/// The configuration struct for Recording Backend
+pub struct RecorderConfig<C> {
+    /// configuration of the inner window backend
+    pub inner: C,
+    pub mode: RecorderMode,
+}
+
+impl<C: Default> Default for RecorderConfig<C> {
+    fn default() -> Self {
+        Self {
+            inner: Default::default(),
+            mode: Default::default(),
+        }
+    }
+}
+
+pub enum RecorderState {
+    Passthrough,
+    Recording(BufWriter<File>),
+    Replaying(VecDeque<RecordedFrame>),
+}
+
+/// A window backend which wraps another window backend to record / replay its input.
+pub struct RecordingBackend<W: WindowBackend> {
+    pub inner: W,
+    pub state: RecorderState,
+    /// logical size of the current frame. set by [`RecorderApp`] before calling [`UserApp::run`]
+    pub current_logical_size: [f32; 2],
+    /// number of frames recorded or replayed so far
+    pub frame_count: u64,
+}
+
+impl<W: WindowBackend> RecordingBackend<W> {
+    /// returns the logical size to use for the upcoming frame.
+    /// while replaying, this is the recorded logical size. Otherwise, it is the `live_logical_size`.
+    pub fn frame_logical_size(&mut self, live_logical_size: [f32; 2]) -> [f32; 2] {
+        let logical_size = match &self.state {
+            RecorderState::Replaying(frames) => frames
+                .front()
+                .map(|frame| frame.logical_size)
+                .unwrap_or(live_logical_size),
+            _ => live_logical_size,
+        };
+        self.current_logical_size = logical_size;
+        logical_size
+    }
+    /// true if we are still replaying recorded frames
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.state, RecorderState::Replaying(_))
+    }
+    /// stops recording / replaying. flushes the recording file if any.
+    pub fn stop(&mut self) {
+        if let RecorderState::Recording(writer) = &mut self.state {
+            if let Err(e) = writer.flush() {
+                tracing::error!("failed to flush the recording file: {e}");
+            }
+        }
+        self.state = RecorderState::Passthrough;
+    }
+}
+
+impl<W: WindowBackend> WindowBackend for RecordingBackend<W> {
+    type Configuration = RecorderConfig<W::Configuration>;
+    type WindowType = W::WindowType;
+
+    fn try_new(
+        config: Self::Configuration,
+        backend_config: BackendConfig,
+    ) -> Result<Self, BackendError> {
+        let RecorderConfig { inner, mode } = config;
+        let state = match mode {
+            RecorderMode::Passthrough => RecorderState::Passthrough,
+            RecorderMode::Record(path) => {
+                tracing::info!("recording input to {}", path.display());
+                RecorderState::Recording(BufWriter::new(File::create(&path).map_err(|e| {
+                    BackendError::Other(format!(
+                        "failed to create recording file {}: {e}",
+                        path.display()
+                    ))
+                })?))
+            }
+            RecorderMode::Replay(path) => {
+                let frames = read_recording(&path).map_err(|e| {
+                    BackendError::Other(format!(
+                        "failed to read recording file {}: {e}",
+                        path.display()
+                    ))
+                })?;
+                tracing::info!("replaying {} frames from {}", frames.len(), path.display());
+                RecorderState::Replaying(frames.into())
+            }
+        };
+        Ok(Self {
+            inner: W::try_new(inner, backend_config)?,
+            state,
+            current_logical_size: [0.0, 0.0],
+            frame_count: 0,
+        })
+    }
+
+    fn take_raw_input(&mut self) -> RawInput {
+        // we always take the live input, so that the events don't pile up in the inner backend while replaying
+        let live_input = self.inner.take_raw_input();
+        let raw_input = match &mut self.state {
+            RecorderState::Passthrough => return live_input,
+            RecorderState::Recording(writer) => {
+                let frame = RecordedFrame {
+                    logical_size: self.current_logical_size,
+                    raw_input: live_input,
+                };
+                // flush every frame, as some event loops (winit) never return.
+                if let Err(e) = serde_json::to_writer(&mut *writer, &frame)
+                    .map_err(std::io::Error::from)
+                    .and_then(|_| writer.write_all(b"\n"))
+                    .and_then(|_| writer.flush())
+                {
+                    tracing::error!("failed to record frame {}: {e}", self.frame_count);
+                }
+                frame.raw_input
+            }
+            RecorderState::Replaying(frames) => {
+                if let Some(frame) = frames.pop_front() {
+                    frame.raw_input
+                } else {
+                    tracing::warn!(
+                        "replay finished after {} frames. switching to live input",
+                        self.frame_count
+                    );
+                    self.state = RecorderState::Passthrough;
+                    return live_input;
+                }
+            }
+        };
+        self.frame_count += 1;
+        raw_input
+    }
+
+    fn get_window(&mut self) -> Option<&mut Self::WindowType> {
+        self.inner.get_window()
+    }
+
+    fn get_live_physical_size_framebuffer(&mut self) -> Option<[u32; 2]> {
+        self.inner.get_live_physical_size_framebuffer()
+    }
+
+    fn run_event_loop<U: UserApp<UserWindowBackend = Self> + 'static>(user_app: U) {
+        W::run_event_loop(RecorderApp(user_app));
+    }
+
+    fn poll_events(&mut self) -> Result<(), BackendError> {
+        self.inner.poll_events()
+    }
+
+    fn run_frame<U: UserApp<UserWindowBackend = Self>>(
+        user_app: &mut U,
+    ) -> Option<(PlatformOutput, Duration)> {
+        W::run_frame(&mut RecorderApp(user_app))
+    }
+
+    fn should_close(&self) -> bool {
+        self.inner.should_close()
+    }
+
+    fn set_should_close(&mut self, should_close: bool) {
+        self.inner.set_should_close(should_close)
+    }
+
+    fn get_storage(&mut self) -> Option<&mut dyn storage::Storage> {
+        self.inner.get_storage()
+    }
+
+    fn get_clipboard(&mut self) -> Option<&mut dyn clipboard::ClipboardProvider> {
+        self.inner.get_clipboard()
+    }
+
+    fn get_config(&self) -> &BackendConfig {
+        self.inner.get_config()
+    }
+
+    fn capabilities(&self) -> WindowCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn swap_buffers(&mut self) -> Result<(), BackendError> {
+        self.inner.swap_buffers()
+    }
+
+    fn is_opengl(&self) -> bool {
+        self.inner.is_opengl()
+    }
+
+    fn get_proc_address(&mut self, symbol: &str) -> Option<*const core::ffi::c_void> {
+        self.inner.get_proc_address(symbol)
+    }
+
+    fn set_window_title(&mut self, title: &str) {
+        self.inner.set_window_title(title)
+    }
+
+    fn get_window_position(&mut self) -> Option<[f32; 2]> {
+        self.inner.get_window_position()
+    }
+
+    fn set_window_position(&mut self, pos: [f32; 2]) -> Result<(), BackendError> {
+        self.inner.set_window_position(pos)
+    }
+
+    fn get_window_size(&mut self) -> Option<[f32; 2]> {
+        self.inner.get_window_size()
+    }
+
+    fn set_window_size(&mut self, size: [f32; 2]) {
+        self.inner.set_window_size(size)
+    }
+
+    fn get_window_minimized(&mut self) -> Option<bool> {
+        self.inner.get_window_minimized()
+    }
+
+    fn set_minimize_window(&mut self, min: bool) {
+        self.inner.set_minimize_window(min)
+    }
+
+    fn get_window_maximized(&mut self) -> Option<bool> {
+        self.inner.get_window_maximized()
+    }
+
+    fn set_maximize_window(&mut self, max: bool) {
+        self.inner.set_maximize_window(max)
+    }
+
+    fn get_window_visibility(&mut self) -> Option<bool> {
+        self.inner.get_window_visibility()
+    }
+
+    fn set_window_visibility(&mut self, vis: bool) {
+        self.inner.set_window_visibility(vis)
+    }
+
+    fn get_always_on_top(&mut self) -> Option<bool> {
+        self.inner.get_always_on_top()
+    }
+
+    fn set_always_on_top(&mut self, always_on_top: bool) -> Result<(), BackendError> {
+        self.inner.set_always_on_top(always_on_top)
+    }
+
+    fn get_passthrough(&mut self) -> Option<bool> {
+        self.inner.get_passthrough()
+    }
+
+    fn set_passthrough(&mut self, passthrough: bool) -> Result<(), BackendError> {
+        self.inner.set_passthrough(passthrough)
+    }
+
+    fn get_decorations(&mut self) -> Option<bool> {
+        self.inner.get_decorations()
+    }
+
+    fn set_decorations(&mut self, decorations: bool) -> Result<(), BackendError> {
+        self.inner.set_decorations(decorations)
+    }
+
+    fn get_resizable(&mut self) -> Option<bool> {
+        self.inner.get_resizable()
+    }
+
+    fn set_resizable(&mut self, resizable: bool) -> Result<(), BackendError> {
+        self.inner.set_resizable(resizable)
+    }
+
+    fn begin_drag_move(&mut self) -> Result<(), BackendError> {
+        self.inner.begin_drag_move()
+    }
+
+    fn begin_drag_resize(&mut self, edge: ResizeEdge) -> Result<(), BackendError> {
+        self.inner.begin_drag_resize(edge)
+    }
+
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        self.inner.monitors()
+    }
+
+    fn current_monitor(&mut self) -> Option<usize> {
+        self.inner.current_monitor()
+    }
+
+    fn get_fullscreen(&mut self) -> Option<FullscreenMode> {
+        self.inner.get_fullscreen()
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), BackendError> {
+        self.inner.set_fullscreen(mode)
+    }
+
+    fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<(), BackendError> {
+        self.inner.set_cursor_grab(grab)
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<(), BackendError> {
+        self.inner.set_cursor_visible(visible)
+    }
+
+    fn get_relative_mouse_mode(&mut self) -> Option<bool> {
+        self.inner.get_relative_mouse_mode()
+    }
+
+    fn set_relative_mouse_mode(&mut self, relative: bool) -> Result<(), BackendError> {
+        self.inner.set_relative_mouse_mode(relative)
+    }
+
+    fn mouse_motion(&mut self) -> Option<[f32; 2]> {
+        self.inner.mouse_motion()
+    }
+
+    fn gamepads(&mut self) -> Vec<gamepad::GamepadState> {
+        self.inner.gamepads()
+    }
+
+    fn handle_platform_output(&mut self, platform_output: &PlatformOutput) {
+        self.inner.handle_platform_output(platform_output)
+    }
+
+    fn create_window(&mut self, attributes: WindowAttributes) -> Result<WindowId, BackendError> {
+        self.inner.create_window(attributes)
+    }
+
+    fn destroy_window(&mut self, id: WindowId) -> Result<(), BackendError> {
+        self.inner.destroy_window(id)
+    }
+
+    fn window_ids(&self) -> Vec<WindowId> {
+        self.inner.window_ids()
+    }
+
+    fn get_window_by_id(&mut self, id: WindowId) -> Option<&mut Self::WindowType> {
+        self.inner.get_window_by_id(id)
+    }
+
+    fn get_live_physical_size_framebuffer_by_id(&mut self, id: WindowId) -> Option<[u32; 2]> {
+        self.inner.get_live_physical_size_framebuffer_by_id(id)
+    }
+
+    fn take_raw_input_by_id(&mut self, id: WindowId) -> RawInput {
+        if id == WindowId::MAIN {
+            return self.take_raw_input();
+        }
+        // only the main window is recorded. other windows always get live input.
+        self.inner.take_raw_input_by_id(id)
+    }
+
+    fn swap_buffers_by_id(&mut self, id: WindowId) -> Result<(), BackendError> {
+        self.inner.swap_buffers_by_id(id)
+    }
+
+    fn make_gl_context_current(&mut self, id: WindowId) -> Result<(), BackendError> {
+        self.inner.make_gl_context_current(id)
+    }
+}
+
+/// Adapter which lets the inner backend's event loop drive a `UserApp` of [`RecordingBackend`].
+/// [`RecordingBackend::run_event_loop`] uses this internally, but you can also use it if you drive the event loop yourself.
+pub struct RecorderApp<U>(pub U);
+
+impl<W, U> UserApp for RecorderApp<U>
+where
+    W: WindowBackend,
+    U: UserApp<UserWindowBackend = RecordingBackend<W>>,
+{
+    type UserGfxBackend = U::UserGfxBackend;
+    type UserWindowBackend = W;
+
+    fn get_all(
+        &mut self,
+    ) -> (
+        &mut Self::UserWindowBackend,
+        &mut Self::UserGfxBackend,
+        &egui::Context,
+    ) {
+        let (wb, gb, egui_context) = self.0.get_all();
+        (&mut wb.inner, gb, egui_context)
+    }
+
+    fn resize_framebuffer(&mut self) {
+        self.0.resize_framebuffer()
+    }
+
+    fn resume(&mut self) {
+        self.0.resume()
+    }
+
+    fn suspend(&mut self) {
+        self.0.suspend()
+    }
+
+    fn run(&mut self, logical_size: [f32; 2]) -> Option<(PlatformOutput, Duration)> {
+        let logical_size = self.0.get_all().0.frame_logical_size(logical_size);
+        self.0.run(logical_size)
+    }
+
+    fn gui_run(&mut self) {
+        self.0.gui_run()
+    }
+
+    fn on_close_requested(&mut self) -> bool {
+        self.0.on_close_requested()
+    }
+
+    fn on_focus_changed(&mut self, focused: bool) {
+        self.0.on_focus_changed(focused)
+    }
+
+    fn on_files_dropped(&mut self, files: &[egui::DroppedFile]) {
+        self.0.on_files_dropped(files)
+    }
+
+    fn on_exit(&mut self) {
+        self.0.on_exit()
+    }
+
+    fn handle_lifecycle_events(&mut self, events: Vec<LifecycleEvent>) -> bool {
+        self.0.handle_lifecycle_events(events)
+    }
+
+    fn get_window_context(&mut self, id: WindowId) -> Option<egui::Context> {
+        self.0.get_window_context(id)
+    }
+
+    fn gui_run_window(&mut self, id: WindowId) {
+        self.0.gui_run_window(id)
+    }
+
+    fn open_window(&mut self, attributes: WindowAttributes) -> Result<WindowId, BackendError> {
+        self.0.open_window(attributes)
+    }
+
+    fn close_window(&mut self, id: WindowId) {
+        self.0.close_window(id)
+    }
+
+    fn resize_framebuffer_by_id(&mut self, id: WindowId) {
+        self.0.resize_framebuffer_by_id(id)
+    }
+
+    fn run_window(
+        &mut self,
+        id: WindowId,
+        logical_size: [f32; 2],
+    ) -> Option<(PlatformOutput, Duration)> {
+        self.0.run_window(id, logical_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{ClippedPrimitive, Event, TexturesDelta};
+    use egui_window_headless::{HeadlessBackend, HeadlessConfig};
+
+    struct NoopGfx;
+    impl GfxBackend for NoopGfx {
+        type Configuration = ();
+        fn try_new(_: &mut impl WindowBackend, _: ()) -> Result<Self, BackendError> {
+            Ok(Self)
+        }
+        fn resize_framebuffer(&mut self, _: &mut impl WindowBackend) {}
+        fn prepare_frame(&mut self, _: &mut impl WindowBackend) {}
+        fn render_egui(&mut self, _: Vec<ClippedPrimitive>, _: TexturesDelta, _: [f32; 2]) {}
+        fn present(&mut self, _: &mut impl WindowBackend) {}
+    }
+
+    /// records the input and screen size that egui got in every frame
+    struct App {
+        window_backend: RecordingBackend<HeadlessBackend>,
+        gfx_backend: NoopGfx,
+        egui_context: egui::Context,
+        frames: Vec<(RawInput, egui::Vec2)>,
+    }
+    impl App {
+        /// runs `max_frames` frames of a headless backend wrapped in a recorder
+        fn run(inner: HeadlessConfig, mode: RecorderMode) -> Self {
+            let app = Self {
+                window_backend: RecordingBackend::new(
+                    RecorderConfig { inner, mode },
+                    Default::default(),
+                ),
+                gfx_backend: NoopGfx,
+                egui_context: Default::default(),
+                frames: vec![],
+            };
+            let mut recorder_app = RecorderApp(app);
+            HeadlessBackend::run_frames(&mut recorder_app);
+            let mut app = recorder_app.0;
+            app.window_backend.stop();
+            app
+        }
+    }
+    impl UserApp for App {
+        type UserGfxBackend = NoopGfx;
+        type UserWindowBackend = RecordingBackend<HeadlessBackend>;
+        fn get_all(
+            &mut self,
+        ) -> (
+            &mut RecordingBackend<HeadlessBackend>,
+            &mut NoopGfx,
+            &egui::Context,
+        ) {
+            (
+                &mut self.window_backend,
+                &mut self.gfx_backend,
+                &self.egui_context,
+            )
+        }
+        fn gui_run(&mut self) {
+            let frame = self
+                .egui_context
+                .input(|i| (i.raw.clone(), i.screen_rect().size()));
+            self.frames.push(frame);
+        }
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "egui_window_recorder_test_{}.jsonl",
+            std::process::id()
+        ));
+        let scripted_input = (0..3)
+            .map(|i| RawInput {
+                events: vec![
+                    Event::PointerMoved([i as f32 * 10.0, 5.0].into()),
+                    Event::Text(i.to_string()),
+                ],
+                ..Default::default()
+            })
+            .collect();
+        let recorded = App::run(
+            HeadlessConfig {
+                logical_size: [320.0, 240.0],
+                scale: 1.5,
+                scripted_input,
+                max_frames: Some(3),
+                ..Default::default()
+            },
+            RecorderMode::Record(path.clone()),
+        );
+        assert_eq!(read_recording(&path).unwrap().len(), 3);
+
+        // live input has a different size and no events, but replay must give the app the recorded frames.
+        let replayed = App::run(
+            HeadlessConfig {
+                max_frames: Some(3),
+                ..Default::default()
+            },
+            RecorderMode::Replay(path.clone()),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(recorded.frames.len(), 3);
+        assert_eq!(replayed.frames, recorded.frames);
+        assert!(recorded
+            .frames
+            .iter()
+            .all(|(_, size)| *size == egui::vec2(320.0, 240.0)));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
 
 use egui::{Event, Key, Modifiers, PointerButton, RawInput};
 use egui_backend::WindowBackend;
@@ -18,29 +18,84 @@ pub struct Sdl2Backend {
     pub gl_context: Option<sdl2::video::GLContext>,
     pub latest_resize_event: bool,
     pub should_close: bool,
+    /// collected in [`Self::tick`] and dispatched to the hooks of [`UserApp`] at the start of [`WindowBackend::run_frame`].
+    pub lifecycle_events: Vec<LifecycleEvent>,
+    /// created from [`BackendConfig::persistence_id`]. see [`storage::load_state`]
+    pub storage: Option<Box<dyn storage::Storage>>,
+    /// [`SDL2Config::clipboard`] or a [`Sdl2Clipboard`] by default. see [`clipboard`]
+    pub clipboard: Box<dyn clipboard::ClipboardProvider>,
     pub backend_config: BackendConfig,
+    /// sdl2 only keeps a pointer to the cursor which is set. So, we need to keep it alive until the next cursor is set.
+    pub cursor: Option<sdl2::mouse::Cursor>,
+    /// the cursor icon which was set in the last frame
+    pub cursor_icon: egui::CursorIcon,
+    /// we move/resize the window with the mouse in [`Self::tick`] while this is `Some`. see [`WindowBackend::begin_drag_move`]
+    pub window_drag: Option<util::WindowDrag>,
+    /// set by [`WindowBackend::set_cursor_grab`]
+    pub cursor_grab: CursorGrab,
+    /// set by [`WindowBackend::set_cursor_visible`]
+    pub cursor_visible: bool,
+    /// set by [`WindowBackend::set_relative_mouse_mode`]
+    pub relative_mouse_mode: bool,
+    /// mouse movement of this frame in relative mouse mode. reset after every frame. see [`WindowBackend::mouse_motion`]
+    pub mouse_motion: [f32; 2],
+    /// `None` if sdl2 failed to initialize the game controller subsystem.
+    pub game_controller_subsystem: Option<sdl2::GameControllerSubsystem>,
+    /// opened game controllers and their state by instance id. see [`WindowBackend::gamepads`]
+    pub game_controllers: BTreeMap<u32, (sdl2::controller::GameController, gamepad::GamepadState)>,
+    /// true while the user is composing text with IME. egui needs a `CompositionStart` event before any updates.
+    pub ime_composing: bool,
+}
+/// creates the window. the error string will be returned as [`BackendError::WindowCreation`] from `try_new`.
+/// Use [`window_builder_from_config`] to start with the settings of [`BackendConfig`].
+pub type WindowCreatorCallback =
+    Box<dyn FnOnce(&sdl2::VideoSubsystem, &BackendConfig) -> Result<sdl2::video::Window, String>>;
+/// creates a window builder with the settings of [`BackendConfig`] which must be set *before* creating the window.
+/// the rest (min/max size) are set by `try_new` after the window is created.
+pub fn window_builder_from_config(
+    video_subsystem: &sdl2::VideoSubsystem,
+    backend_config: &BackendConfig,
+) -> sdl2::video::WindowBuilder {
+    let mut window_builder = video_subsystem.window(
+        &backend_config.title,
+        backend_config.size[0] as u32,
+        backend_config.size[1] as u32,
+    );
+    // with highdpi, sdl2 window coordinates are in logical points.
+    window_builder.allow_highdpi();
+    if let Some([x, y]) = backend_config.position {
+        window_builder.position(x as i32, y as i32);
+    }
+    if !backend_config.decorations {
+        window_builder.borderless();
+    }
+    if backend_config.resizable {
+        window_builder.resizable();
+    }
+    window_builder
 }
-pub type WindowCreatorCallback = Box<dyn FnOnce(&sdl2::VideoSubsystem) -> sdl2::video::Window>;
 pub fn default_window_creator_callback(
     video_subsystem: &sdl2::VideoSubsystem,
-) -> sdl2::video::Window {
-    let mut window_builder = video_subsystem.window("default title", 800, 600);
+    backend_config: &BackendConfig,
+) -> Result<sdl2::video::Window, String> {
+    let mut window_builder = window_builder_from_config(video_subsystem, backend_config);
     // use opengl on wasm
     #[cfg(target_arch = "wasm32")]
     window_builder.opengl();
     #[cfg(not(target_arch = "wasm32"))]
     window_builder.vulkan();
-    window_builder.allow_highdpi();
-    window_builder.resizable();
-    window_builder.build().expect("failed to create a window")
+    window_builder.build().map_err(|e| e.to_string())
 }
 pub struct SDL2Config {
     pub window_creator_callback: WindowCreatorCallback,
+    /// `None` (default) uses the system clipboard with [`Sdl2Clipboard`]. see [`Sdl2Backend::clipboard`]
+    pub clipboard: Option<Box<dyn clipboard::ClipboardProvider>>,
 }
 impl Default for SDL2Config {
     fn default() -> Self {
         Self {
             window_creator_callback: Box::new(default_window_creator_callback),
+            clipboard: None,
         }
     }
 }
@@ -50,11 +105,39 @@ impl WindowBackend for Sdl2Backend {
 
     type WindowType = sdl2::video::Window;
 
-    fn new(config: Self::Configuration, backend_config: BackendConfig) -> Self {
-        let sdl_context = sdl2::init().unwrap();
-        let video_subsystem = sdl_context.video().unwrap();
-        let event_pump = sdl_context.event_pump().expect("failed to get event pump");
-        let window = (config.window_creator_callback)(&video_subsystem);
+    fn try_new(
+        config: Self::Configuration,
+        backend_config: BackendConfig,
+    ) -> Result<Self, BackendError> {
+        let sdl_context = sdl2::init().map_err(BackendError::WindowBackendInit)?;
+        // hints must be set before the video subsystem is initialized
+        if let Some(app_id) = backend_config.app_id.as_ref() {
+            sdl2::hint::set("SDL_VIDEO_X11_WMCLASS", app_id);
+            sdl2::hint::set("SDL_VIDEO_WAYLAND_WMCLASS", app_id);
+        }
+        let video_subsystem = sdl_context
+            .video()
+            .map_err(BackendError::WindowBackendInit)?;
+        let event_pump = sdl_context
+            .event_pump()
+            .map_err(BackendError::WindowBackendInit)?;
+        // gamepads are optional. sdl2 sends a device added event for each connected controller, where we open them.
+        let game_controller_subsystem = sdl_context
+            .game_controller()
+            .map_err(|err| tracing::error!("failed to initialize game controller subsystem: {err}"))
+            .ok();
+        let mut window = (config.window_creator_callback)(&video_subsystem, &backend_config)
+            .map_err(BackendError::WindowCreation)?;
+        if let Some([width, height]) = backend_config.min_size {
+            window
+                .set_minimum_size(width as u32, height as u32)
+                .map_err(|e| BackendError::WindowCreation(e.to_string()))?;
+        }
+        if let Some([width, height]) = backend_config.max_size {
+            window
+                .set_maximum_size(width as u32, height as u32)
+                .map_err(|e| BackendError::WindowCreation(e.to_string()))?;
+        }
         let window_flags = window.window_flags();
         let opengl_window_flag: u32 = sdl2::sys::SDL_WindowFlags::SDL_WINDOW_OPENGL as u32;
         let gl_context = if (window_flags & opengl_window_flag) != 0 {
@@ -62,10 +145,18 @@ impl WindowBackend for Sdl2Backend {
             // if window flags has opengl flag, create and make the context current.
             let gl_context = window
                 .gl_create_context()
-                .expect("failed to create opengl context");
+                .map_err(BackendError::GlContextCreation)?;
             window
                 .gl_make_current(&gl_context)
-                .expect("failed to make gl context current");
+                .map_err(BackendError::GlContextCreation)?;
+            let swap_interval = if backend_config.vsync {
+                sdl2::video::SwapInterval::VSync
+            } else {
+                sdl2::video::SwapInterval::Immediate
+            };
+            if let Err(err) = video_subsystem.gl_set_swap_interval(swap_interval) {
+                tracing::error!("failed to set swap interval: {err}");
+            }
             Some(gl_context)
         } else {
             None
@@ -87,7 +178,7 @@ impl WindowBackend for Sdl2Backend {
             pixels_per_point: Some(scale[0]),
             ..Default::default()
         };
-        Self {
+        Ok(Self {
             sdl_context,
             window,
             size_physical_pixels,
@@ -98,9 +189,24 @@ impl WindowBackend for Sdl2Backend {
             latest_resize_event: true,
             event_pump,
             should_close: false,
+            lifecycle_events: Vec::new(),
+            storage: storage::storage_from_config(&backend_config),
+            clipboard: config
+                .clipboard
+                .unwrap_or_else(|| Box::new(Sdl2Clipboard(video_subsystem.clipboard()))),
             gl_context,
             backend_config,
-        }
+            cursor: None,
+            cursor_icon: egui::CursorIcon::Default,
+            window_drag: None,
+            cursor_grab: CursorGrab::None,
+            cursor_visible: true,
+            relative_mouse_mode: false,
+            mouse_motion: [0.0; 2],
+            game_controller_subsystem,
+            game_controllers: BTreeMap::new(),
+            ime_composing: false,
+        })
     }
 
     fn take_raw_input(&mut self) -> egui::RawInput {
@@ -118,40 +224,27 @@ impl WindowBackend for Sdl2Backend {
     }
 
     fn run_event_loop<U: UserApp<UserWindowBackend = Self> + 'static>(mut user_app: U) {
-        let mut events_wait_duration = std::time::Duration::ZERO;
+        let mut frame_scheduler = util::FrameScheduler::new(user_app.get_all().0.get_config());
+        storage::load_state(&mut user_app);
         let callback = move || {
-            // gather events
-            user_app.get_all().0.tick(events_wait_duration);
-            // prepare surface for drawing
-            if user_app.get_all().0.latest_resize_event {
-                user_app.resize_framebuffer();
-                user_app.get_all().0.latest_resize_event = false;
-            }
-            // run userapp gui function. let user do anything he wants with window or gfx backends
-            let logical_size = [
-                user_app.get_all().0.size_physical_pixels[0] as f32 / user_app.get_all().0.scale[0],
-                user_app.get_all().0.size_physical_pixels[1] as f32 / user_app.get_all().0.scale[1],
-            ];
-            if let Some((platform_output, timeout)) = user_app.run(logical_size) {
-                events_wait_duration = timeout;
-                if !platform_output.copied_text.is_empty() {
-                    if let Err(err) = user_app
-                        .get_all()
-                        .0
-                        .window
-                        .subsystem()
-                        .clipboard()
-                        .set_clipboard_text(&platform_output.copied_text)
-                    {
-                        tracing::error!("failed to set clipboard text due to error: {err}");
-                    }
-                }
-            } else {
-                events_wait_duration = std::time::Duration::ZERO
-            }
+            // gather events. waiting forever is same as waiting for the max duration
+            user_app
+                .get_all()
+                .0
+                .tick(frame_scheduler.wait.unwrap_or(std::time::Duration::MAX));
+            frame_scheduler.start_frame();
+            let output = Self::run_frame(&mut user_app);
+            frame_scheduler.end_frame(output.map(|(_, repaint_after)| repaint_after));
             // on non emscripten targets (desktop), return a boolean indicating if event loop should close.
             #[cfg(not(target_os = "emscripten"))]
-            user_app.get_all().0.should_close
+            {
+                let should_close = user_app.get_all().0.should_close;
+                if should_close {
+                    storage::save_state(&mut user_app);
+                    user_app.on_exit();
+                }
+                should_close
+            }
         };
         // on emscripten, just keep calling forever i guess.
         #[cfg(target_os = "emscripten")]
@@ -169,15 +262,109 @@ impl WindowBackend for Sdl2Backend {
         }
     }
 
+    fn poll_events(&mut self) -> Result<(), BackendError> {
+        self.tick(std::time::Duration::ZERO);
+        Ok(())
+    }
+
+    fn run_frame<U: UserApp<UserWindowBackend = Self>>(
+        user_app: &mut U,
+    ) -> Option<(egui::PlatformOutput, std::time::Duration)> {
+        let events = std::mem::take(&mut user_app.get_all().0.lifecycle_events);
+        if user_app.handle_lifecycle_events(events) {
+            user_app.get_all().0.should_close = true;
+        }
+        // prepare surface for drawing
+        if user_app.get_all().0.latest_resize_event {
+            user_app.resize_framebuffer();
+            user_app.get_all().0.latest_resize_event = false;
+        }
+        // run userapp gui function. let user do anything he wants with window or gfx backends
+        let window_backend = user_app.get_all().0;
+        let logical_size = [
+            window_backend.size_physical_pixels[0] as f32 / window_backend.scale[0],
+            window_backend.size_physical_pixels[1] as f32 / window_backend.scale[1],
+        ];
+        let output = user_app.run(logical_size);
+        if let Some((platform_output, _)) = output.as_ref() {
+            user_app.get_all().0.handle_platform_output(platform_output);
+        }
+        user_app.get_all().0.mouse_motion = [0.0; 2];
+        output
+    }
+
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn set_should_close(&mut self, should_close: bool) {
+        self.should_close = should_close;
+    }
+
+    fn get_storage(&mut self) -> Option<&mut dyn storage::Storage> {
+        // shorten the `'static` lifetime of the boxed trait object
+        self.storage
+            .as_deref_mut()
+            .map(|s| s as &mut dyn storage::Storage)
+    }
+
+    fn get_clipboard(&mut self) -> Option<&mut dyn clipboard::ClipboardProvider> {
+        Some(self.clipboard.as_mut())
+    }
+
     fn get_config(&self) -> &BackendConfig {
         &self.backend_config
     }
-    fn swap_buffers(&mut self) {
+    fn capabilities(&self) -> WindowCapabilities {
+        let is_opengl = self.is_opengl();
+        WindowCapabilities {
+            swap_buffers: is_opengl,
+            get_proc_address: is_opengl,
+            get_window_position: true,
+            set_window_position: true,
+            get_window_size: true,
+            set_window_size: true,
+            get_window_minimized: true,
+            set_minimize_window: true,
+            get_window_maximized: true,
+            set_maximize_window: true,
+            get_window_visibility: true,
+            set_window_visibility: true,
+            get_always_on_top: true,
+            set_always_on_top: true,
+            // sdl2 doesn't support mouse passthrough
+            get_passthrough: false,
+            set_passthrough: false,
+            get_decorations: true,
+            set_decorations: true,
+            get_resizable: true,
+            set_resizable: true,
+            begin_drag_move: true,
+            begin_drag_resize: true,
+            monitors: true,
+            get_fullscreen: true,
+            set_fullscreen: true,
+            set_cursor_grab: true,
+            set_cursor_visible: true,
+            relative_mouse_mode: true,
+            gamepads: self.game_controller_subsystem.is_some(),
+            // sdl2 has a single event pump for all windows. we don't route events per window (yet).
+            multi_window: false,
+            window_surface: true,
+        }
+    }
+    fn swap_buffers(&mut self) -> Result<(), BackendError> {
+        if !self.is_opengl() {
+            return Err(BackendError::Unsupported("swap_buffers"));
+        }
         self.window.gl_swap_window();
+        Ok(())
     }
 
-    fn get_proc_address(&mut self, symbol: &str) -> *const core::ffi::c_void {
-        self.window.subsystem().gl_get_proc_address(symbol) as *const core::ffi::c_void
+    fn get_proc_address(&mut self, symbol: &str) -> Option<*const core::ffi::c_void> {
+        self.is_opengl().then(|| {
+            self.window.subsystem().gl_get_proc_address(symbol) as *const core::ffi::c_void
+        })
     }
 
     fn is_opengl(&self) -> bool {
@@ -194,11 +381,12 @@ impl WindowBackend for Sdl2Backend {
         [pos.0 as f32, pos.1 as f32].into()
     }
 
-    fn set_window_position(&mut self, pos: [f32; 2]) {
+    fn set_window_position(&mut self, pos: [f32; 2]) -> Result<(), BackendError> {
         self.window.set_position(
             sdl2::video::WindowPos::Positioned(pos[0] as i32),
             sdl2::video::WindowPos::Positioned(pos[1] as i32),
         );
+        Ok(())
     }
 
     fn get_window_size(&mut self) -> Option<[f32; 2]> {
@@ -237,8 +425,8 @@ impl WindowBackend for Sdl2Backend {
     }
 
     fn get_window_visibility(&mut self) -> Option<bool> {
-        // self.window.sho().into()
-        unimplemented!()
+        let shown_flag = sdl2::sys::SDL_WindowFlags::SDL_WINDOW_SHOWN as u32;
+        Some((self.window.window_flags() & shown_flag) != 0)
     }
 
     fn set_window_visibility(&mut self, vis: bool) {
@@ -250,89 +438,322 @@ impl WindowBackend for Sdl2Backend {
     }
 
     fn get_always_on_top(&mut self) -> Option<bool> {
-        unimplemented!()
+        Some(self.window.is_always_on_top())
     }
 
-    fn set_always_on_top(&mut self, _always_on_top: bool) {
-        unimplemented!()
+    fn set_always_on_top(&mut self, always_on_top: bool) -> Result<(), BackendError> {
+        self.window.set_always_on_top(always_on_top);
+        Ok(())
     }
 
     fn get_passthrough(&mut self) -> Option<bool> {
-        todo!()
+        None
     }
 
-    fn set_passthrough(&mut self, _passthrough: bool) {
-        todo!()
+    fn set_passthrough(&mut self, _passthrough: bool) -> Result<(), BackendError> {
+        Err(BackendError::Unsupported("set_passthrough"))
     }
-}
 
-impl Sdl2Backend {
-    pub fn tick(&mut self, events_wait_duration: std::time::Duration) {
-        self.frame_events.clear();
-        let mut modifiers = Modifiers::default();
-        for pressed in self.event_pump.keyboard_state().pressed_scancodes() {
-            match pressed {
-                sdl2::keyboard::Scancode::LCtrl => {
-                    modifiers.ctrl = true;
-                }
-                sdl2::keyboard::Scancode::LShift => {
-                    modifiers.shift = true;
-                }
-                sdl2::keyboard::Scancode::LAlt => {
-                    modifiers.alt = true;
-                }
-                sdl2::keyboard::Scancode::LGui => {
-                    modifiers.command = true;
-                }
-                sdl2::keyboard::Scancode::RCtrl => {
-                    modifiers.ctrl = true;
-                }
-                sdl2::keyboard::Scancode::RShift => {
-                    modifiers.shift = true;
-                }
-                sdl2::keyboard::Scancode::RAlt => {
-                    modifiers.alt = true;
-                }
-                sdl2::keyboard::Scancode::RGui => {
-                    modifiers.command = true;
-                }
-                _ => {}
+    fn get_decorations(&mut self) -> Option<bool> {
+        let borderless_flag = sdl2::sys::SDL_WindowFlags::SDL_WINDOW_BORDERLESS as u32;
+        Some((self.window.window_flags() & borderless_flag) == 0)
+    }
+
+    fn set_decorations(&mut self, decorations: bool) -> Result<(), BackendError> {
+        self.window.set_bordered(decorations);
+        Ok(())
+    }
+
+    fn get_resizable(&mut self) -> Option<bool> {
+        let resizable_flag = sdl2::sys::SDL_WindowFlags::SDL_WINDOW_RESIZABLE as u32;
+        Some((self.window.window_flags() & resizable_flag) != 0)
+    }
+
+    fn set_resizable(&mut self, resizable: bool) -> Result<(), BackendError> {
+        self.window.set_resizable(resizable);
+        Ok(())
+    }
+
+    fn begin_drag_move(&mut self) -> Result<(), BackendError> {
+        self.begin_drag(None);
+        Ok(())
+    }
+
+    fn begin_drag_resize(&mut self, edge: ResizeEdge) -> Result<(), BackendError> {
+        self.begin_drag(Some(edge));
+        Ok(())
+    }
+
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        let video_subsystem = self.window.subsystem();
+        let count = video_subsystem.num_video_displays().unwrap_or_else(|err| {
+            tracing::error!("failed to get number of displays: {err}");
+            0
+        });
+        (0..count)
+            .map(|index| sdl2_monitor_info(video_subsystem, index))
+            .collect()
+    }
+
+    fn current_monitor(&mut self) -> Option<usize> {
+        self.window
+            .display_index()
+            .ok()
+            .and_then(|index| usize::try_from(index).ok())
+    }
+
+    fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<(), BackendError> {
+        self.cursor_grab = grab;
+        self.update_cursor_grab();
+        Ok(())
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<(), BackendError> {
+        self.cursor_visible = visible;
+        self.sdl_context.mouse().show_cursor(visible);
+        Ok(())
+    }
+
+    fn get_relative_mouse_mode(&mut self) -> Option<bool> {
+        Some(self.relative_mouse_mode)
+    }
+
+    fn mouse_motion(&mut self) -> Option<[f32; 2]> {
+        Some(self.mouse_motion)
+    }
+
+    fn set_relative_mouse_mode(&mut self, relative: bool) -> Result<(), BackendError> {
+        self.relative_mouse_mode = relative;
+        self.update_cursor_grab();
+        Ok(())
+    }
+
+    fn gamepads(&mut self) -> Vec<gamepad::GamepadState> {
+        self.game_controllers
+            .values()
+            .map(|(_, state)| state.clone())
+            .collect()
+    }
+
+    fn get_fullscreen(&mut self) -> Option<FullscreenMode> {
+        let monitor = self.current_monitor();
+        Some(match self.window.fullscreen_state() {
+            sdl2::video::FullscreenType::Off => FullscreenMode::Windowed,
+            sdl2::video::FullscreenType::Desktop => FullscreenMode::Borderless { monitor },
+            sdl2::video::FullscreenType::True => FullscreenMode::Exclusive {
+                monitor,
+                video_mode: sdl2_to_video_mode(self.window.display_mode().ok()?),
+            },
+        })
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), BackendError> {
+        use sdl2::video::FullscreenType;
+        let (monitor, fullscreen_type) = match mode {
+            FullscreenMode::Windowed => (None, FullscreenType::Off),
+            FullscreenMode::Borderless { monitor } => (monitor, FullscreenType::Desktop),
+            FullscreenMode::Exclusive {
+                monitor,
+                video_mode,
+            } => {
+                let display_index = monitor
+                    .or_else(|| self.current_monitor())
+                    .unwrap_or_default() as i32;
+                let video_subsystem = self.window.subsystem();
+                let modes = video_subsystem
+                    .num_display_modes(display_index)
+                    .map_err(BackendError::Other)?;
+                let display_mode = (0..modes)
+                    .filter_map(|i| video_subsystem.display_mode(display_index, i).ok())
+                    .find(|dm| sdl2_to_video_mode(*dm) == video_mode)
+                    .ok_or_else(|| {
+                        BackendError::Other(format!(
+                            "monitor doesn't support video mode {video_mode:?}"
+                        ))
+                    })?;
+                self.window
+                    .set_display_mode(Some(display_mode))
+                    .map_err(BackendError::Other)?;
+                (monitor, FullscreenType::True)
             }
+        };
+        // sdl2 goes fullscreen on the display which contains the window. So, move the window there first.
+        if let Some(monitor) = monitor {
+            let bounds = self
+                .window
+                .subsystem()
+                .display_bounds(monitor as i32)
+                .map_err(BackendError::Other)?;
+            self.window.set_position(
+                sdl2::video::WindowPos::Positioned(bounds.x()),
+                sdl2::video::WindowPos::Positioned(bounds.y()),
+            );
         }
-        // first wait for the event or until time out.
-        if let Some(event) = self
-            .event_pump
-            .wait_event_timeout(events_wait_duration.as_millis() as u32)
-        {
-            for pressed in self.event_pump.keyboard_state().pressed_scancodes() {
-                match pressed {
-                    sdl2::keyboard::Scancode::LCtrl => {
-                        modifiers.ctrl = true;
-                    }
-                    sdl2::keyboard::Scancode::LShift => {
-                        modifiers.shift = true;
-                    }
-                    sdl2::keyboard::Scancode::LAlt => {
-                        modifiers.alt = true;
-                    }
-                    sdl2::keyboard::Scancode::LGui => {
-                        modifiers.command = true;
-                    }
-                    sdl2::keyboard::Scancode::RCtrl => {
-                        modifiers.ctrl = true;
-                    }
-                    sdl2::keyboard::Scancode::RShift => {
-                        modifiers.shift = true;
-                    }
-                    sdl2::keyboard::Scancode::RAlt => {
-                        modifiers.alt = true;
-                    }
-                    sdl2::keyboard::Scancode::RGui => {
-                        modifiers.command = true;
+        // sdl2 remembers the windowed position and size by itself
+        self.window
+            .set_fullscreen(fullscreen_type)
+            .map_err(BackendError::Other)
+    }
+
+    fn handle_platform_output(&mut self, platform_output: &egui::PlatformOutput) {
+        if !platform_output.copied_text.is_empty() {
+            self.clipboard.set_text(&platform_output.copied_text);
+        }
+        if platform_output.cursor_icon != self.cursor_icon {
+            self.cursor_icon = platform_output.cursor_icon;
+            if let Some(system_cursor) = egui_to_sdl2_cursor(platform_output.cursor_icon) {
+                match sdl2::mouse::Cursor::from_system(system_cursor) {
+                    Ok(cursor) => {
+                        cursor.set();
+                        self.cursor = Some(cursor);
                     }
-                    _ => {}
+                    Err(err) => tracing::error!("failed to create system cursor: {err}"),
                 }
+                // user might want the cursor to stay hidden
+                self.sdl_context.mouse().show_cursor(self.cursor_visible);
+            } else {
+                self.sdl_context.mouse().show_cursor(false);
             }
+        }
+        if let Some(open_url) = platform_output.open_url.as_ref() {
+            if let Err(err) = sdl2::url::open_url(&open_url.url) {
+                tracing::error!("failed to open url {}: {err}", open_url.url);
+            }
+        }
+        // sdl2 starts text input by default. we only keep it active while a text field has focus.
+        // On mobile, this will also show/hide the on-screen keyboard.
+        let text_input = self.window.subsystem().text_input();
+        if platform_output.mutable_text_under_cursor != text_input.is_active() {
+            if platform_output.mutable_text_under_cursor {
+                text_input.start();
+            } else {
+                text_input.stop();
+                // text field lost focus in the middle of a composition
+                if std::mem::take(&mut self.ime_composing) {
+                    self.raw_input
+                        .events
+                        .push(Event::CompositionEnd(String::new()));
+                }
+            }
+        }
+        if platform_output.mutable_text_under_cursor {
+            if let Some(pos) = platform_output.text_cursor_pos {
+                // egui points are the same as sdl2 window coordinates. the rect tells IME where to show the candidate popup.
+                text_input.set_rect(sdl2::rect::Rect::new(pos.x as i32, pos.y as i32, 1, 1));
+            }
+        }
+    }
+}
+
+/// the system clipboard through sdl2's clipboard util.
+pub struct Sdl2Clipboard(pub sdl2::clipboard::ClipboardUtil);
+
+impl clipboard::ClipboardProvider for Sdl2Clipboard {
+    fn get_text(&mut self) -> Option<String> {
+        if !self.0.has_clipboard_text() {
+            return None;
+        }
+        self.0
+            .clipboard_text()
+            .map_err(|err| tracing::error!("failed to get clipboard text due to error: {err}"))
+            .ok()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        if let Err(err) = self.0.set_clipboard_text(text) {
+            tracing::error!("failed to set clipboard text due to error: {err}");
+        }
+    }
+}
+
+pub fn sdl2_to_video_mode(display_mode: sdl2::video::DisplayMode) -> VideoMode {
+    VideoMode {
+        size: [display_mode.w as u32, display_mode.h as u32],
+        // SDL_BITSPERPIXEL
+        bit_depth: ((display_mode.format as u32 >> 8) & 0xFF) as u16,
+        // 0 means unspecified
+        refresh_rate: (display_mode.refresh_rate != 0).then_some(display_mode.refresh_rate as f32),
+    }
+}
+/// display `index` must be less than `VideoSubsystem::num_video_displays`
+pub fn sdl2_monitor_info(video_subsystem: &sdl2::VideoSubsystem, index: i32) -> MonitorInfo {
+    let current = video_subsystem.current_display_mode(index).ok();
+    let position = video_subsystem
+        .display_bounds(index)
+        .map(|bounds| [bounds.x() as f32, bounds.y() as f32])
+        .unwrap_or_default();
+    // sdl2 has no content scale. 96 dpi is the default scale on most platforms.
+    let scale = video_subsystem
+        .display_dpi(index)
+        .map(|(_, hdpi, _)| hdpi / 96.0)
+        .unwrap_or(1.0);
+    let video_modes = video_subsystem
+        .num_display_modes(index)
+        .map(|count| {
+            (0..count)
+                .filter_map(|i| video_subsystem.display_mode(index, i).ok())
+                .map(sdl2_to_video_mode)
+                .collect()
+        })
+        .unwrap_or_default();
+    MonitorInfo {
+        name: video_subsystem.display_name(index).unwrap_or_default(),
+        position,
+        size: current
+            .map(|dm| [dm.w as u32, dm.h as u32])
+            .unwrap_or_default(),
+        scale,
+        refresh_rate: current.and_then(|dm| sdl2_to_video_mode(dm).refresh_rate),
+        video_modes,
+    }
+}
+/// converts egui's cursor icon to sdl2's system cursor. returns None for [`egui::CursorIcon::None`], which means that
+/// the cursor should be hidden. sdl2 has very few cursors, so we use the closest one.
+pub fn egui_to_sdl2_cursor(cursor: egui::CursorIcon) -> Option<sdl2::mouse::SystemCursor> {
+    use egui::CursorIcon;
+    use sdl2::mouse::SystemCursor;
+    Some(match cursor {
+        CursorIcon::None => return None,
+        CursorIcon::Text | CursorIcon::VerticalText => SystemCursor::IBeam,
+        CursorIcon::Wait => SystemCursor::Wait,
+        CursorIcon::Progress => SystemCursor::WaitArrow,
+        CursorIcon::Crosshair | CursorIcon::Cell => SystemCursor::Crosshair,
+        CursorIcon::PointingHand | CursorIcon::Grab | CursorIcon::Grabbing => SystemCursor::Hand,
+        CursorIcon::NotAllowed | CursorIcon::NoDrop => SystemCursor::No,
+        CursorIcon::Move | CursorIcon::AllScroll => SystemCursor::SizeAll,
+        CursorIcon::ResizeHorizontal
+        | CursorIcon::ResizeEast
+        | CursorIcon::ResizeWest
+        | CursorIcon::ResizeColumn => SystemCursor::SizeWE,
+        CursorIcon::ResizeVertical
+        | CursorIcon::ResizeNorth
+        | CursorIcon::ResizeSouth
+        | CursorIcon::ResizeRow => SystemCursor::SizeNS,
+        CursorIcon::ResizeNeSw | CursorIcon::ResizeNorthEast | CursorIcon::ResizeSouthWest => {
+            SystemCursor::SizeNESW
+        }
+        CursorIcon::ResizeNwSe | CursorIcon::ResizeNorthWest | CursorIcon::ResizeSouthEast => {
+            SystemCursor::SizeNWSE
+        }
+        _ => SystemCursor::Arrow,
+    })
+}
+
+impl Sdl2Backend {
+    /// waits for events until `events_wait_duration` and then gathers all the events.
+    /// durations longer than `i32::MAX` milliseconds (eg: [`std::time::Duration::MAX`]) wait until we get an event.
+    pub fn tick(&mut self, events_wait_duration: std::time::Duration) {
+        self.frame_events.clear();
+        // first wait for the event or until time out.
+        let timeout_ms = events_wait_duration.as_millis();
+        let event = if timeout_ms > i32::MAX as u128 {
+            Some(self.event_pump.wait_event())
+        } else {
+            self.event_pump.wait_event_timeout(timeout_ms as u32)
+        };
+        // the modifiers for mouse and text events, which don't have their own modifier state.
+        let modifiers = sdl_to_egui_modifiers(self.sdl_context.keyboard().mod_state());
+        if let Some(event) = event {
             self.on_event(modifiers, event);
         }
         // after the timeout or an event before timeout, drain the rest of the events from pump
@@ -343,13 +764,153 @@ impl Sdl2Backend {
         for event in events {
             self.on_event(modifiers, event);
         }
+        self.update_window_drag();
     }
 
+    /// applies [`Self::cursor_grab`] and [`Self::relative_mouse_mode`].
+    /// sdl2 can only lock the cursor in relative mode, which also hides the cursor.
+    fn update_cursor_grab(&mut self) {
+        let relative = self.relative_mouse_mode || self.cursor_grab == CursorGrab::Locked;
+        self.sdl_context.mouse().set_relative_mouse_mode(relative);
+        self.window
+            .set_grab(relative || self.cursor_grab == CursorGrab::Confined);
+    }
+    /// returns the cursor position relative to the screen and whether the left mouse button is pressed.
+    fn global_mouse_state() -> ([f32; 2], bool) {
+        let mut x = 0;
+        let mut y = 0;
+        let buttons = unsafe { sdl2::sys::SDL_GetGlobalMouseState(&mut x, &mut y) };
+        // SDL_BUTTON_LMASK
+        ([x as f32, y as f32], (buttons & 1) != 0)
+    }
+    /// starts a [`util::WindowDrag`] from the current cursor position.
+    fn begin_drag(&mut self, edge: Option<ResizeEdge>) {
+        let (cursor, _) = Self::global_mouse_state();
+        let (x, y) = self.window.position();
+        let (width, height) = self.window.size();
+        self.window_drag = Some(util::WindowDrag {
+            edge,
+            start_cursor: cursor,
+            start_position: [x as f32, y as f32],
+            start_size: [width as f32, height as f32],
+        });
+        // keep getting mouse events even if the cursor moves outside the window faster than the window can follow
+        self.sdl_context.mouse().capture(true);
+    }
+    /// moves/resizes the window if there's a [`util::WindowDrag`] in progress. ends it once the left mouse button is released.
+    fn update_window_drag(&mut self) {
+        let Some(drag) = self.window_drag else {
+            return;
+        };
+        let (cursor, pressed) = Self::global_mouse_state();
+        if !pressed {
+            self.window_drag = None;
+            self.sdl_context.mouse().capture(false);
+            return;
+        }
+        let (position, size) =
+            drag.update(cursor, self.backend_config.min_size.unwrap_or([1.0, 1.0]));
+        self.window.set_position(
+            sdl2::video::WindowPos::Positioned(position[0] as i32),
+            sdl2::video::WindowPos::Positioned(position[1] as i32),
+        );
+        if drag.edge.is_some() {
+            if let Err(err) = self.window.set_size(size[0] as u32, size[1] as u32) {
+                tracing::error!("failed to resize window: {err}");
+            }
+        }
+    }
+
+    /// updates the gamepad state and returns the navigation event for the button (if enabled).
+    fn on_controller_button(
+        &mut self,
+        which: u32,
+        button: sdl2::controller::Button,
+        pressed: bool,
+    ) -> Option<Event> {
+        let (_, state) = self.game_controllers.get_mut(&which)?;
+        let button = sdl2_to_gamepad_button(button)?;
+        state.buttons[button as usize] = pressed;
+        if self.backend_config.gamepad_navigation {
+            gamepad::navigation_event(button, pressed)
+        } else {
+            None
+        }
+    }
+    /// closing the last window sends both window close and quit events. we only want one close request per frame.
+    fn push_close_requested(&mut self) {
+        if !self
+            .lifecycle_events
+            .contains(&LifecycleEvent::CloseRequested)
+        {
+            self.lifecycle_events.push(LifecycleEvent::CloseRequested);
+        }
+    }
+
+    /// sdl2 has no scale change event. So, we recalculate the scale from the window size and framebuffer size
+    /// whenever the window is resized or moved to a different display.
+    fn update_scale(&mut self) {
+        let (pw, ph) = self.window.drawable_size();
+        self.size_physical_pixels = [pw, ph];
+        let (w, h) = self.window.size();
+        // minimized windows can have zero size
+        if w == 0 || h == 0 {
+            return;
+        }
+        let scale = [pw as f32 / w as f32, ph as f32 / h as f32];
+        if scale != self.scale {
+            tracing::info!("window scale changed from {:?} to {scale:?}", self.scale);
+            self.scale = scale;
+            self.raw_input.pixels_per_point = Some(scale[0]);
+            self.latest_resize_event = true;
+        }
+    }
+    /// finger positions are normalized (`0.0..=1.0`) to the window, so we convert them to logical pixels.
+    fn touch_event(
+        &self,
+        touch_id: i64,
+        finger_id: i64,
+        phase: egui::TouchPhase,
+        [x, y]: [f32; 2],
+        pressure: f32,
+    ) -> Event {
+        let (w, h) = self.window.size();
+        Event::Touch {
+            device_id: egui::TouchDeviceId(touch_id as u64),
+            id: egui::TouchId(finger_id as u64),
+            phase,
+            pos: [x * w as f32, y * h as f32].into(),
+            force: pressure,
+        }
+    }
+
+    fn on_key(
+        &mut self,
+        scan_code: Scancode,
+        keymod: sdl2::keyboard::Mod,
+        pressed: bool,
+        repeat: bool,
+    ) {
+        if let Some(key) = sdl_to_egui_key(scan_code) {
+            let input = keyboard::KeyInput {
+                key,
+                pressed,
+                repeat,
+                modifiers: sdl_to_egui_modifiers(keymod),
+            };
+            let clipboard = &mut self.clipboard;
+            self.backend_config.shortcuts.push_key_events(
+                input,
+                &mut self.raw_input.events,
+                || clipboard.get_text(),
+            );
+        }
+    }
     fn on_event(&mut self, modifiers: Modifiers, event: sdl2::event::Event) {
         self.frame_events.push(event.clone());
         if let Some(egui_event) = match event {
             sdl2::event::Event::Quit { .. } => {
-                self.should_close = true;
+                self.push_close_requested();
                 None
             }
             sdl2::event::Event::Window { win_event, .. } => match win_event {
@@ -360,117 +921,78 @@ impl Sdl2Backend {
                         [w as f32, h as f32].into(),
                     ));
                     // physical width and height for framebuffer resize.
-                    let (pw, ph) = self.window.drawable_size();
-                    self.size_physical_pixels = [pw, ph];
+                    self.update_scale();
                     self.latest_resize_event = true;
 
                     None
                 }
+                // the window moved to a different display, which might have a different scale
+                sdl2::event::WindowEvent::DisplayChanged(_) => {
+                    self.update_scale();
+                    None
+                }
                 sdl2::event::WindowEvent::Close => {
-                    self.should_close = true;
+                    self.push_close_requested();
+                    None
+                }
+                sdl2::event::WindowEvent::FocusGained => {
+                    self.raw_input.focused = true;
+                    self.lifecycle_events
+                        .push(LifecycleEvent::FocusChanged(true));
+                    None
+                }
+                sdl2::event::WindowEvent::FocusLost => {
+                    self.raw_input.focused = false;
+                    self.lifecycle_events
+                        .push(LifecycleEvent::FocusChanged(false));
                     None
                 }
                 sdl2::event::WindowEvent::Leave => Some(Event::PointerGone),
                 _ => None,
             },
             sdl2::event::Event::KeyDown {
-                scancode,
+                scancode: Some(scan_code),
                 keymod,
                 repeat,
                 ..
             } => {
-                let scan_code = scancode.expect("scan code empty");
-                let modifiers = sdl_to_egui_modifiers(keymod);
-                match scan_code {
-                    Scancode::C => {
-                        if modifiers.ctrl {
-                            Some(Event::Copy)
-                        } else {
-                            None
-                        }
-                    }
-                    Scancode::X => {
-                        if modifiers.ctrl {
-                            Some(Event::Cut)
-                        } else {
-                            None
-                        }
-                    }
-                    Scancode::V => {
-                        if modifiers.ctrl {
-                            match self.window.subsystem().clipboard().clipboard_text() {
-                                Ok(text) => Some(Event::Text(text)),
-                                Err(err) => {
-                                    tracing::error!(
-                                        "failed to get clipboard text due to error: {err}"
-                                    );
-                                    None
-                                }
-                            }
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
-                }
-                .or_else(|| {
-                    sdl_to_egui_key(scan_code).map(|key| Event::Key {
-                        key,
-                        pressed: true,
-                        modifiers,
-                        repeat,
-                    })
-                })
+                self.on_key(scan_code, keymod, true, repeat);
+                None
             }
-
             sdl2::event::Event::KeyUp {
-                scancode,
+                scancode: Some(scan_code),
                 keymod,
                 repeat,
                 ..
             } => {
-                let scan_code = scancode.expect("scan code empty");
-                let modifiers = sdl_to_egui_modifiers(keymod);
-                match scan_code {
-                    Scancode::C => {
-                        if modifiers.ctrl {
-                            Some(Event::Copy)
-                        } else {
-                            None
-                        }
-                    }
-                    Scancode::X => {
-                        if modifiers.ctrl {
-                            Some(Event::Cut)
-                        } else {
-                            None
-                        }
-                    }
-                    Scancode::V => {
-                        if modifiers.ctrl {
-                            Some(Event::Text(
-                                self.window
-                                    .subsystem()
-                                    .clipboard()
-                                    .clipboard_text()
-                                    .unwrap_or_default(),
-                            ))
-                        } else {
-                            None
-                        }
-                    }
-                    _ => None,
+                self.on_key(scan_code, keymod, false, repeat);
+                None
+            }
+            // the committed text of IME composition
+            sdl2::event::Event::TextInput { text, .. } if self.ime_composing => {
+                self.ime_composing = false;
+                Some(Event::CompositionEnd(text))
+            }
+            sdl2::event::Event::TextInput { text, .. } => keyboard::text_event(&text, modifiers),
+            // sdl2 sends empty editing text after a commit or when the composition is cancelled
+            sdl2::event::Event::TextEditing { text, .. }
+                if text.is_empty() && !self.ime_composing =>
+            {
+                None
+            }
+            sdl2::event::Event::TextEditing { text, .. } => {
+                if !self.ime_composing {
+                    self.ime_composing = true;
+                    self.raw_input.events.push(Event::CompositionStart);
                 }
-                .or_else(|| {
-                    sdl_to_egui_key(scan_code).map(|key| Event::Key {
-                        key,
-                        pressed: false,
-                        modifiers,
-                        repeat,
-                    })
-                })
+                Some(Event::CompositionUpdate(text))
+            }
+            // window coordinates are logical points (see `update_scale`). so, no need to convert.
+            sdl2::event::Event::MouseMotion { xrel, yrel, .. } if self.relative_mouse_mode => {
+                self.mouse_motion[0] += xrel as f32;
+                self.mouse_motion[1] += yrel as f32;
+                None
             }
-            sdl2::event::Event::TextInput { text, .. } => Some(Event::Text(text)),
             sdl2::event::Event::MouseMotion { x, y, .. } => {
                 Some(Event::PointerMoved([x as f32, y as f32].into()))
             }
@@ -493,9 +1015,51 @@ impl Sdl2Backend {
             sdl2::event::Event::MouseWheel { x, y, .. } => {
                 Some(Event::Scroll([x as f32 * 25.0, y as f32 * 25.0].into()))
             }
+            sdl2::event::Event::FingerDown {
+                touch_id,
+                finger_id,
+                x,
+                y,
+                pressure,
+                ..
+            } => Some(self.touch_event(
+                touch_id,
+                finger_id,
+                egui::TouchPhase::Start,
+                [x, y],
+                pressure,
+            )),
+            sdl2::event::Event::FingerMotion {
+                touch_id,
+                finger_id,
+                x,
+                y,
+                pressure,
+                ..
+            } => Some(self.touch_event(
+                touch_id,
+                finger_id,
+                egui::TouchPhase::Move,
+                [x, y],
+                pressure,
+            )),
+            sdl2::event::Event::FingerUp {
+                touch_id,
+                finger_id,
+                x,
+                y,
+                pressure,
+                ..
+            } => {
+                Some(self.touch_event(touch_id, finger_id, egui::TouchPhase::End, [x, y], pressure))
+            }
+            // d_dist is the change in distance between the fingers, normalized to the size of the touch device.
+            sdl2::event::Event::MultiGesture { d_dist, .. } if d_dist != 0.0 => {
+                Some(Event::Zoom(1.0 + d_dist))
+            }
 
             sdl2::event::Event::DropFile { filename, .. } => {
-                self.raw_input.dropped_files.push(egui::DroppedFile {
+                let file = egui::DroppedFile {
                     path: Some(
                         PathBuf::from_str(&filename)
                             .expect("invalid path given for dropped file event"),
@@ -503,15 +1067,70 @@ impl Sdl2Backend {
                     name: "".to_string(),
                     last_modified: None,
                     bytes: None,
-                });
+                };
+                self.raw_input.dropped_files.push(file.clone());
+                self.lifecycle_events
+                    .push(LifecycleEvent::FilesDropped(vec![file]));
                 None
             }
             sdl2::event::Event::AppTerminating { .. } => {
                 tracing::info!("app terminating event");
                 None
             }
+            // a display was connected or disconnected, or its orientation changed
+            sdl2::event::Event::Display { .. } => {
+                self.update_scale();
+                None
+            }
+            sdl2::event::Event::ControllerDeviceAdded { which, .. } => {
+                // `which` is the device index here, but instance id in the rest of the controller events.
+                if let Some(subsystem) = self.game_controller_subsystem.as_ref() {
+                    match subsystem.open(which) {
+                        Ok(controller) => {
+                            tracing::info!("game controller connected: {}", controller.name());
+                            let state = gamepad::GamepadState::new(
+                                controller.instance_id(),
+                                controller.name(),
+                            );
+                            self.game_controllers
+                                .insert(controller.instance_id(), (controller, state));
+                        }
+                        Err(err) => tracing::error!("failed to open game controller: {err}"),
+                    }
+                }
+                None
+            }
+            sdl2::event::Event::ControllerDeviceRemoved { which, .. } => {
+                self.game_controllers.remove(&which);
+                None
+            }
+            sdl2::event::Event::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                if let Some((_, state)) = self.game_controllers.get_mut(&which) {
+                    let (axis, value) = sdl2_to_gamepad_axis(axis, value);
+                    state.axes[axis as usize] = value;
+                }
+                None
+            }
+            sdl2::event::Event::ControllerButtonDown { which, button, .. } => {
+                self.on_controller_button(which, button, true)
+            }
+            sdl2::event::Event::ControllerButtonUp { which, button, .. } => {
+                self.on_controller_button(which, button, false)
+            }
+            // game controllers are also joysticks. we use the controller events instead.
+            sdl2::event::Event::JoyAxisMotion { .. }
+            | sdl2::event::Event::JoyBallMotion { .. }
+            | sdl2::event::Event::JoyHatMotion { .. }
+            | sdl2::event::Event::JoyButtonDown { .. }
+            | sdl2::event::Event::JoyButtonUp { .. }
+            | sdl2::event::Event::JoyDeviceAdded { .. }
+            | sdl2::event::Event::JoyDeviceRemoved { .. }
+            | sdl2::event::Event::ControllerDeviceRemapped { .. } => None,
             rest => {
-                unimplemented!("sdl2 egui backend doesn't support this kinda event yet: {rest:#?}")
+                tracing::trace!("ignoring sdl2 event: {rest:?}");
+                None
             }
         } {
             self.raw_input.events.push(egui_event);
@@ -519,6 +1138,48 @@ impl Sdl2Backend {
     }
 }
 
+pub fn sdl2_to_gamepad_button(button: sdl2::controller::Button) -> Option<gamepad::GamepadButton> {
+    use gamepad::GamepadButton as GB;
+    use sdl2::controller::Button;
+    Some(match button {
+        Button::A => GB::South,
+        Button::B => GB::East,
+        Button::X => GB::West,
+        Button::Y => GB::North,
+        Button::LeftShoulder => GB::LeftBumper,
+        Button::RightShoulder => GB::RightBumper,
+        Button::Back => GB::Back,
+        Button::Start => GB::Start,
+        Button::Guide => GB::Guide,
+        Button::LeftStick => GB::LeftThumb,
+        Button::RightStick => GB::RightThumb,
+        Button::DPadUp => GB::DPadUp,
+        Button::DPadDown => GB::DPadDown,
+        Button::DPadLeft => GB::DPadLeft,
+        Button::DPadRight => GB::DPadRight,
+        // paddles, touchpad etc..
+        _ => return None,
+    })
+}
+/// converts the raw `value` to the range of [`gamepad::GamepadState::axis`].
+pub fn sdl2_to_gamepad_axis(
+    axis: sdl2::controller::Axis,
+    value: i16,
+) -> (gamepad::GamepadAxis, f32) {
+    use gamepad::GamepadAxis as GA;
+    use sdl2::controller::Axis;
+    // i16::MIN would be slightly below -1.0
+    let value = (value as f32 / i16::MAX as f32).max(-1.0);
+    let axis = match axis {
+        Axis::LeftX => GA::LeftX,
+        Axis::LeftY => GA::LeftY,
+        Axis::RightX => GA::RightX,
+        Axis::RightY => GA::RightY,
+        Axis::TriggerLeft => GA::LeftTrigger,
+        Axis::TriggerRight => GA::RightTrigger,
+    };
+    (axis, value)
+}
 fn sdl_to_egui_pointer_button(mb: sdl2::mouse::MouseButton) -> Option<egui::PointerButton> {
     match mb {
         sdl2::mouse::MouseButton::Left => Some(PointerButton::Primary),
@@ -532,13 +1193,12 @@ fn sdl_to_egui_pointer_button(mb: sdl2::mouse::MouseButton) -> Option<egui::Poin
 
 fn sdl_to_egui_modifiers(modifiers: sdl2::keyboard::Mod) -> Modifiers {
     use sdl2::keyboard::Mod;
-    Modifiers {
-        alt: modifiers.contains(Mod::LALTMOD) || modifiers.contains(Mod::RALTMOD),
-        ctrl: modifiers.contains(Mod::LCTRLMOD) || modifiers.contains(Mod::RCTRLMOD),
-        shift: modifiers.contains(Mod::LSHIFTMOD) || modifiers.contains(Mod::RSHIFTMOD),
-        mac_cmd: false,
-        command: modifiers.contains(Mod::LGUIMOD) || modifiers.contains(Mod::RGUIMOD),
-    }
+    keyboard::modifiers(
+        modifiers.intersects(Mod::LALTMOD | Mod::RALTMOD),
+        modifiers.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+        modifiers.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+        modifiers.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+    )
 }
 fn sdl_to_egui_key(key: Scancode) -> Option<egui::Key> {
     match key {
@@ -613,6 +1273,19 @@ fn sdl_to_egui_key(key: Scancode) -> Option<egui::Key> {
         Scancode::F18 => Some(Key::F18),
         Scancode::F19 => Some(Key::F19),
         Scancode::F20 => Some(Key::F20),
+        Scancode::Minus | Scancode::KpMinus => Some(Key::Minus),
+        Scancode::Equals | Scancode::KpPlus => Some(Key::PlusEquals),
+        Scancode::Kp0 => Some(Key::Num0),
+        Scancode::Kp1 => Some(Key::Num1),
+        Scancode::Kp2 => Some(Key::Num2),
+        Scancode::Kp3 => Some(Key::Num3),
+        Scancode::Kp4 => Some(Key::Num4),
+        Scancode::Kp5 => Some(Key::Num5),
+        Scancode::Kp6 => Some(Key::Num6),
+        Scancode::Kp7 => Some(Key::Num7),
+        Scancode::Kp8 => Some(Key::Num8),
+        Scancode::Kp9 => Some(Key::Num9),
+        Scancode::KpEnter => Some(Key::Enter),
         _ => None,
     }
 }
@@ -14,23 +14,23 @@ use wasm_bindgen::JsCast;
 use winit::platform::web::WindowBuilderExtWebSys;
 
 /// config that you provide to winit backend
-#[derive(Debug)]
 pub struct WinitConfig {
     #[cfg(target_os = "android")]
     pub android_app: winit::platform::android::activity::AndroidApp,
-    /// window title
-    pub title: String,
     /// on web: winit will try to get the canvas element with this id attribute and use it as the window's context
     /// for now, it must not be empty. we can later provide options like creating a canvas ourselves and adding it to dom
     /// defualt value is : `egui_canvas`
     /// so, make sure there's a canvas element in html body with this id
     pub dom_element_id: Option<String>,
+    /// `None` (default) uses the system clipboard. The system clipboard is not even created if this is set.
+    /// see [`WinitBackend::clipboard`]
+    pub clipboard: Option<Box<dyn clipboard::ClipboardProvider>>,
 }
 impl Default for WinitConfig {
     fn default() -> Self {
         Self {
-            title: "egui winit window".to_string(),
             dom_element_id: Some("egui_canvas".to_string()),
+            clipboard: None,
             #[cfg(target_os = "android")]
             android_app: unimplemented!(
                 "winit requires android 'app' struct from android_main function"
@@ -38,6 +38,16 @@ impl Default for WinitConfig {
         }
     }
 }
+impl std::fmt::Debug for WinitConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut s = f.debug_struct("WinitConfig");
+        #[cfg(target_os = "android")]
+        s.field("android_app", &self.android_app);
+        s.field("dom_element_id", &self.dom_element_id)
+            .field("clipboard", &self.clipboard.as_ref().map(|_| "custom"))
+            .finish()
+    }
+}
 /// This is the winit WindowBackend for egui
 pub struct WinitBackend {
     /// we want to take out the event loop when we call the  `WindowBackend::run_event_loop` fn
@@ -63,10 +73,31 @@ pub struct WinitBackend {
     /// should be true if there's been a resize event
     /// should be set to false once the renderer takes the latest size during `GfxBackend::prepare_frame`
     pub latest_resize_event: bool,
-    /// ???
+    /// set when the user app accepts a close request. the event loop exits after this frame.
     pub should_close: bool,
+    /// collected while handling events and dispatched to the hooks of [`UserApp`] at the start of [`WindowBackend::run_frame`].
+    pub lifecycle_events: Vec<LifecycleEvent>,
+    /// created from [`BackendConfig::persistence_id`]. see [`storage::load_state`]
+    pub storage: Option<Box<dyn storage::Storage>>,
     pub backend_config: BackendConfig,
     pub window_builder: WindowBuilder,
+    /// [`WinitConfig::clipboard`] if set. winit doesn't have a clipboard api. So, this is an [`ArboardClipboard`] by default,
+    /// or a [`clipboard::MemoryClipboard`] if arboard is not supported on this platform or fails. see [`clipboard`]
+    pub clipboard: Box<dyn clipboard::ClipboardProvider>,
+    /// the cursor icon which was set in the last frame
+    pub cursor_icon: egui::CursorIcon,
+    /// whether IME (text input) is enabled for the window. egui enables it when a text field has focus.
+    pub ime_allowed: bool,
+    /// true between the first IME preedit event and the commit. egui needs a `CompositionStart` event before any updates.
+    pub ime_composing: bool,
+    /// set by [`WindowBackend::set_cursor_grab`]
+    pub cursor_grab: CursorGrab,
+    /// set by [`WindowBackend::set_cursor_visible`]
+    pub cursor_visible: bool,
+    /// set by [`WindowBackend::set_relative_mouse_mode`]
+    pub relative_mouse_mode: bool,
+    /// mouse movement of this frame in relative mouse mode. reset after every frame. see [`WindowBackend::mouse_motion`]
+    pub mouse_motion: [f32; 2],
 }
 impl Drop for WinitBackend {
     fn drop(&mut self) {
@@ -77,7 +108,10 @@ impl WindowBackend for WinitBackend {
     type Configuration = WinitConfig;
     type WindowType = winit::window::Window;
 
-    fn new(config: Self::Configuration, backend_config: BackendConfig) -> Self {
+    fn try_new(
+        config: Self::Configuration,
+        backend_config: BackendConfig,
+    ) -> Result<Self, BackendError> {
         let mut event_loop = winit::event_loop::EventLoopBuilder::with_user_event();
         #[cfg(target_os = "android")]
         use winit::platform::android::EventLoopBuilderExtAndroid;
@@ -88,26 +122,72 @@ impl WindowBackend for WinitBackend {
 
         #[allow(unused_mut)]
         let mut window_builder = WindowBuilder::new()
-            .with_resizable(true)
-            .with_title(config.title);
+            .with_title(&backend_config.title)
+            .with_resizable(backend_config.resizable)
+            .with_decorations(backend_config.decorations)
+            .with_transparent(backend_config.transparent.unwrap_or_default());
+        // canvas or screen decides the size on web and android
+        #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+        {
+            let [width, height] = backend_config.size;
+            window_builder =
+                window_builder.with_inner_size(winit::dpi::LogicalSize::new(width, height));
+            if let Some([x, y]) = backend_config.position {
+                window_builder =
+                    window_builder.with_position(winit::dpi::LogicalPosition::new(x, y));
+            }
+        }
+        if let Some([width, height]) = backend_config.min_size {
+            window_builder =
+                window_builder.with_min_inner_size(winit::dpi::LogicalSize::new(width, height));
+        }
+        if let Some([width, height]) = backend_config.max_size {
+            window_builder =
+                window_builder.with_max_inner_size(winit::dpi::LogicalSize::new(width, height));
+        }
+        // this also sets the app_id on wayland
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        if let Some(app_id) = backend_config.app_id.as_ref() {
+            use winit::platform::x11::WindowBuilderExtX11;
+            window_builder = window_builder.with_name(app_id, app_id);
+        }
         #[cfg(target_arch = "wasm32")]
         let window = {
             let document = web_sys::window()
-                .expect("failed ot get websys window")
-                .document()
-                .expect("failed to get websys doc");
-            let canvas = config.dom_element_id.map(|canvas_id| {
+                .and_then(|window| window.document())
+                .ok_or_else(|| {
+                    BackendError::WindowBackendInit("failed to get websys document".to_string())
+                })?;
+            let canvas = config
+                .dom_element_id
+                .map(|canvas_id| {
                     document
                         .get_element_by_id(&canvas_id)
-                        .expect("config doesn't contain canvas and DOM doesn't have a canvas element either")
-                        .dyn_into::<web_sys::HtmlCanvasElement>().expect("failed to get canvas converted into html canvas element")
-                });
+                        .ok_or_else(|| {
+                            BackendError::WindowCreation(format!(
+                                "DOM doesn't have a canvas element with id {canvas_id}"
+                            ))
+                        })?
+                        .dyn_into::<web_sys::HtmlCanvasElement>()
+                        .map_err(|_| {
+                            BackendError::WindowCreation(format!(
+                                "element with id {canvas_id} is not a canvas"
+                            ))
+                        })
+                })
+                .transpose()?;
             window_builder = window_builder.with_canvas(canvas);
             // create winit window
             let window = window_builder
                 .clone()
                 .build(&el)
-                .expect("failed to create winit window");
+                .map_err(|e| BackendError::WindowCreation(e.to_string()))?;
 
             Some(window)
         };
@@ -116,7 +196,7 @@ impl WindowBackend for WinitBackend {
             window_builder
                 .clone()
                 .build(&el)
-                .expect("failed to create winit window"),
+                .map_err(|e| BackendError::WindowCreation(e.to_string()))?,
         );
 
         #[cfg(target_os = "android")]
@@ -126,7 +206,8 @@ impl WindowBackend for WinitBackend {
         let scale = 1.0;
 
         let raw_input = RawInput::default();
-        Self {
+        let clipboard = config.clipboard.unwrap_or_else(system_clipboard);
+        Ok(Self {
             event_loop: Some(el),
             window,
             modifiers: Modifiers::default(),
@@ -137,10 +218,20 @@ impl WindowBackend for WinitBackend {
             frame_events: Vec::new(),
             latest_resize_event: true,
             should_close: false,
+            lifecycle_events: Vec::new(),
+            storage: storage::storage_from_config(&backend_config),
             backend_config,
             window_builder,
             pointer_touch_id: None,
-        }
+            clipboard,
+            cursor_icon: egui::CursorIcon::Default,
+            ime_allowed: false,
+            ime_composing: false,
+            cursor_grab: CursorGrab::None,
+            cursor_visible: true,
+            relative_mouse_mode: false,
+            mouse_motion: [0.0; 2],
+        })
     }
 
     fn take_raw_input(&mut self) -> egui::RawInput {
@@ -151,6 +242,80 @@ impl WindowBackend for WinitBackend {
         self.window.as_mut()
     }
 
+    /// pumps the winit event loop until all the pending events are handled.
+    /// not available on web, ios and android (as the window is created/destroyed on resume/suspend).
+    #[cfg(not(any(target_arch = "wasm32", target_os = "ios", target_os = "android")))]
+    fn poll_events(&mut self) -> Result<(), BackendError> {
+        use winit::platform::run_return::EventLoopExtRunReturn;
+        let mut event_loop = self
+            .event_loop
+            .take()
+            .ok_or(BackendError::Unsupported("poll_events"))?;
+        event_loop.run_return(|event, _, control_flow| {
+            control_flow.set_poll();
+            match event {
+                event::Event::Resumed => {
+                    if self.window.is_some() {
+                        self.update_window_state();
+                        self.latest_resize_event = true;
+                    }
+                }
+                // all pending events are handled. we will draw in `run_frame`
+                event::Event::MainEventsCleared => control_flow.set_exit(),
+                event::Event::RedrawRequested(_) => {}
+                rest => self.handle_event(rest),
+            }
+        });
+        self.event_loop = Some(event_loop);
+        Ok(())
+    }
+
+    fn run_frame<U: UserApp<UserWindowBackend = Self>>(
+        user_app: &mut U,
+    ) -> Option<(egui::PlatformOutput, std::time::Duration)> {
+        let events = std::mem::take(&mut user_app.get_all().0.lifecycle_events);
+        if user_app.handle_lifecycle_events(events) {
+            user_app.get_all().0.should_close = true;
+        }
+        // take egui input
+        if user_app.get_all().0.latest_resize_event {
+            user_app.resize_framebuffer();
+            user_app.get_all().0.latest_resize_event = false;
+        }
+        // begin egui with input
+        let window_backend = user_app.get_all().0;
+        let logical_size = [
+            window_backend.framebuffer_size[0] as f32 / window_backend.scale,
+            window_backend.framebuffer_size[1] as f32 / window_backend.scale,
+        ];
+        // run userapp gui function. let user do anything he wants with window or gfx backends
+        let output = user_app.run(logical_size);
+        if let Some((platform_output, _)) = output.as_ref() {
+            user_app.get_all().0.handle_platform_output(platform_output);
+        }
+        user_app.get_all().0.mouse_motion = [0.0; 2];
+        output
+    }
+
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn set_should_close(&mut self, should_close: bool) {
+        self.should_close = should_close;
+    }
+
+    fn get_storage(&mut self) -> Option<&mut dyn storage::Storage> {
+        // shorten the `'static` lifetime of the boxed trait object
+        self.storage
+            .as_deref_mut()
+            .map(|s| s as &mut dyn storage::Storage)
+    }
+
+    fn get_clipboard(&mut self) -> Option<&mut dyn clipboard::ClipboardProvider> {
+        Some(self.clipboard.as_mut())
+    }
+
     fn get_live_physical_size_framebuffer(&mut self) -> Option<[u32; 2]> {
         if let Some(window) = self.window.as_ref() {
             let size = window.inner_size();
@@ -169,7 +334,10 @@ impl WindowBackend for WinitBackend {
             .expect("event loop missing");
 
         let mut suspended = true;
-        let mut events_wait_duration = std::time::Duration::ZERO;
+        let mut frame_scheduler =
+            egui_backend::util::FrameScheduler::new(user_app.get_all().0.get_config());
+        // on android, there's no window yet. so, only egui memory is restored.
+        storage::load_state(&mut user_app);
         el.run(move |event, _event_loop, control_flow| {
             match event {
                 event::Event::Suspended => {
@@ -197,36 +365,8 @@ impl WindowBackend for WinitBackend {
                         );
                         user_app.resume(window_backend);
                     }
-                    let framebuffer_size_physical = user_app
-                        .get_all()
-                        .0
-                        .window
-                        .as_ref()
-                        .expect("failed to get size of window after resume event")
-                        .inner_size();
-
-                    user_app.get_all().0.framebuffer_size = [
-                        framebuffer_size_physical.width,
-                        framebuffer_size_physical.height,
-                    ];
+                    user_app.get_all().0.update_window_state();
                     user_app.resize_framebuffer();
-                    user_app.get_all().0.scale = user_app
-                        .get_all()
-                        .0
-                        .window
-                        .as_ref()
-                        .expect("failed to get scale of window after resume event")
-                        .scale_factor() as f32;
-                    let window_size = framebuffer_size_physical
-                        .to_logical::<f32>(user_app.get_all().0.scale as f64);
-                    user_app.get_all().0.raw_input = RawInput {
-                        screen_rect: Some(Rect::from_two_pos(
-                            [0.0, 0.0].into(),
-                            [window_size.width, window_size.height].into(),
-                        )),
-                        pixels_per_point: Some(user_app.get_all().0.scale),
-                        ..Default::default()
-                    };
                 }
                 event::Event::MainEventsCleared => {
                     // no point in redrawing if we are suspended.
@@ -239,31 +379,25 @@ impl WindowBackend for WinitBackend {
                 // assume single window, so no need to check window id.
                 event::Event::RedrawRequested(_) => {
                     if !suspended {
-                        // take egui input
-                        if user_app.get_all().0.latest_resize_event {
-                            user_app.resize_framebuffer();
-                            user_app.get_all().0.latest_resize_event = false;
-                        }
-                        // begin egui with input
-                        let logical_size = [
-                            user_app.get_all().0.framebuffer_size[0] as f32
-                                / user_app.get_all().0.scale,
-                            user_app.get_all().0.framebuffer_size[1] as f32
-                                / user_app.get_all().0.scale,
-                        ];
-                        // run userapp gui function. let user do anything he wants with window or gfx backends
-                        if let Some((_platform_output, timeout)) = user_app.run(logical_size) {
-                            events_wait_duration = timeout;
-                        }
+                        frame_scheduler.start_frame();
+                        let output = Self::run_frame(&mut user_app);
+                        frame_scheduler.end_frame(output.map(|(_, repaint_after)| repaint_after));
                     }
                 }
+                event::Event::LoopDestroyed => {
+                    storage::save_state(&mut user_app);
+                    user_app.on_exit();
+                }
                 rest => user_app.get_all().0.handle_event(rest),
             }
             if user_app.get_all().0.should_close {
                 *control_flow = ControlFlow::Exit;
             } else {
-                control_flow.set_wait_timeout(events_wait_duration);
-                events_wait_duration = std::time::Duration::ZERO;
+                match frame_scheduler.wait {
+                    Some(wait) if wait.is_zero() => control_flow.set_poll(),
+                    Some(wait) => control_flow.set_wait_timeout(wait),
+                    None => control_flow.set_wait(),
+                }
             }
         })
     }
@@ -272,12 +406,49 @@ impl WindowBackend for WinitBackend {
         &self.backend_config
     }
 
-    fn swap_buffers(&mut self) {
-        unimplemented!("winit backend doesn't support swapping buffers")
-    }
-
-    fn get_proc_address(&mut self, _: &str) -> *const core::ffi::c_void {
-        unimplemented!("winit backend doesn't support loading opengl function pointers")
+    fn capabilities(&self) -> WindowCapabilities {
+        // wayland, android and web don't let us get/set the position of the window.
+        let position = self
+            .window
+            .as_ref()
+            .map(|w| w.inner_position().is_ok())
+            .unwrap_or_default();
+        let desktop = cfg!(not(any(
+            target_arch = "wasm32",
+            target_os = "android",
+            target_os = "ios"
+        )));
+        // winit doesn't have getters for window level or hittest. swap buffers / get proc address are for gl windows.
+        WindowCapabilities {
+            get_window_position: position,
+            set_window_position: position,
+            get_window_size: true,
+            set_window_size: true,
+            // is_minimized might still return None on some platforms (eg: wayland)
+            get_window_minimized: desktop,
+            set_minimize_window: desktop,
+            get_window_maximized: desktop,
+            set_maximize_window: desktop,
+            get_window_visibility: desktop,
+            set_window_visibility: desktop,
+            set_always_on_top: desktop,
+            set_passthrough: desktop,
+            get_decorations: desktop,
+            set_decorations: desktop,
+            get_resizable: desktop,
+            set_resizable: desktop,
+            begin_drag_move: desktop,
+            begin_drag_resize: desktop,
+            monitors: true,
+            get_fullscreen: true,
+            // web only has borderless fullscreen. android and ios are always fullscreen.
+            set_fullscreen: desktop || cfg!(target_arch = "wasm32"),
+            set_cursor_grab: desktop || cfg!(target_arch = "wasm32"),
+            set_cursor_visible: true,
+            relative_mouse_mode: desktop || cfg!(target_arch = "wasm32"),
+            window_surface: true,
+            ..Default::default()
+        }
     }
 
     fn is_opengl(&self) -> bool {
@@ -291,16 +462,24 @@ impl WindowBackend for WinitBackend {
     }
 
     fn get_window_position(&mut self) -> Option<[f32; 2]> {
-        self.window.as_mut().map(|w| {
+        self.window.as_mut().and_then(|w| {
             w.inner_position()
-                .unwrap()
-                .to_logical::<f32>(w.scale_factor())
-                .into()
+                .ok()
+                .map(|pos| pos.to_logical::<f32>(w.scale_factor()).into())
         })
     }
 
-    fn set_window_position(&mut self, _pos: [f32; 2]) {
-        unimplemented!()
+    fn set_window_position(&mut self, pos: [f32; 2]) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_mut()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        // inner position fails on platforms where window position is not supported
+        if w.inner_position().is_err() {
+            return Err(BackendError::Unsupported("set_window_position"));
+        }
+        w.set_outer_position(winit::dpi::LogicalPosition::new(pos[0], pos[1]));
+        Ok(())
     }
 
     fn get_window_size(&mut self) -> Option<[f32; 2]> {
@@ -346,31 +525,280 @@ impl WindowBackend for WinitBackend {
     }
 
     fn get_always_on_top(&mut self) -> Option<bool> {
-        unimplemented!()
+        // winit doesn't let us query the window level
+        None
     }
 
-    fn set_always_on_top(&mut self, always_on_top: bool) {
-        if let Some(w) = self.window.as_mut() {
-            w.set_window_level(if always_on_top {
-                window::WindowLevel::AlwaysOnTop
-            } else {
-                window::WindowLevel::Normal
-            })
-        };
+    fn set_always_on_top(&mut self, always_on_top: bool) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_mut()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        w.set_window_level(if always_on_top {
+            window::WindowLevel::AlwaysOnTop
+        } else {
+            window::WindowLevel::Normal
+        });
+        Ok(())
     }
 
     fn get_passthrough(&mut self) -> Option<bool> {
-        unimplemented!()
+        // winit doesn't let us query the cursor hittest
+        None
+    }
+
+    fn set_passthrough(&mut self, passthrough: bool) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_mut()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        // passthrough means that the window should *not* be hit by the cursor
+        w.set_cursor_hittest(!passthrough)
+            .map_err(|e| winit_external_error(e, "set_passthrough"))
+    }
+
+    fn get_decorations(&mut self) -> Option<bool> {
+        self.window.as_mut().map(|w| w.is_decorated())
+    }
+
+    fn set_decorations(&mut self, decorations: bool) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_mut()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        w.set_decorations(decorations);
+        Ok(())
+    }
+
+    fn get_resizable(&mut self) -> Option<bool> {
+        self.window.as_mut().map(|w| w.is_resizable())
+    }
+
+    fn set_resizable(&mut self, resizable: bool) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_mut()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        w.set_resizable(resizable);
+        Ok(())
+    }
+
+    fn begin_drag_move(&mut self) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_mut()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        // the OS takes over the mouse until the button is released
+        w.drag_window()
+            .map_err(|e| winit_external_error(e, "begin_drag_move"))
+    }
+
+    fn begin_drag_resize(&mut self, edge: ResizeEdge) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_mut()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        use window::ResizeDirection as RD;
+        let direction = match edge {
+            ResizeEdge::North => RD::North,
+            ResizeEdge::South => RD::South,
+            ResizeEdge::East => RD::East,
+            ResizeEdge::West => RD::West,
+            ResizeEdge::NorthEast => RD::NorthEast,
+            ResizeEdge::NorthWest => RD::NorthWest,
+            ResizeEdge::SouthEast => RD::SouthEast,
+            ResizeEdge::SouthWest => RD::SouthWest,
+        };
+        w.drag_resize_window(direction)
+            .map_err(|e| winit_external_error(e, "begin_drag_resize"))
     }
 
-    fn set_passthrough(&mut self, passthrough: bool) {
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
         self.window
+            .as_ref()
+            .map(|w| w.available_monitors().map(winit_monitor_info).collect())
+            .unwrap_or_default()
+    }
+
+    fn current_monitor(&mut self) -> Option<usize> {
+        let w = self.window.as_ref()?;
+        let current = w.current_monitor()?;
+        w.available_monitors().position(|m| m == current)
+    }
+
+    fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<(), BackendError> {
+        self.cursor_grab = grab;
+        // relative mouse mode keeps its own grab until it is disabled
+        if self.relative_mouse_mode {
+            return Ok(());
+        }
+        let w = self
+            .window
+            .as_ref()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        w.set_cursor_grab(match grab {
+            CursorGrab::None => window::CursorGrabMode::None,
+            CursorGrab::Confined => window::CursorGrabMode::Confined,
+            CursorGrab::Locked => window::CursorGrabMode::Locked,
+        })
+        .map_err(|e| winit_external_error(e, "set_cursor_grab"))
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<(), BackendError> {
+        self.cursor_visible = visible;
+        let w = self
+            .window
+            .as_ref()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        w.set_cursor_visible(visible && !self.relative_mouse_mode);
+        Ok(())
+    }
+
+    fn get_relative_mouse_mode(&mut self) -> Option<bool> {
+        Some(self.relative_mouse_mode)
+    }
+
+    fn mouse_motion(&mut self) -> Option<[f32; 2]> {
+        Some(self.mouse_motion)
+    }
+
+    fn set_relative_mouse_mode(&mut self, relative: bool) -> Result<(), BackendError> {
+        let w = self
+            .window
+            .as_ref()
+            .ok_or(BackendError::WindowNotAvailable)?;
+        if relative {
+            // windows and x11 can't lock the cursor, so we confine it instead. the motion events still work.
+            w.set_cursor_grab(window::CursorGrabMode::Locked)
+                .or_else(|_| w.set_cursor_grab(window::CursorGrabMode::Confined))
+                .map_err(|e| winit_external_error(e, "set_relative_mouse_mode"))?;
+        }
+        self.relative_mouse_mode = relative;
+        w.set_cursor_visible(self.cursor_visible && !relative);
+        if !relative {
+            // restore the user's grab mode
+            self.set_cursor_grab(self.cursor_grab)?;
+        }
+        Ok(())
+    }
+
+    fn get_fullscreen(&mut self) -> Option<FullscreenMode> {
+        let w = self.window.as_ref()?;
+        Some(match w.fullscreen() {
+            None => FullscreenMode::Windowed,
+            Some(window::Fullscreen::Borderless(monitor)) => FullscreenMode::Borderless {
+                monitor: monitor.and_then(|m| w.available_monitors().position(|am| am == m)),
+            },
+            Some(window::Fullscreen::Exclusive(video_mode)) => FullscreenMode::Exclusive {
+                monitor: w
+                    .available_monitors()
+                    .position(|am| am == video_mode.monitor()),
+                video_mode: winit_to_video_mode(&video_mode),
+            },
+        })
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), BackendError> {
+        let w = self
+            .window
             .as_mut()
-            .map(|w| w.set_cursor_hittest(passthrough));
+            .ok_or(BackendError::WindowNotAvailable)?;
+        let monitor_by_index = |index: Option<usize>| match index {
+            Some(index) => w
+                .available_monitors()
+                .nth(index)
+                .map(Some)
+                .ok_or_else(|| BackendError::Other(format!("monitor {index} doesn't exist"))),
+            None => Ok(w.current_monitor()),
+        };
+        // winit remembers the windowed position and size by itself
+        let fullscreen = match mode {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless { monitor } => {
+                Some(window::Fullscreen::Borderless(monitor_by_index(monitor)?))
+            }
+            FullscreenMode::Exclusive {
+                monitor,
+                video_mode,
+            } => {
+                let monitor = monitor_by_index(monitor)?.ok_or_else(|| {
+                    BackendError::Other("window has no current monitor".to_string())
+                })?;
+                let winit_video_mode = monitor
+                    .video_modes()
+                    .find(|vm| winit_to_video_mode(vm) == video_mode)
+                    .ok_or_else(|| {
+                        BackendError::Other(format!(
+                            "monitor doesn't support video mode {video_mode:?}"
+                        ))
+                    })?;
+                Some(window::Fullscreen::Exclusive(winit_video_mode))
+            }
+        };
+        w.set_fullscreen(fullscreen);
+        Ok(())
+    }
+
+    fn handle_platform_output(&mut self, platform_output: &egui::PlatformOutput) {
+        if !platform_output.copied_text.is_empty() {
+            self.clipboard.set_text(&platform_output.copied_text);
+        }
+        if let Some(open_url) = platform_output.open_url.as_ref() {
+            // webbrowser decides whether to open a new tab or not on native. On web, it always opens a new tab
+            if let Err(e) = webbrowser::open(&open_url.url) {
+                tracing::error!("failed to open url {}: {e}", open_url.url);
+            }
+        }
+        let Some(window) = self.window.as_ref() else {
+            return;
+        };
+        if platform_output.cursor_icon != self.cursor_icon {
+            self.cursor_icon = platform_output.cursor_icon;
+            if let Some(cursor_icon) = egui_to_winit_cursor(platform_output.cursor_icon) {
+                // user might want the cursor to stay hidden
+                window.set_cursor_visible(self.cursor_visible && !self.relative_mouse_mode);
+                window.set_cursor_icon(cursor_icon);
+            } else {
+                window.set_cursor_visible(false);
+            }
+        }
+        // only enable IME while a text field has focus. otherwise, IME might eat keys like space
+        if platform_output.mutable_text_under_cursor != self.ime_allowed {
+            self.ime_allowed = platform_output.mutable_text_under_cursor;
+            window.set_ime_allowed(self.ime_allowed);
+        }
+        if self.ime_allowed {
+            if let Some(pos) = platform_output.text_cursor_pos {
+                // so that the IME candidate popup shows up near the text cursor
+                window.set_ime_position(winit::dpi::LogicalPosition::new(pos.x, pos.y));
+            }
+        }
     }
 }
 
 impl WinitBackend {
+    /// updates size, scale and input after the window is (re)created on resume event.
+    fn update_window_state(&mut self) {
+        let window = self
+            .window
+            .as_ref()
+            .expect("failed to get window after resume event");
+        let framebuffer_size_physical = window.inner_size();
+        self.framebuffer_size = [
+            framebuffer_size_physical.width,
+            framebuffer_size_physical.height,
+        ];
+        self.scale = window.scale_factor() as f32;
+        let window_size = framebuffer_size_physical.to_logical::<f32>(self.scale as f64);
+        self.raw_input = RawInput {
+            screen_rect: Some(Rect::from_two_pos(
+                [0.0, 0.0].into(),
+                [window_size.width, window_size.height].into(),
+            )),
+            pixels_per_point: Some(self.scale),
+            ..Default::default()
+        };
+    }
     fn handle_event(&mut self, event: winit::event::Event<()>) {
         if let Some(egui_event) = match event {
             event::Event::WindowEvent { event, .. } => match event {
@@ -385,11 +813,16 @@ impl WinitBackend {
                     None
                 }
                 event::WindowEvent::CloseRequested => {
-                    self.should_close = true;
+                    self.lifecycle_events.push(LifecycleEvent::CloseRequested);
+                    None
+                }
+                event::WindowEvent::Focused(focused) => {
+                    self.lifecycle_events
+                        .push(LifecycleEvent::FocusChanged(focused));
                     None
                 }
                 event::WindowEvent::DroppedFile(df) => {
-                    self.raw_input.dropped_files.push(DroppedFile {
+                    let file = DroppedFile {
                         path: Some(df.clone()),
                         name: df
                             .file_name()
@@ -399,11 +832,40 @@ impl WinitBackend {
                             .to_string(),
                         last_modified: None,
                         bytes: None,
-                    });
+                    };
+                    self.raw_input.dropped_files.push(file.clone());
+                    self.lifecycle_events
+                        .push(LifecycleEvent::FilesDropped(vec![file]));
                     None
                 }
 
-                event::WindowEvent::ReceivedCharacter(c) => Some(Event::Text(c.to_string())),
+                event::WindowEvent::ReceivedCharacter(c) => {
+                    keyboard::text_event(c.encode_utf8(&mut [0; 4]), self.modifiers)
+                }
+                event::WindowEvent::Ime(ime) => match ime {
+                    event::Ime::Enabled => None,
+                    // winit sends an empty preedit after a commit or when the composition is cancelled
+                    event::Ime::Preedit(text, _) if text.is_empty() && !self.ime_composing => None,
+                    event::Ime::Preedit(text, _) => {
+                        if !self.ime_composing {
+                            self.ime_composing = true;
+                            self.raw_input.events.push(Event::CompositionStart);
+                        }
+                        Some(Event::CompositionUpdate(text))
+                    }
+                    // egui ignores the composition end if it didn't see a start. eg: IME committed without a preedit.
+                    event::Ime::Commit(text) if !self.ime_composing => Some(Event::Text(text)),
+                    event::Ime::Commit(text) => {
+                        self.ime_composing = false;
+                        Some(Event::CompositionEnd(text))
+                    }
+                    // text field lost focus in the middle of a composition
+                    event::Ime::Disabled if self.ime_composing => {
+                        self.ime_composing = false;
+                        Some(Event::CompositionEnd(String::new()))
+                    }
+                    event::Ime::Disabled => None,
+                },
 
                 event::WindowEvent::KeyboardInput { input, .. } => {
                     let pressed = match input.state {
@@ -411,25 +873,28 @@ impl WinitBackend {
                         event::ElementState::Released => false,
                     };
 
-                    if let Some(key_code) = input.virtual_keycode {
-                        if let Some(egui_key) = winit_key_to_egui(key_code) {
-                            Some(Event::Key {
-                                key: egui_key,
-                                pressed,
-                                modifiers: self.modifiers,
-                                repeat: false,
-                            })
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
+                    if let Some(key) = input.virtual_keycode.and_then(winit_key_to_egui) {
+                        let input = keyboard::KeyInput {
+                            key,
+                            pressed,
+                            repeat: false,
+                            modifiers: self.modifiers,
+                        };
+                        let clipboard = &mut self.clipboard;
+                        self.backend_config.shortcuts.push_key_events(
+                            input,
+                            &mut self.raw_input.events,
+                            || clipboard.get_text(),
+                        );
                     }
+                    None
                 }
                 event::WindowEvent::ModifiersChanged(modifiers) => {
                     self.modifiers = winit_modifiers_to_egui(modifiers);
                     None
                 }
+                // we send the raw mouse motion from device events instead
+                event::WindowEvent::CursorMoved { .. } if self.relative_mouse_mode => None,
                 event::WindowEvent::CursorMoved { position, .. } => {
                     let logical = position.to_logical::<f32>(self.scale as f64);
                     self.cursor_pos_logical = [logical.x, logical.y];
@@ -532,6 +997,15 @@ impl WinitBackend {
                 }
                 _ => None,
             },
+            event::Event::DeviceEvent {
+                event: event::DeviceEvent::MouseMotion { delta },
+                ..
+            } if self.relative_mouse_mode => {
+                // raw device counts. close enough to physical pixels without mouse acceleration.
+                self.mouse_motion[0] += delta.0 as f32 / self.scale;
+                self.mouse_motion[1] += delta.1 as f32 / self.scale;
+                None
+            }
             _ => None,
         } {
             self.raw_input.events.push(egui_event);
@@ -539,15 +1013,127 @@ impl WinitBackend {
     }
 }
 
-fn winit_modifiers_to_egui(modifiers: ModifiersState) -> Modifiers {
-    Modifiers {
-        alt: modifiers.alt(),
-        ctrl: modifiers.ctrl(),
-        shift: modifiers.shift(),
-        // i have no idea what a mac_cmd key is
-        mac_cmd: false,
-        command: modifiers.logo(),
+/// converts egui's cursor icon to winit's cursor icon. returns None for [`egui::CursorIcon::None`], which means that
+/// the cursor should be hidden.
+pub fn egui_to_winit_cursor(cursor: egui::CursorIcon) -> Option<window::CursorIcon> {
+    use window::CursorIcon as WC;
+    Some(match cursor {
+        egui::CursorIcon::None => return None,
+        egui::CursorIcon::Default => WC::Default,
+        egui::CursorIcon::ContextMenu => WC::ContextMenu,
+        egui::CursorIcon::Help => WC::Help,
+        egui::CursorIcon::PointingHand => WC::Hand,
+        egui::CursorIcon::Progress => WC::Progress,
+        egui::CursorIcon::Wait => WC::Wait,
+        egui::CursorIcon::Cell => WC::Cell,
+        egui::CursorIcon::Crosshair => WC::Crosshair,
+        egui::CursorIcon::Text => WC::Text,
+        egui::CursorIcon::VerticalText => WC::VerticalText,
+        egui::CursorIcon::Alias => WC::Alias,
+        egui::CursorIcon::Copy => WC::Copy,
+        egui::CursorIcon::Move => WC::Move,
+        egui::CursorIcon::NoDrop => WC::NoDrop,
+        egui::CursorIcon::NotAllowed => WC::NotAllowed,
+        egui::CursorIcon::Grab => WC::Grab,
+        egui::CursorIcon::Grabbing => WC::Grabbing,
+        egui::CursorIcon::AllScroll => WC::AllScroll,
+        egui::CursorIcon::ResizeHorizontal => WC::EwResize,
+        egui::CursorIcon::ResizeNeSw => WC::NeswResize,
+        egui::CursorIcon::ResizeNwSe => WC::NwseResize,
+        egui::CursorIcon::ResizeVertical => WC::NsResize,
+        egui::CursorIcon::ResizeEast => WC::EResize,
+        egui::CursorIcon::ResizeSouthEast => WC::SeResize,
+        egui::CursorIcon::ResizeSouth => WC::SResize,
+        egui::CursorIcon::ResizeSouthWest => WC::SwResize,
+        egui::CursorIcon::ResizeWest => WC::WResize,
+        egui::CursorIcon::ResizeNorthWest => WC::NwResize,
+        egui::CursorIcon::ResizeNorth => WC::NResize,
+        egui::CursorIcon::ResizeNorthEast => WC::NeResize,
+        egui::CursorIcon::ResizeColumn => WC::ColResize,
+        egui::CursorIcon::ResizeRow => WC::RowResize,
+        egui::CursorIcon::ZoomIn => WC::ZoomIn,
+        egui::CursorIcon::ZoomOut => WC::ZoomOut,
+    })
+}
+
+pub fn winit_to_video_mode(video_mode: &monitor::VideoMode) -> VideoMode {
+    VideoMode {
+        size: video_mode.size().into(),
+        bit_depth: video_mode.bit_depth(),
+        refresh_rate: Some(video_mode.refresh_rate_millihertz() as f32 / 1000.0),
+    }
+}
+pub fn winit_monitor_info(monitor: monitor::MonitorHandle) -> MonitorInfo {
+    let scale = monitor.scale_factor();
+    MonitorInfo {
+        name: monitor.name().unwrap_or_default(),
+        // logical, just like the window position
+        position: monitor.position().to_logical::<f32>(scale).into(),
+        size: monitor.size().into(),
+        scale: scale as f32,
+        refresh_rate: monitor
+            .refresh_rate_millihertz()
+            .map(|mhz| mhz as f32 / 1000.0),
+        video_modes: monitor
+            .video_modes()
+            .map(|vm| winit_to_video_mode(&vm))
+            .collect(),
+    }
+}
+/// `op` is the name of the [`WindowBackend`] fn which is not supported on this platform.
+fn winit_external_error(error: winit::error::ExternalError, op: &'static str) -> BackendError {
+    match error {
+        winit::error::ExternalError::NotSupported(_) => BackendError::Unsupported(op),
+        rest => BackendError::Other(rest.to_string()),
+    }
+}
+/// [`ArboardClipboard`] or [`clipboard::MemoryClipboard`] if arboard is not supported on this platform or fails.
+fn system_clipboard() -> Box<dyn clipboard::ClipboardProvider> {
+    #[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+    let clipboard: Box<dyn clipboard::ClipboardProvider> = match arboard::Clipboard::new() {
+        Ok(clipboard) => Box::new(ArboardClipboard(clipboard)),
+        Err(e) => {
+            tracing::error!("failed to create clipboard: {e}. using in-memory clipboard instead");
+            Box::<clipboard::MemoryClipboard>::default()
+        }
+    };
+    #[cfg(any(target_arch = "wasm32", target_os = "android"))]
+    let clipboard: Box<dyn clipboard::ClipboardProvider> =
+        Box::<clipboard::MemoryClipboard>::default();
+    clipboard
+}
+
+/// the system clipboard through arboard.
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+pub struct ArboardClipboard(pub arboard::Clipboard);
+
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+impl clipboard::ClipboardProvider for ArboardClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        match self.0.get_text() {
+            Ok(text) => Some(text),
+            // not an error. the clipboard is just empty or has an image etc..
+            Err(arboard::Error::ContentNotAvailable) => None,
+            Err(e) => {
+                tracing::error!("failed to get clipboard text: {e}");
+                None
+            }
+        }
     }
+
+    fn set_text(&mut self, text: &str) {
+        if let Err(e) = self.0.set_text(text) {
+            tracing::error!("failed to set clipboard text: {e}");
+        }
+    }
+}
+fn winit_modifiers_to_egui(modifiers: ModifiersState) -> Modifiers {
+    keyboard::modifiers(
+        modifiers.alt(),
+        modifiers.ctrl(),
+        modifiers.shift(),
+        modifiers.logo(),
+    )
 }
 fn winit_mouse_button_to_egui(mb: winit::event::MouseButton) -> egui::PointerButton {
     match mb {
@@ -567,7 +1153,7 @@ fn winit_key_to_egui(key_code: VirtualKeyCode) -> Option<Key> {
         VirtualKeyCode::Escape => Key::Escape,
         VirtualKeyCode::Tab => Key::Tab,
         VirtualKeyCode::Back => Key::Backspace,
-        VirtualKeyCode::Return => Key::Enter,
+        VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => Key::Enter,
         VirtualKeyCode::Space => Key::Space,
 
         VirtualKeyCode::Insert => Key::Insert,
@@ -576,6 +1162,10 @@ fn winit_key_to_egui(key_code: VirtualKeyCode) -> Option<Key> {
         VirtualKeyCode::End => Key::End,
         VirtualKeyCode::PageUp => Key::PageUp,
         VirtualKeyCode::PageDown => Key::PageDown,
+        VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => Key::Minus,
+        VirtualKeyCode::Equals | VirtualKeyCode::Plus | VirtualKeyCode::NumpadAdd => {
+            Key::PlusEquals
+        }
 
         VirtualKeyCode::Key0 | VirtualKeyCode::Numpad0 => Key::Num0,
         VirtualKeyCode::Key1 | VirtualKeyCode::Numpad1 => Key::Num1,
@@ -244,6 +244,7 @@ pub fn fake_main() {
             is_opengl: false,
             opengl_config: Default::default(),
             transparent: true.into(),
+            ..Default::default()
         },
     );
 
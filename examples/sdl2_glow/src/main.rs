@@ -71,17 +71,17 @@ pub fn fake_main() {
         .with(tracing_subscriber::EnvFilter::from_default_env())
         .init();
     let config = egui_window_sdl2::SDL2Config {
-        window_creator_callback: Box::new(|video_subsystem| {
+        window_creator_callback: Box::new(|video_subsystem, backend_config| {
             // renderdoc only works with opengl 3.3+
             video_subsystem.gl_attr().set_context_major_version(3);
             video_subsystem.gl_attr().set_context_minor_version(3);
-            let mut window_builder = video_subsystem.window("sdl2 creator", 800, 600);
+            let mut window_builder =
+                egui_window_sdl2::window_builder_from_config(video_subsystem, backend_config);
             window_builder.opengl();
-            window_builder.allow_highdpi();
-            window_builder.resizable();
 
-            window_builder.build().expect("failed to create a window")
+            window_builder.build().map_err(|e| e.to_string())
         }),
+        ..Default::default()
     };
     let mut window_backend = Sdl2Backend::new(config, BackendConfig::default());
     let glow_backend = GlowBackend::new(&mut window_backend, Default::default());
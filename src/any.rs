@@ -0,0 +1,753 @@
+//! enum-dispatch backends, so that the window and gfx backends can be chosen at runtime.
+//!
+//! [`AnyWindowBackend`] and [`AnyGfxBackend`] try each backend of their preference list in order, until one of them succeeds.
+//! eg: if wgpu fails to find an adapter (no vulkan/metal/dx12 drivers), we fall back to glow.
+//!
+//! The preference list can be overridden with comma separated names in env variables:
+//! * `ETK_WINDOW_BACKEND`: `winit`, `glfw`, `sdl2`, `headless`
+//! * `ETK_GFX_BACKEND`: `wgpu`, `glow`, `soft`
+//!
+//! ```rust,no_run
+//! use etk::any::{AnyGfxBackend, AnyWindowBackend};
+//! etk::run::<AnyWindowBackend, AnyGfxBackend>(Default::default(), |ctx| {
+//!     etk::egui::Window::new("hello").show(ctx, |ui| {
+//!         ui.label("world");
+//!     });
+//! })
+//! .expect("no backend works on this machine");
+//! ```
+//!
+//! glow needs an opengl window. winit windows don't have an opengl context, so use glfw or sdl2 (with the default `BackendConfig`) for the glow fallback.
+//! Multiple windows are not supported by these backends.
+
+use egui_backend::egui::{self, ClippedPrimitive, PlatformOutput, RawInput, TexturesDelta};
+use egui_backend::raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+use egui_backend::*;
+#[cfg(feature = "glow")]
+use egui_render_glow::{GlowBackend, GlowConfig};
+use egui_render_soft::{SoftBackend, SoftConfig};
+use egui_render_wgpu::{WgpuBackend, WgpuConfig};
+#[cfg(feature = "passthrough")]
+use egui_window_glfw_passthrough::{GlfwBackend, GlfwConfig};
+use egui_window_headless::{HeadlessBackend, HeadlessConfig};
+#[cfg(feature = "sdl2")]
+use egui_window_sdl2::{SDL2Config, Sdl2Backend};
+use egui_window_winit::{WinitBackend, WinitConfig};
+use std::marker::PhantomData;
+use std::time::Duration;
+
+/// env variable to override [`AnyWindowConfig::preference`]
+pub const WINDOW_BACKEND_ENV: &str = "ETK_WINDOW_BACKEND";
+/// env variable to override [`AnyGfxConfig::preference`]
+pub const GFX_BACKEND_ENV: &str = "ETK_GFX_BACKEND";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowBackendKind {
+    Winit,
+    /// needs `passthrough` feature
+    Glfw,
+    /// needs `sdl2` feature
+    Sdl2,
+    Headless,
+}
+
+impl WindowBackendKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "winit" => Some(Self::Winit),
+            "glfw" => Some(Self::Glfw),
+            "sdl2" => Some(Self::Sdl2),
+            "headless" => Some(Self::Headless),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GfxBackendKind {
+    Wgpu,
+    /// needs `glow` feature
+    Glow,
+    Soft,
+}
+
+impl GfxBackendKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "wgpu" => Some(Self::Wgpu),
+            "glow" => Some(Self::Glow),
+            "soft" => Some(Self::Soft),
+            _ => None,
+        }
+    }
+}
+
+/// parses the comma separated list in the env variable. unknown names are skipped.
+/// returns `None` if the variable is not set or doesn't contain any known names.
+fn preference_from_env<T>(var: &str, from_name: fn(&str) -> Option<T>) -> Option<Vec<T>> {
+    let value = std::env::var(var).ok()?;
+    let kinds: Vec<T> = value
+        .split(',')
+        .filter_map(|name| {
+            let kind = from_name(name);
+            if kind.is_none() {
+                tracing::warn!("unknown backend {name} in {var}");
+            }
+            kind
+        })
+        .collect();
+    (!kinds.is_empty()).then_some(kinds)
+}
+
+/// config for [`AnyWindowBackend`]. has the config of every window backend, but only the chosen one is used.
+pub struct AnyWindowConfig {
+    /// window backends to try in order. overridden by [`WINDOW_BACKEND_ENV`] env variable.
+    pub preference: Vec<WindowBackendKind>,
+    pub winit: WinitConfig,
+    #[cfg(feature = "passthrough")]
+    pub glfw: GlfwConfig,
+    #[cfg(feature = "sdl2")]
+    pub sdl2: SDL2Config,
+    pub headless: HeadlessConfig,
+}
+
+impl Default for AnyWindowConfig {
+    /// glfw, sdl2 (if enabled) and then winit. glfw and sdl2 come first as they support opengl, which allows glow fallback.
+    fn default() -> Self {
+        let mut preference = vec![];
+        if cfg!(feature = "passthrough") {
+            preference.push(WindowBackendKind::Glfw);
+        }
+        if cfg!(feature = "sdl2") {
+            preference.push(WindowBackendKind::Sdl2);
+        }
+        preference.push(WindowBackendKind::Winit);
+        Self {
+            preference,
+            winit: Default::default(),
+            #[cfg(feature = "passthrough")]
+            glfw: Default::default(),
+            #[cfg(feature = "sdl2")]
+            sdl2: Default::default(),
+            headless: Default::default(),
+        }
+    }
+}
+
+/// a window backend chosen at runtime. see the [module docs](self)
+// there's only one of these in an app, so the size difference between variants doesn't matter.
+#[allow(clippy::large_enum_variant)]
+pub enum AnyWindowBackend {
+    Winit(WinitBackend),
+    #[cfg(feature = "passthrough")]
+    Glfw(GlfwBackend),
+    #[cfg(feature = "sdl2")]
+    Sdl2(Sdl2Backend),
+    Headless(HeadlessBackend),
+}
+
+/// calls the same code for whichever backend is inside the enum
+macro_rules! dispatch_window {
+    ($any:expr, $backend:ident => $body:expr) => {
+        match $any {
+            AnyWindowBackend::Winit($backend) => $body,
+            #[cfg(feature = "passthrough")]
+            AnyWindowBackend::Glfw($backend) => $body,
+            #[cfg(feature = "sdl2")]
+            AnyWindowBackend::Sdl2($backend) => $body,
+            AnyWindowBackend::Headless($backend) => $body,
+        }
+    };
+}
+
+impl AnyWindowBackend {
+    pub fn kind(&self) -> WindowBackendKind {
+        match self {
+            AnyWindowBackend::Winit(_) => WindowBackendKind::Winit,
+            #[cfg(feature = "passthrough")]
+            AnyWindowBackend::Glfw(_) => WindowBackendKind::Glfw,
+            #[cfg(feature = "sdl2")]
+            AnyWindowBackend::Sdl2(_) => WindowBackendKind::Sdl2,
+            AnyWindowBackend::Headless(_) => WindowBackendKind::Headless,
+        }
+    }
+}
+
+/// only valid if [`WindowBackend::get_window`] returns `Some`.
+unsafe impl HasRawWindowHandle for AnyWindowBackend {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        match self {
+            AnyWindowBackend::Winit(backend) => backend
+                .window
+                .as_ref()
+                .expect("winit window is not available")
+                .raw_window_handle(),
+            #[cfg(feature = "passthrough")]
+            AnyWindowBackend::Glfw(backend) => backend.window.raw_window_handle(),
+            #[cfg(feature = "sdl2")]
+            AnyWindowBackend::Sdl2(backend) => backend.window.raw_window_handle(),
+            AnyWindowBackend::Headless(backend) => backend.window.raw_window_handle(),
+        }
+    }
+}
+
+unsafe impl HasRawDisplayHandle for AnyWindowBackend {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        match self {
+            AnyWindowBackend::Winit(backend) => backend
+                .window
+                .as_ref()
+                .expect("winit window is not available")
+                .raw_display_handle(),
+            #[cfg(feature = "passthrough")]
+            AnyWindowBackend::Glfw(backend) => backend.window.raw_display_handle(),
+            #[cfg(feature = "sdl2")]
+            AnyWindowBackend::Sdl2(backend) => backend.window.raw_display_handle(),
+            AnyWindowBackend::Headless(backend) => backend.window.raw_display_handle(),
+        }
+    }
+}
+
+impl WindowBackend for AnyWindowBackend {
+    type Configuration = AnyWindowConfig;
+    /// the window handles come from the inner backend's window
+    type WindowType = Self;
+
+    fn try_new(
+        config: Self::Configuration,
+        backend_config: BackendConfig,
+    ) -> Result<Self, BackendError> {
+        let AnyWindowConfig {
+            preference,
+            winit,
+            #[cfg(feature = "passthrough")]
+            glfw,
+            #[cfg(feature = "sdl2")]
+            sdl2,
+            headless,
+        } = config;
+        // configs have callbacks which can only be used once. so, each backend is tried at most once.
+        let mut winit = Some(winit);
+        #[cfg(feature = "passthrough")]
+        let mut glfw = Some(glfw);
+        #[cfg(feature = "sdl2")]
+        let mut sdl2 = Some(sdl2);
+        let mut headless = Some(headless);
+        let preference = preference_from_env(WINDOW_BACKEND_ENV, WindowBackendKind::from_name)
+            .unwrap_or(preference);
+        let mut last_error = BackendError::Other("no window backend to try".to_string());
+        for kind in preference {
+            let result = match kind {
+                WindowBackendKind::Winit => winit
+                    .take()
+                    .map(|c| WinitBackend::try_new(c, backend_config.clone()).map(Self::Winit)),
+                #[cfg(feature = "passthrough")]
+                WindowBackendKind::Glfw => glfw
+                    .take()
+                    .map(|c| GlfwBackend::try_new(c, backend_config.clone()).map(Self::Glfw)),
+                #[cfg(feature = "sdl2")]
+                WindowBackendKind::Sdl2 => sdl2
+                    .take()
+                    .map(|c| Sdl2Backend::try_new(c, backend_config.clone()).map(Self::Sdl2)),
+                WindowBackendKind::Headless => headless.take().map(|c| {
+                    HeadlessBackend::try_new(c, backend_config.clone()).map(Self::Headless)
+                }),
+                #[allow(unreachable_patterns)]
+                _ => {
+                    tracing::warn!("{kind:?} window backend is not enabled. skipping it");
+                    continue;
+                }
+            };
+            match result {
+                Some(Ok(backend)) => {
+                    tracing::info!("using {kind:?} window backend");
+                    return Ok(backend);
+                }
+                Some(Err(e)) => {
+                    tracing::warn!("failed to create {kind:?} window backend: {e}");
+                    last_error = e;
+                }
+                None => {}
+            }
+        }
+        Err(last_error)
+    }
+
+    fn take_raw_input(&mut self) -> RawInput {
+        dispatch_window!(self, backend => backend.take_raw_input())
+    }
+
+    fn get_window(&mut self) -> Option<&mut Self::WindowType> {
+        if dispatch_window!(&mut *self, backend => backend.get_window().is_some()) {
+            Some(self)
+        } else {
+            None
+        }
+    }
+
+    fn get_live_physical_size_framebuffer(&mut self) -> Option<[u32; 2]> {
+        dispatch_window!(self, backend => backend.get_live_physical_size_framebuffer())
+    }
+
+    fn run_event_loop<U: UserApp<UserWindowBackend = Self> + 'static>(mut user_app: U) {
+        match user_app.get_all().0.kind() {
+            WindowBackendKind::Winit => WinitBackend::run_event_loop(AnyApp::new(user_app)),
+            #[cfg(feature = "passthrough")]
+            WindowBackendKind::Glfw => GlfwBackend::run_event_loop(AnyApp::new(user_app)),
+            #[cfg(feature = "sdl2")]
+            WindowBackendKind::Sdl2 => Sdl2Backend::run_event_loop(AnyApp::new(user_app)),
+            WindowBackendKind::Headless => HeadlessBackend::run_event_loop(AnyApp::new(user_app)),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("disabled window backends are never created"),
+        }
+    }
+
+    fn poll_events(&mut self) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.poll_events())
+    }
+
+    fn run_frame<U: UserApp<UserWindowBackend = Self>>(
+        user_app: &mut U,
+    ) -> Option<(PlatformOutput, Duration)> {
+        match user_app.get_all().0.kind() {
+            WindowBackendKind::Winit => WinitBackend::run_frame(&mut AnyApp::new(user_app)),
+            #[cfg(feature = "passthrough")]
+            WindowBackendKind::Glfw => GlfwBackend::run_frame(&mut AnyApp::new(user_app)),
+            #[cfg(feature = "sdl2")]
+            WindowBackendKind::Sdl2 => Sdl2Backend::run_frame(&mut AnyApp::new(user_app)),
+            WindowBackendKind::Headless => HeadlessBackend::run_frame(&mut AnyApp::new(user_app)),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("disabled window backends are never created"),
+        }
+    }
+
+    fn should_close(&self) -> bool {
+        dispatch_window!(self, backend => backend.should_close())
+    }
+
+    fn set_should_close(&mut self, should_close: bool) {
+        dispatch_window!(self, backend => backend.set_should_close(should_close))
+    }
+
+    fn get_storage(&mut self) -> Option<&mut dyn storage::Storage> {
+        dispatch_window!(self, backend => backend.get_storage())
+    }
+
+    fn get_clipboard(&mut self) -> Option<&mut dyn clipboard::ClipboardProvider> {
+        dispatch_window!(self, backend => backend.get_clipboard())
+    }
+
+    fn get_config(&self) -> &BackendConfig {
+        dispatch_window!(self, backend => backend.get_config())
+    }
+
+    fn capabilities(&self) -> WindowCapabilities {
+        WindowCapabilities {
+            multi_window: false,
+            ..dispatch_window!(self, backend => backend.capabilities())
+        }
+    }
+
+    fn swap_buffers(&mut self) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.swap_buffers())
+    }
+
+    fn is_opengl(&self) -> bool {
+        dispatch_window!(self, backend => backend.is_opengl())
+    }
+
+    fn get_proc_address(&mut self, symbol: &str) -> Option<*const core::ffi::c_void> {
+        dispatch_window!(self, backend => backend.get_proc_address(symbol))
+    }
+
+    fn set_window_title(&mut self, title: &str) {
+        dispatch_window!(self, backend => backend.set_window_title(title))
+    }
+
+    fn get_window_position(&mut self) -> Option<[f32; 2]> {
+        dispatch_window!(self, backend => backend.get_window_position())
+    }
+
+    fn set_window_position(&mut self, pos: [f32; 2]) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_window_position(pos))
+    }
+
+    fn get_window_size(&mut self) -> Option<[f32; 2]> {
+        dispatch_window!(self, backend => backend.get_window_size())
+    }
+
+    fn set_window_size(&mut self, size: [f32; 2]) {
+        dispatch_window!(self, backend => backend.set_window_size(size))
+    }
+
+    fn get_window_minimized(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_window_minimized())
+    }
+
+    fn set_minimize_window(&mut self, min: bool) {
+        dispatch_window!(self, backend => backend.set_minimize_window(min))
+    }
+
+    fn get_window_maximized(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_window_maximized())
+    }
+
+    fn set_maximize_window(&mut self, max: bool) {
+        dispatch_window!(self, backend => backend.set_maximize_window(max))
+    }
+
+    fn get_window_visibility(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_window_visibility())
+    }
+
+    fn set_window_visibility(&mut self, vis: bool) {
+        dispatch_window!(self, backend => backend.set_window_visibility(vis))
+    }
+
+    fn get_always_on_top(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_always_on_top())
+    }
+
+    fn set_always_on_top(&mut self, always_on_top: bool) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_always_on_top(always_on_top))
+    }
+
+    fn get_passthrough(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_passthrough())
+    }
+
+    fn set_passthrough(&mut self, passthrough: bool) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_passthrough(passthrough))
+    }
+
+    fn get_decorations(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_decorations())
+    }
+
+    fn set_decorations(&mut self, decorations: bool) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_decorations(decorations))
+    }
+
+    fn get_resizable(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_resizable())
+    }
+
+    fn set_resizable(&mut self, resizable: bool) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_resizable(resizable))
+    }
+
+    fn begin_drag_move(&mut self) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.begin_drag_move())
+    }
+
+    fn begin_drag_resize(&mut self, edge: ResizeEdge) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.begin_drag_resize(edge))
+    }
+
+    fn monitors(&mut self) -> Vec<MonitorInfo> {
+        dispatch_window!(self, backend => backend.monitors())
+    }
+
+    fn current_monitor(&mut self) -> Option<usize> {
+        dispatch_window!(self, backend => backend.current_monitor())
+    }
+
+    fn get_fullscreen(&mut self) -> Option<FullscreenMode> {
+        dispatch_window!(self, backend => backend.get_fullscreen())
+    }
+
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_fullscreen(mode))
+    }
+
+    fn set_cursor_grab(&mut self, grab: CursorGrab) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_cursor_grab(grab))
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_cursor_visible(visible))
+    }
+
+    fn get_relative_mouse_mode(&mut self) -> Option<bool> {
+        dispatch_window!(self, backend => backend.get_relative_mouse_mode())
+    }
+
+    fn set_relative_mouse_mode(&mut self, relative: bool) -> Result<(), BackendError> {
+        dispatch_window!(self, backend => backend.set_relative_mouse_mode(relative))
+    }
+
+    fn mouse_motion(&mut self) -> Option<[f32; 2]> {
+        dispatch_window!(self, backend => backend.mouse_motion())
+    }
+
+    fn gamepads(&mut self) -> Vec<gamepad::GamepadState> {
+        dispatch_window!(self, backend => backend.gamepads())
+    }
+
+    fn handle_platform_output(&mut self, platform_output: &PlatformOutput) {
+        dispatch_window!(self, backend => backend.handle_platform_output(platform_output))
+    }
+}
+
+/// implemented by the window backends which can be inside [`AnyWindowBackend`]. used by [`AnyApp`].
+pub trait AnyWindowVariant: WindowBackend {
+    /// panics if `any` has a different backend.
+    fn from_any(any: &mut AnyWindowBackend) -> &mut Self;
+}
+
+macro_rules! impl_any_window_variant {
+    ($(#[$attr:meta])* $backend:ty, $variant:ident) => {
+        $(#[$attr])*
+        impl AnyWindowVariant for $backend {
+            fn from_any(any: &mut AnyWindowBackend) -> &mut Self {
+                match any {
+                    AnyWindowBackend::$variant(backend) => backend,
+                    #[allow(unreachable_patterns)]
+                    _ => panic!("expected {} window backend", stringify!($variant)),
+                }
+            }
+        }
+    };
+}
+
+impl_any_window_variant!(WinitBackend, Winit);
+impl_any_window_variant!(
+    #[cfg(feature = "passthrough")]
+    GlfwBackend,
+    Glfw
+);
+impl_any_window_variant!(
+    #[cfg(feature = "sdl2")]
+    Sdl2Backend,
+    Sdl2
+);
+impl_any_window_variant!(HeadlessBackend, Headless);
+
+/// Adapter which lets the event loop of the inner window backend `W` drive a `UserApp` of [`AnyWindowBackend`].
+/// [`AnyWindowBackend::run_event_loop`] uses this internally.
+pub struct AnyApp<U, W> {
+    pub app: U,
+    backend: PhantomData<fn() -> W>,
+}
+
+impl<U, W> AnyApp<U, W> {
+    pub fn new(app: U) -> Self {
+        Self {
+            app,
+            backend: PhantomData,
+        }
+    }
+}
+
+impl<U, W> UserApp for AnyApp<U, W>
+where
+    W: AnyWindowVariant,
+    U: UserApp<UserWindowBackend = AnyWindowBackend>,
+{
+    type UserGfxBackend = U::UserGfxBackend;
+    type UserWindowBackend = W;
+
+    fn get_all(
+        &mut self,
+    ) -> (
+        &mut Self::UserWindowBackend,
+        &mut Self::UserGfxBackend,
+        &egui::Context,
+    ) {
+        let (wb, gb, egui_context) = self.app.get_all();
+        (W::from_any(wb), gb, egui_context)
+    }
+
+    fn resize_framebuffer(&mut self) {
+        self.app.resize_framebuffer()
+    }
+
+    fn resume(&mut self) {
+        self.app.resume()
+    }
+
+    fn suspend(&mut self) {
+        self.app.suspend()
+    }
+
+    fn run(&mut self, logical_size: [f32; 2]) -> Option<(PlatformOutput, Duration)> {
+        self.app.run(logical_size)
+    }
+
+    fn gui_run(&mut self) {
+        self.app.gui_run()
+    }
+
+    fn on_close_requested(&mut self) -> bool {
+        self.app.on_close_requested()
+    }
+
+    fn on_focus_changed(&mut self, focused: bool) {
+        self.app.on_focus_changed(focused)
+    }
+
+    fn on_files_dropped(&mut self, files: &[egui::DroppedFile]) {
+        self.app.on_files_dropped(files)
+    }
+
+    fn on_exit(&mut self) {
+        self.app.on_exit()
+    }
+
+    fn handle_lifecycle_events(&mut self, events: Vec<LifecycleEvent>) -> bool {
+        self.app.handle_lifecycle_events(events)
+    }
+}
+
+/// config for [`AnyGfxBackend`]. has the config of every gfx backend, but only the chosen one is used.
+pub struct AnyGfxConfig {
+    /// gfx backends to try in order. overridden by [`GFX_BACKEND_ENV`] env variable.
+    pub preference: Vec<GfxBackendKind>,
+    pub wgpu: WgpuConfig,
+    #[cfg(feature = "glow")]
+    pub glow: GlowConfig,
+    pub soft: SoftConfig,
+}
+
+impl Default for AnyGfxConfig {
+    /// wgpu and then glow (if enabled). soft is not tried by default, as it only shows anything with its `softbuffer` feature.
+    fn default() -> Self {
+        let mut preference = vec![GfxBackendKind::Wgpu];
+        if cfg!(feature = "glow") {
+            preference.push(GfxBackendKind::Glow);
+        }
+        Self {
+            preference,
+            wgpu: Default::default(),
+            #[cfg(feature = "glow")]
+            glow: Default::default(),
+            soft: Default::default(),
+        }
+    }
+}
+
+/// a gfx backend chosen at runtime. see the [module docs](self)
+// there's only one of these in an app, so the size difference between variants doesn't matter.
+#[allow(clippy::large_enum_variant)]
+pub enum AnyGfxBackend {
+    Wgpu(WgpuBackend),
+    #[cfg(feature = "glow")]
+    Glow(GlowBackend),
+    Soft(SoftBackend),
+}
+
+macro_rules! dispatch_gfx {
+    ($any:expr, $backend:ident => $body:expr) => {
+        match $any {
+            AnyGfxBackend::Wgpu($backend) => $body,
+            #[cfg(feature = "glow")]
+            AnyGfxBackend::Glow($backend) => $body,
+            AnyGfxBackend::Soft($backend) => $body,
+        }
+    };
+}
+
+impl AnyGfxBackend {
+    pub fn kind(&self) -> GfxBackendKind {
+        match self {
+            AnyGfxBackend::Wgpu(_) => GfxBackendKind::Wgpu,
+            #[cfg(feature = "glow")]
+            AnyGfxBackend::Glow(_) => GfxBackendKind::Glow,
+            AnyGfxBackend::Soft(_) => GfxBackendKind::Soft,
+        }
+    }
+}
+
+impl GfxBackend for AnyGfxBackend {
+    type Configuration = AnyGfxConfig;
+
+    fn try_new(
+        window_backend: &mut impl WindowBackend,
+        config: Self::Configuration,
+    ) -> Result<Self, BackendError> {
+        let AnyGfxConfig {
+            preference,
+            wgpu,
+            #[cfg(feature = "glow")]
+            glow,
+            soft,
+        } = config;
+        let mut wgpu = Some(wgpu);
+        #[cfg(feature = "glow")]
+        let mut glow = Some(glow);
+        let mut soft = Some(soft);
+        let preference =
+            preference_from_env(GFX_BACKEND_ENV, GfxBackendKind::from_name).unwrap_or(preference);
+        let mut last_error = BackendError::Other("no gfx backend to try".to_string());
+        for kind in preference {
+            let result = match kind {
+                GfxBackendKind::Wgpu => wgpu
+                    .take()
+                    .map(|c| WgpuBackend::try_new(window_backend, c).map(Self::Wgpu)),
+                #[cfg(feature = "glow")]
+                GfxBackendKind::Glow => glow
+                    .take()
+                    .map(|c| GlowBackend::try_new(window_backend, c).map(Self::Glow)),
+                GfxBackendKind::Soft => soft
+                    .take()
+                    .map(|c| SoftBackend::try_new(window_backend, c).map(Self::Soft)),
+                #[allow(unreachable_patterns)]
+                _ => {
+                    tracing::warn!("{kind:?} gfx backend is not enabled. skipping it");
+                    continue;
+                }
+            };
+            match result {
+                Some(Ok(backend)) => {
+                    tracing::info!("using {kind:?} gfx backend");
+                    return Ok(backend);
+                }
+                Some(Err(e)) => {
+                    tracing::warn!("failed to create {kind:?} gfx backend: {e}");
+                    last_error = e;
+                }
+                None => {}
+            }
+        }
+        Err(last_error)
+    }
+
+    fn suspend(&mut self, window_backend: &mut impl WindowBackend) {
+        dispatch_gfx!(self, backend => backend.suspend(window_backend))
+    }
+
+    fn resume(&mut self, window_backend: &mut impl WindowBackend) {
+        dispatch_gfx!(self, backend => backend.resume(window_backend))
+    }
+
+    fn resize_framebuffer(&mut self, window_backend: &mut impl WindowBackend) {
+        dispatch_gfx!(self, backend => backend.resize_framebuffer(window_backend))
+    }
+
+    fn prepare_frame(&mut self, window_backend: &mut impl WindowBackend) {
+        dispatch_gfx!(self, backend => backend.prepare_frame(window_backend))
+    }
+
+    fn render_egui(
+        &mut self,
+        meshes: Vec<ClippedPrimitive>,
+        textures_delta: TexturesDelta,
+        logical_screen_size: [f32; 2],
+    ) {
+        dispatch_gfx!(self, backend => backend.render_egui(meshes, textures_delta, logical_screen_size))
+    }
+
+    fn present(&mut self, window_backend: &mut impl WindowBackend) {
+        dispatch_gfx!(self, backend => backend.present(window_backend))
+    }
+
+    fn request_screenshot(&mut self) {
+        dispatch_gfx!(self, backend => backend.request_screenshot())
+    }
+
+    fn take_screenshot(&mut self) -> Option<egui::ColorImage> {
+        dispatch_gfx!(self, backend => backend.take_screenshot())
+    }
+}
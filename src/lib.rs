@@ -1 +1,137 @@
+//! `etk` ties a window backend, a gfx backend and an egui context together, so that you only need to write the gui code.
+//!
+//! ```rust,no_run
+//! use etk::{egui, egui_render_wgpu::WgpuBackend, egui_window_winit::WinitBackend};
+//!
+//! etk::run::<WinitBackend, WgpuBackend>(Default::default(), |ctx| {
+//!     egui::Window::new("hello").show(ctx, |ui| {
+//!         ui.label("world");
+//!     });
+//! })
+//! .expect("failed to create backends");
+//! ```
+//!
+//! If you need the backends inside the gui closure (eg: to set the window title or draw with the renderer),
+//! use [`run_with_backends`]. If you need more control (custom `run` fn, multiple windows etc..),
+//! implement [`UserApp`] for your own struct instead.
 
+pub mod any;
+
+pub use egui_backend;
+pub use egui_backend::egui;
+use egui_backend::{BackendConfig, BackendError, GfxBackend, UserApp, WindowBackend};
+#[cfg(feature = "glow")]
+pub use egui_render_glow;
+pub use egui_render_soft;
+pub use egui_render_wgpu;
+#[cfg(feature = "passthrough")]
+pub use egui_window_glfw_passthrough;
+pub use egui_window_headless;
+#[cfg(feature = "sdl2")]
+pub use egui_window_sdl2;
+pub use egui_window_winit;
+
+/// configuration of both backends used by [`run`].
+pub struct RunConfig<W: WindowBackend, G: GfxBackend> {
+    pub window_config: W::Configuration,
+    pub gfx_config: G::Configuration,
+    pub backend_config: BackendConfig,
+}
+
+impl<W: WindowBackend, G: GfxBackend> Default for RunConfig<W, G> {
+    fn default() -> Self {
+        Self {
+            window_config: Default::default(),
+            gfx_config: Default::default(),
+            backend_config: Default::default(),
+        }
+    }
+}
+
+/// the [`UserApp`] used by [`run`]. It owns the backends and egui context, and calls `gui` every frame.
+pub struct EtkApp<W, G, F> {
+    pub window_backend: W,
+    pub gfx_backend: G,
+    pub egui_context: egui::Context,
+    pub gui: F,
+}
+
+impl<W, G, F> EtkApp<W, G, F>
+where
+    W: WindowBackend,
+    G: GfxBackend,
+    F: FnMut(&egui::Context, &mut W, &mut G),
+{
+    /// creates the window backend and then the gfx backend for its window.
+    pub fn new(config: RunConfig<W, G>, gui: F) -> Result<Self, BackendError> {
+        let mut window_backend = W::try_new(config.window_config, config.backend_config)?;
+        let gfx_backend = G::try_new(&mut window_backend, config.gfx_config)?;
+        Ok(Self {
+            window_backend,
+            gfx_backend,
+            egui_context: Default::default(),
+            gui,
+        })
+    }
+}
+
+impl<W, G, F> UserApp for EtkApp<W, G, F>
+where
+    W: WindowBackend,
+    G: GfxBackend,
+    F: FnMut(&egui::Context, &mut W, &mut G),
+{
+    type UserGfxBackend = G;
+    type UserWindowBackend = W;
+
+    fn get_all(
+        &mut self,
+    ) -> (
+        &mut Self::UserWindowBackend,
+        &mut Self::UserGfxBackend,
+        &egui::Context,
+    ) {
+        (
+            &mut self.window_backend,
+            &mut self.gfx_backend,
+            &self.egui_context,
+        )
+    }
+
+    fn gui_run(&mut self) {
+        (self.gui)(
+            &self.egui_context,
+            &mut self.window_backend,
+            &mut self.gfx_backend,
+        );
+    }
+}
+
+/// creates the backends and runs the event loop, calling `gui` every frame.
+///
+/// returns an error if the backends can't be created. Otherwise, returns after the event loop exits.
+/// some window backends (eg: winit) never return and just exit the process.
+pub fn run<W, G>(
+    config: RunConfig<W, G>,
+    mut gui: impl FnMut(&egui::Context) + 'static,
+) -> Result<(), BackendError>
+where
+    W: WindowBackend + 'static,
+    G: GfxBackend + 'static,
+{
+    run_with_backends(config, move |ctx, _: &mut W, _: &mut G| gui(ctx))
+}
+
+/// same as [`run`], but `gui` also gets the window and gfx backends.
+pub fn run_with_backends<W, G>(
+    config: RunConfig<W, G>,
+    gui: impl FnMut(&egui::Context, &mut W, &mut G) + 'static,
+) -> Result<(), BackendError>
+where
+    W: WindowBackend + 'static,
+    G: GfxBackend + 'static,
+{
+    let app = EtkApp::new(config, gui)?;
+    W::run_event_loop(app);
+    Ok(())
+}
@@ -1,3 +1,13 @@
+use etk::{egui, egui_render_wgpu::WgpuBackend, egui_window_winit::WinitBackend};
+
 fn main() {
-    unimplemented!()
+    tracing_subscriber::fmt::init();
+    let mut frame_count = 0usize;
+    etk::run::<WinitBackend, WgpuBackend>(Default::default(), move |ctx| {
+        frame_count += 1;
+        egui::Window::new("etk").show(ctx, |ui| {
+            ui.label(format!("frame number: {frame_count}"));
+        });
+    })
+    .expect("failed to create backends");
 }